@@ -1,7 +1,10 @@
+mod report;
+
 use std::{
     collections::{HashMap, HashSet},
+    env, fs, process,
     sync::{
-        Arc,
+        Arc, OnceLock,
         atomic::{AtomicU64, AtomicUsize, Ordering},
     },
     thread,
@@ -9,8 +12,71 @@ use std::{
 };
 
 use nano64::*;
+use report::*;
+
+static FORMAT: OnceLock<ReportFormat> = OnceLock::new();
+
+// Human readable progress goes to stdout in text mode, and to stderr when a machine readable
+// report is requested so stdout only contains the report.
+macro_rules! say {
+    ($($arg:tt)*) => {
+        if *FORMAT.get().unwrap_or(&ReportFormat::Text) == ReportFormat::Text {
+            println!($($arg)*);
+        } else {
+            eprintln!($($arg)*);
+        }
+    };
+}
+
+struct Args {
+    format: ReportFormat,
+    output: Option<String>,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut args = Args {
+        format: ReportFormat::Text,
+        output: None,
+    };
+    let mut iter = env::args().skip(1);
+    while let Some(arg) = iter.next() {
+        let (flag, inline) = match arg.split_once('=') {
+            Some((flag, value)) => (flag.to_string(), Some(value.to_string())),
+            None => (arg.clone(), None),
+        };
+        let mut value = || {
+            inline
+                .clone()
+                .or_else(|| iter.next())
+                .ok_or(format!("{flag} requires a value"))
+        };
+        match flag.as_str() {
+            "--format" => args.format = value()?.parse()?,
+            "--output" | "-o" => args.output = Some(value()?),
+            "--help" | "-h" => {
+                println!(
+                    "Usage: nano64_collision_benchmark [--format text|json|csv] [--output <path>]"
+                );
+                process::exit(0);
+            }
+            other => return Err(format!("unknown argument '{other}'")),
+        }
+    }
+    // Text mode only prints progress; there is no report to write.
+    if args.format == ReportFormat::Text && args.output.is_some() {
+        return Err("--output requires --format json or --format csv".into());
+    }
+    Ok(args)
+}
 
 fn main() {
+    let args = parse_args().unwrap_or_else(|e| {
+        eprintln!("{e}");
+        process::exit(2);
+    });
+    FORMAT.set(args.format).expect("format is only set once");
+    let mut report = BenchReport::default();
+
     let high_speed_count = 5_000_000;
 
     let max_throughput_duration = Duration::from_millis(1000);
@@ -31,73 +97,103 @@ fn main() {
     let concurrent_generation_as_fast_as_possible_num_threads_no_collision_tracking = 100;
 
     /********************** High Speed Generation **********************/
-    println!(
+    say!(
         "\nTesting high speed generation: Generating {} IDs as fast as possible.",
         with_commas(high_speed_count)
     );
-    test_high_speed_generation(high_speed_count);
+    report
+        .scenarios
+        .push(test_high_speed_generation(high_speed_count));
 
     /********************* Concurrent Generation (uncoordinated threads) ***********************/
-    println!(
+    say!(
         "\nTesting concurrent generation : {} IDs over {concurrent_generation_uncoordinated_threads_num_threads} threads (uncoordinated threads)",
         with_commas(concurrent_generation_uncoordinated_threads_count)
     );
-    test_concurrent_generation_uncoordinated_threads(
-        concurrent_generation_uncoordinated_threads_count,
-        concurrent_generation_uncoordinated_threads_num_threads,
-    );
+    report
+        .scenarios
+        .push(test_concurrent_generation_uncoordinated_threads(
+            concurrent_generation_uncoordinated_threads_count,
+            concurrent_generation_uncoordinated_threads_num_threads,
+        ));
 
     /********************* Concurrent Generation (WITH coordinated threads) ***********************/
-    println!(
+    say!(
         "\nTesting concurrent generation : {} IDs over {concurrent_generation_with_coordinated_threads_num_threads} threads (WITH coordinated threads)",
         with_commas(concurrent_generation_with_coordinated_threads_count)
     );
-    println!(
+    say!(
         "  Note: Threads are coordinated, which means collisions rates should see a significant drop vs uncoordinated."
     );
-    test_concurrent_generation_with_coordinated_threads(
-        concurrent_generation_with_coordinated_threads_count,
-        concurrent_generation_with_coordinated_threads_num_threads,
-    );
+    report
+        .scenarios
+        .push(test_concurrent_generation_with_coordinated_threads(
+            concurrent_generation_with_coordinated_threads_count,
+            concurrent_generation_with_coordinated_threads_num_threads,
+        ));
 
     /********* Concurrent Generation (as fast as possible, track collisions) **************/
-    println!(
+    say!(
         "\nTesting concurrent generation : {} IDs over {concurrent_generation_as_fast_as_possible_num_threads} threads as fast as possible (track collisions)",
         with_commas(concurrent_generation_as_fast_as_possible_count)
     );
-    test_concurrent_generation_generate_ids_as_fast_as_possible(
-        concurrent_generation_as_fast_as_possible_count,
-        concurrent_generation_as_fast_as_possible_num_threads,
-    );
+    report
+        .scenarios
+        .push(test_concurrent_generation_generate_ids_as_fast_as_possible(
+            "concurrent_fast_tracked",
+            concurrent_generation_as_fast_as_possible_count,
+            concurrent_generation_as_fast_as_possible_num_threads,
+        ));
 
     /********* Concurrent Generation (as fast as possible, NO collision tracking) **************/
-    println!(
+    say!(
         "\nTesting concurrent generation : {} IDs over {concurrent_generation_as_fast_as_possible_num_threads_no_collision_tracking} threads as fast as possible (NO collision tracking)",
         with_commas(concurrent_generation_as_fast_as_possible_count_no_collision_tracking)
     );
-    test_concurrent_generation_generate_ids_as_fast_as_possible_without_counting_collisions(
-        concurrent_generation_as_fast_as_possible_count_no_collision_tracking,
-        concurrent_generation_as_fast_as_possible_num_threads_no_collision_tracking,
+    report.scenarios.push(
+        test_concurrent_generation_generate_ids_as_fast_as_possible_without_counting_collisions(
+            concurrent_generation_as_fast_as_possible_count_no_collision_tracking,
+            concurrent_generation_as_fast_as_possible_num_threads_no_collision_tracking,
+        ),
     );
 
     /************************** Sustained Rate *************************/
-    println!(
+    say!(
         "\nTesting sustained rate: {} IDs/sec for {sustained_rate_duration:?}",
         with_commas(sustained_rate_count)
     );
-    test_sustained_rate(sustained_rate_count, sustained_rate_duration);
+    report.scenarios.push(test_sustained_rate(
+        sustained_rate_count,
+        sustained_rate_duration,
+    ));
 
     /************************* Max Throughput **************************/
-    println!("\nTesting max throughput [{max_throughput_duration:?} burst]:");
-    let max_throughput_result = test_max_throughput(max_throughput_duration);
+    say!("\nTesting max throughput [{max_throughput_duration:?} burst]:");
+    let (max_throughput_report, max_per_ms, max_collisions) =
+        test_max_throughput(max_throughput_duration);
+    report.scenarios.push(max_throughput_report);
 
     /*********************** Print analysis ************************/
-    analyze_peak_ms(max_throughput_result.0, max_throughput_result.1);
+    report.peak = Some(analyze_peak_ms(max_per_ms, max_collisions));
+
+    /*********************** Write report ************************/
+    let rendered = match args.format {
+        ReportFormat::Text => return,
+        ReportFormat::Json => report.to_json(),
+        ReportFormat::Csv => report.to_csv(),
+    };
+    match args.output {
+        Some(path) => fs::write(&path, rendered).unwrap_or_else(|e| {
+            eprintln!("failed to write report to {path}: {e}");
+            process::exit(1);
+        }),
+        None => println!("{rendered}"),
+    }
 }
 
-fn analyze_peak_ms(max_per_ms: u64, max_collisions: u64) {
-    println!("\n======= Analysis of peak MS (from [max throughput test]) =========");
-    println!("  At peak rate of {} IDs/ms", with_commas(max_per_ms));
+fn analyze_peak_ms(max_per_ms: u64, max_collisions: u64) -> PeakAnalysis {
+    say!("\n======= Analysis of peak MS (from [max throughput test]) =========");
+    say!("  At peak rate of {} IDs/ms", with_commas(max_per_ms));
 
     const RANDOM_BITS: u32 = 20;
     #[allow(non_snake_case)]
@@ -113,29 +209,37 @@ fn analyze_peak_ms(max_per_ms: u64, max_collisions: u64) {
     // 1% collision probability threshold
     let safe_rate = (2.0 * R * 0.01).sqrt();
 
-    println!("    • Expected collisions: {expected_collisions:.2}");
-    println!(
+    say!("    • Expected collisions: {expected_collisions:.2}");
+    say!(
         "    • Actual collisions observed: {}",
         with_commas(max_collisions)
     );
     if expected_collisions > 0.0 {
-        println!(
+        say!(
             "    • Observed/expected ratio: {:.1}x",
             max_collisions as f64 / expected_collisions
         );
     }
-    println!(
+    say!(
         "    • This is {:.1}× the safe rate (~{} IDs/ms for 1% risk)",
         n / safe_rate,
         with_commas(format!("{safe_rate:.2}"))
     );
-    println!(
+    say!(
         "    • Probability of at least one collision: {:.2}%",
         prob_at_least_one * 100.0
     );
+
+    PeakAnalysis {
+        max_per_ms,
+        observed_collisions: max_collisions,
+        expected_collisions,
+        probability_at_least_one: prob_at_least_one,
+        safe_rate_per_ms: safe_rate,
+    }
 }
 
-fn test_high_speed_generation(count: u64) {
+fn test_high_speed_generation(count: u64) -> ScenarioReport {
     let mut seen = HashSet::<u64>::new();
     let mut collisions = 0;
     let start = Instant::now();
@@ -153,21 +257,32 @@ fn test_high_speed_generation(count: u64) {
     let unique_ids = seen.len() as f64;
     let collision_prob = collisions as f64 / unique_ids * 100.0;
 
-    println!("  Generated {} IDs", with_commas(count));
-    println!(
+    say!("  Generated {} IDs", with_commas(count));
+    say!(
         "  Duration: {:.6}ms",
         with_commas(start.elapsed().as_millis())
     );
-    println!("  Rate: {} IDs/Second", with_commas(rate));
-    println!("  Collisions: {}", with_commas(collisions));
-    println!(
+    say!("  Rate: {} IDs/Second", with_commas(rate));
+    say!("  Collisions: {}", with_commas(collisions));
+    say!(
         "  Collision probability: {:.6}%",
         with_commas(collision_prob)
     );
+
+    ScenarioReport {
+        name: "high_speed",
+        threads: 1,
+        generated: count,
+        unique: Some(seen.len() as u64),
+        collisions: Some(collisions as u64),
+        duration_ms: elapsed * 1000.0,
+        rate_per_sec: count as f64 / elapsed,
+        max_per_ms: None,
+    }
 }
 
 // Will try to rate limit to `target_rate` id's per second, over `duration` time period.
-fn test_sustained_rate(target_rate: u64, duration: Duration) {
+fn test_sustained_rate(target_rate: u64, duration: Duration) -> ScenarioReport {
     let mut seen = HashSet::<u64>::new();
     let mut collisions = 0;
     let mut ms_stats = HashMap::<u64, u64>::new();
@@ -207,21 +322,32 @@ fn test_sustained_rate(target_rate: u64, duration: Duration) {
     let total_generated = seen.len() + collisions;
     let actual_rate = format!("{:.2}", total_generated as f64 / elapsed.as_secs_f64());
 
-    println!("  Target Rate: {} IDs/s", with_commas(target_rate));
-    println!("  Duration: {duration:?}");
-    println!("  Generated: {}", with_commas(total_generated));
-    println!("  Actual Rate: {} IDs/s", with_commas(actual_rate));
-    println!(
+    say!("  Target Rate: {} IDs/s", with_commas(target_rate));
+    say!("  Duration: {duration:?}");
+    say!("  Generated: {}", with_commas(total_generated));
+    say!("  Actual Rate: {} IDs/s", with_commas(actual_rate));
+    say!(
         "  Collisions: {} ({:.6}%)",
         with_commas(collisions),
         with_commas(collisions as f64 / total_generated as f64 * 100.0)
     );
-    println!("  Unique IDs: {}", with_commas(seen.len()));
-    println!("  Max IDs in a single ms: {}", with_commas(max_per_ms));
-    println!("  Milliseconds with IDs: {}", with_commas(ms_stats.len()));
+    say!("  Unique IDs: {}", with_commas(seen.len()));
+    say!("  Max IDs in a single ms: {}", with_commas(max_per_ms));
+    say!("  Milliseconds with IDs: {}", with_commas(ms_stats.len()));
+
+    ScenarioReport {
+        name: "sustained_rate",
+        threads: 1,
+        generated: total_generated as u64,
+        unique: Some(seen.len() as u64),
+        collisions: Some(collisions as u64),
+        duration_ms: elapsed.as_secs_f64() * 1000.0,
+        rate_per_sec: total_generated as f64 / elapsed.as_secs_f64(),
+        max_per_ms: Some(max_per_ms),
+    }
 }
 
-fn test_max_throughput(duration: Duration) -> (u64, u64) {
+fn test_max_throughput(duration: Duration) -> (ScenarioReport, u64, u64) {
     let mut seen = HashSet::<u64>::new();
     let mut collisions = 0;
     let mut ids_per_ms = HashMap::<u64, u64>::new();
@@ -272,14 +398,14 @@ fn test_max_throughput(duration: Duration) -> (u64, u64) {
     let collision_prob = collisions as f64 / (seen.len() as f64) * 100.0;
     let rate = format!("{:.2}", total_generated_ids as f64 / elapsed.as_secs_f64());
 
-    println!("  Duration : {:.6}ms", with_commas(elapsed.as_millis()));
-    println!("  Rate : {} IDs/ms", with_commas(rate));
-    println!(
+    say!("  Duration : {:.6}ms", with_commas(elapsed.as_millis()));
+    say!("  Rate : {} IDs/ms", with_commas(rate));
+    say!(
         "  Total Generated IDs : {}",
         with_commas(total_generated_ids)
     );
-    println!("  Unique IDs : {}", with_commas(seen.len()));
-    println!(
+    say!("  Unique IDs : {}", with_commas(seen.len()));
+    say!(
         "  Collisions : {} ({:.6}%)",
         with_commas(collisions),
         with_commas(collision_prob)
@@ -288,30 +414,45 @@ fn test_max_throughput(duration: Duration) -> (u64, u64) {
     let ts_most_ids_timestamp = timestamp_with_most_ids.0;
     let ts_most_ids_count = with_commas(timestamp_with_most_ids.1);
 
-    println!(
+    say!(
         "  Most IDs in a single ms : \n\tIDs Created : {ts_most_ids_count},\n\tTimestamp : {ts_most_ids_timestamp},\n\tCollisions : {timestamp_with_most_ids_collisions}"
     );
 
     let ts_fewest_ids_timestamp = timestamp_with_fewest_ids.0;
     let ts_fewest_ids_count = with_commas(timestamp_with_fewest_ids.1);
 
-    println!(
+    say!(
         "  Fewest IDs in a single ms :\n\tIDs Created : {ts_fewest_ids_count},\n\tTimestamp : {ts_fewest_ids_timestamp}"
     );
-    println!(
+    say!(
         "  Most collisions in a single ms :\n\tCollisions : {},\n\tTimestamp : {},\n\tIDs Created : {}",
         with_commas(max_collision.1),
         max_collision.0,
         with_commas(ids_per_ms.get(max_collision.0).unwrap()),
     );
 
+    let report = ScenarioReport {
+        name: "max_throughput",
+        threads: 1,
+        generated: total_generated_ids as u64,
+        unique: Some(seen.len() as u64),
+        collisions: Some(collisions as u64),
+        duration_ms: elapsed.as_secs_f64() * 1000.0,
+        rate_per_sec: total_generated_ids as f64 / elapsed.as_secs_f64(),
+        max_per_ms: Some(*timestamp_with_most_ids.1),
+    };
+
     (
+        report,
         *timestamp_with_most_ids.1,
         *timestamp_with_most_ids_collisions, /* max_collision.1 */
     )
 }
 
-fn test_concurrent_generation_uncoordinated_threads(total_count: u64, num_threads: usize) {
+fn test_concurrent_generation_uncoordinated_threads(
+    total_count: u64,
+    num_threads: usize,
+) -> ScenarioReport {
     let count_per_thread = total_count / num_threads as u64;
     let mut handles = Vec::with_capacity(num_threads);
 
@@ -356,26 +497,48 @@ fn test_concurrent_generation_uncoordinated_threads(total_count: u64, num_thread
     let total_generated = unique_count as u64 + global_collisions;
     let rate = total_generated as f64 / elapsed.as_secs_f64();
 
-    println!(
+    say!(
         "  Note: Threads are not coordinated, which means collision rate should increase dramatically.\n  Note: More threads = more collisions."
     );
-    println!("  Generated: {} IDs", with_commas(total_generated));
-    println!("  Threads: {num_threads}");
-    println!("  Duration: {}ms", with_commas(elapsed_ms));
-    println!("  Rate: {} IDs/sec", with_commas(format!("{rate:.2}")));
-    println!(
+    say!("  Generated: {} IDs", with_commas(total_generated));
+    say!("  Threads: {num_threads}");
+    say!("  Duration: {}ms", with_commas(elapsed_ms));
+    say!("  Rate: {} IDs/sec", with_commas(format!("{rate:.2}")));
+    say!(
         "  Collisions: {} ({:.6}%)",
         with_commas(global_collisions),
         with_commas(global_collisions as f64 / total_count as f64 * 100.0)
     );
-    println!("  Unique IDs: {}", with_commas(unique_count));
+    say!("  Unique IDs: {}", with_commas(unique_count));
+
+    ScenarioReport {
+        name: "concurrent_uncoordinated",
+        threads: num_threads,
+        generated: total_generated,
+        unique: Some(unique_count as u64),
+        collisions: Some(global_collisions),
+        duration_ms: elapsed.as_secs_f64() * 1000.0,
+        rate_per_sec: rate,
+        max_per_ms: None,
+    }
 }
 
-fn test_concurrent_generation_with_coordinated_threads(total_count: u64, num_threads: usize) {
-    test_concurrent_generation_generate_ids_as_fast_as_possible(total_count, num_threads);
+fn test_concurrent_generation_with_coordinated_threads(
+    total_count: u64,
+    num_threads: usize,
+) -> ScenarioReport {
+    test_concurrent_generation_generate_ids_as_fast_as_possible(
+        "concurrent_coordinated",
+        total_count,
+        num_threads,
+    )
 }
 
-fn test_concurrent_generation_generate_ids_as_fast_as_possible(total_ids: u64, num_threads: usize) {
+fn test_concurrent_generation_generate_ids_as_fast_as_possible(
+    name: &'static str,
+    total_ids: u64,
+    num_threads: usize,
+) -> ScenarioReport {
     let counter = Arc::new(AtomicUsize::new(0));
     let collisions = Arc::new(AtomicU64::new(0));
     let mut handles = Vec::new();
@@ -420,21 +583,32 @@ fn test_concurrent_generation_generate_ids_as_fast_as_possible(total_ids: u64, n
     let elapsed = start.elapsed();
     let rate = format!("{:.2}", total_ids as f64 / elapsed.as_secs_f64());
 
-    println!("  Threads: {num_threads}");
-    println!("  Generated: {}", with_commas(total_generated));
-    println!("  Duration: {}ms", with_commas(elapsed.as_millis()));
-    println!(
+    say!("  Threads: {num_threads}");
+    say!("  Generated: {}", with_commas(total_generated));
+    say!("  Duration: {}ms", with_commas(elapsed.as_millis()));
+    say!(
         "  Collisions: {} ({:.6}%)",
         with_commas(collisions.load(Ordering::Relaxed)),
         with_commas(collisions.load(Ordering::Relaxed) as f64 / total_ids as f64 * 100.0)
     );
-    println!("  Rate: {} IDs/sec", with_commas(rate));
+    say!("  Rate: {} IDs/sec", with_commas(rate));
+
+    ScenarioReport {
+        name,
+        threads: num_threads,
+        generated: total_ids,
+        unique: Some(global_seen.len() as u64),
+        collisions: Some(collisions.load(Ordering::Relaxed)),
+        duration_ms: elapsed.as_secs_f64() * 1000.0,
+        rate_per_sec: total_ids as f64 / elapsed.as_secs_f64(),
+        max_per_ms: None,
+    }
 }
 
 fn test_concurrent_generation_generate_ids_as_fast_as_possible_without_counting_collisions(
     total_ids: u64,
     num_threads: usize,
-) {
+) -> ScenarioReport {
     let work_per_thread = total_ids / num_threads as u64;
     let mut handles = Vec::new();
     let start = Instant::now();
@@ -459,13 +633,24 @@ fn test_concurrent_generation_generate_ids_as_fast_as_possible_without_counting_
     let elapsed_ms = format!("{:.3?}", elapsed.as_millis());
     let rate = format!("{:.2}", total_generated as f64 / elapsed.as_secs_f64());
 
-    println!(
+    say!(
         "  Notes: each thread stores it's own count, which is merged once all threads have completed."
     );
-    println!("  Threads: {num_threads}");
-    println!("  Generated: {}", with_commas(total_generated));
-    println!("  Duration: {}ms", with_commas(elapsed_ms));
-    println!("  Rate: {} IDs/sec", with_commas(rate));
+    say!("  Threads: {num_threads}");
+    say!("  Generated: {}", with_commas(total_generated));
+    say!("  Duration: {}ms", with_commas(elapsed_ms));
+    say!("  Rate: {} IDs/sec", with_commas(rate));
+
+    ScenarioReport {
+        name: "concurrent_fast_untracked",
+        threads: num_threads,
+        generated: total_generated,
+        unique: None,
+        collisions: None,
+        duration_ms: elapsed.as_secs_f64() * 1000.0,
+        rate_per_sec: total_generated as f64 / elapsed.as_secs_f64(),
+        max_per_ms: None,
+    }
 }
 
 fn with_commas<T: ToString>(value: T) -> String {
@@ -0,0 +1,130 @@
+use std::{fmt::Write, str::FromStr};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReportFormat {
+    Text,
+    Json,
+    Csv,
+}
+
+impl FromStr for ReportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "text" => Ok(ReportFormat::Text),
+            "json" => Ok(ReportFormat::Json),
+            "csv" => Ok(ReportFormat::Csv),
+            other => Err(format!(
+                "unknown format '{other}', expected text, json or csv"
+            )),
+        }
+    }
+}
+
+// Result of a single benchmark scenario.
+#[derive(Clone, Debug)]
+pub struct ScenarioReport {
+    pub name: &'static str,
+    pub threads: usize,
+    pub generated: u64,
+    pub unique: Option<u64>,
+    pub collisions: Option<u64>,
+    pub duration_ms: f64,
+    pub rate_per_sec: f64,
+    pub max_per_ms: Option<u64>,
+}
+
+// Birthday-bound analysis of the busiest millisecond of the max throughput scenario.
+#[derive(Clone, Debug)]
+pub struct PeakAnalysis {
+    pub max_per_ms: u64,
+    pub observed_collisions: u64,
+    pub expected_collisions: f64,
+    pub probability_at_least_one: f64,
+    pub safe_rate_per_ms: f64,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct BenchReport {
+    pub scenarios: Vec<ScenarioReport>,
+    pub peak: Option<PeakAnalysis>,
+}
+
+impl BenchReport {
+    pub fn to_json(&self) -> String {
+        let mut out = String::new();
+        out.push_str("{\n");
+        let _ = writeln!(out, "  \"version\": \"{}\",", env!("CARGO_PKG_VERSION"));
+        out.push_str("  \"scenarios\": [");
+        for (i, s) in self.scenarios.iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            let _ = write!(
+                out,
+                "\n    {{\"name\": \"{}\", \"threads\": {}, \"generated\": {}, \"unique\": {}, \"collisions\": {}, \"duration_ms\": {:.3}, \"rate_per_sec\": {:.2}, \"max_per_ms\": {}}}",
+                s.name,
+                s.threads,
+                s.generated,
+                json_opt(s.unique),
+                json_opt(s.collisions),
+                s.duration_ms,
+                s.rate_per_sec,
+                json_opt(s.max_per_ms),
+            );
+        }
+        out.push_str("\n  ],\n");
+        match &self.peak {
+            Some(p) => {
+                let _ = writeln!(
+                    out,
+                    "  \"peak\": {{\"max_per_ms\": {}, \"observed_collisions\": {}, \"expected_collisions\": {:.4}, \"probability_at_least_one\": {:.6}, \"safe_rate_per_ms\": {:.2}}}",
+                    p.max_per_ms,
+                    p.observed_collisions,
+                    p.expected_collisions,
+                    p.probability_at_least_one,
+                    p.safe_rate_per_ms,
+                );
+            }
+            None => out.push_str("  \"peak\": null\n"),
+        }
+        out.push('}');
+        out
+    }
+
+    // One row per scenario. Peak analysis is appended to the `max_throughput` row.
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from(
+            "name,threads,generated,unique,collisions,duration_ms,rate_per_sec,max_per_ms,expected_peak_collisions,peak_collision_probability\n",
+        );
+        for s in &self.scenarios {
+            let peak = self.peak.as_ref().filter(|_| s.name == "max_throughput");
+            let _ = writeln!(
+                out,
+                "{},{},{},{},{},{:.3},{:.2},{},{},{}",
+                s.name,
+                s.threads,
+                s.generated,
+                csv_opt(s.unique),
+                csv_opt(s.collisions),
+                s.duration_ms,
+                s.rate_per_sec,
+                csv_opt(s.max_per_ms),
+                peak.map(|p| format!("{:.4}", p.expected_collisions))
+                    .unwrap_or_default(),
+                peak.map(|p| format!("{:.6}", p.probability_at_least_one))
+                    .unwrap_or_default(),
+            );
+        }
+        out
+    }
+}
+
+fn json_opt(value: Option<u64>) -> String {
+    value.map_or_else(|| "null".to_string(), |v| v.to_string())
+}
+
+fn csv_opt(value: Option<u64>) -> String {
+    value.map(|v| v.to_string()).unwrap_or_default()
+}
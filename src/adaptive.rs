@@ -0,0 +1,285 @@
+use crate::{
+    MAX_TIMESTAMP, Nano64, Nano64Error, RANDOM_BITS, RANDOM_MASK, RandomNumberGeneratorImpl,
+    TIMESTAMP_SHIFT, default_rng, time_now_since_epoch_ms,
+};
+use std::sync::Mutex;
+
+// Options for `AdaptiveSequencer`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AdaptiveOptions {
+    // How many windows in a row must exhaust their sequence space before another low timestamp
+    // bit is borrowed (and, symmetrically, how many calm windows before one is given back).
+    pub exhaustion_threshold: u32,
+    // Upper bound on the number of low timestamp bits that may be borrowed.
+    pub max_borrowed_bits: u32,
+}
+
+impl Default for AdaptiveOptions {
+    fn default() -> Self {
+        Self {
+            exhaustion_threshold: 3,
+            max_borrowed_bits: 4,
+        }
+    }
+}
+
+// Reported to the hook whenever the sequencer changes how many timestamp bits it borrows.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AdaptiveEvent {
+    // Sequence space was extended; `borrowed_bits` low timestamp bits are now used as sequence.
+    Degraded { borrowed_bits: u32 },
+    // Sequence space was shrunk but some bits are still borrowed.
+    Relaxed { borrowed_bits: u32 },
+    // All borrowed bits were given back; IDs carry full millisecond precision again.
+    Recovered,
+}
+
+pub type AdaptiveHookImpl = fn(event: AdaptiveEvent);
+
+// Point-in-time snapshot of an `AdaptiveSequencer`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AdaptiveStats {
+    pub borrowed_bits: u32,
+    pub degraded: bool,
+    pub total_exhaustions: u64,
+}
+
+struct AdaptiveState {
+    last_value: u64,
+    borrowed_bits: u32,
+    exhausted_windows: u32,
+    calm_windows: u32,
+    window_exhausted: bool,
+    total_exhaustions: u64,
+}
+
+// Monotonic generator that, when the 20-bit random space keeps running out, temporarily treats
+// the lowest timestamp bits as additional sequence space.
+//
+// With `k` borrowed bits, IDs generated within a 2^k ms window all carry the timestamp of the
+// start of that window and share a (20 + k)-bit sequence. Ordering stays strict. Timestamps
+// normally lag the wall clock by up to 2^k - 1 ms, but once a window's sequence runs out the
+// increment carries into the next window, and IDs run ahead of the clock until it catches up.
+pub struct AdaptiveSequencer {
+    options: AdaptiveOptions,
+    rng: RandomNumberGeneratorImpl,
+    hook: Option<AdaptiveHookImpl>,
+    state: Mutex<AdaptiveState>,
+}
+
+impl AdaptiveSequencer {
+    pub fn new(
+        options: AdaptiveOptions,
+        rng: Option<RandomNumberGeneratorImpl>,
+    ) -> Result<Self, Nano64Error> {
+        if options.max_borrowed_bits > 16 {
+            return Err(Nano64Error::Error(format!(
+                "max_borrowed_bits must be 0-16, got {}",
+                options.max_borrowed_bits
+            )));
+        }
+
+        let rng = if let Some(_rng) = rng {
+            _rng
+        } else {
+            default_rng
+        };
+
+        Ok(Self {
+            options,
+            rng,
+            hook: None,
+            state: Mutex::new(AdaptiveState {
+                last_value: 0,
+                borrowed_bits: 0,
+                exhausted_windows: 0,
+                calm_windows: 0,
+                window_exhausted: false,
+                total_exhaustions: 0,
+            }),
+        })
+    }
+
+    pub fn with_hook(mut self, hook: AdaptiveHookImpl) -> Self {
+        self.hook = Some(hook);
+        self
+    }
+
+    pub fn generate_now(&self) -> Result<Nano64, Nano64Error> {
+        self.generate(time_now_since_epoch_ms())
+    }
+
+    pub fn generate(&self, timestamp: u64) -> Result<Nano64, Nano64Error> {
+        if timestamp > MAX_TIMESTAMP {
+            return Err(Nano64Error::TimeStampExceedsBitRange(timestamp));
+        }
+
+        let mut state = self
+            .state
+            .lock()
+            .map_err(|_| Nano64Error::Error("Error unlocking state".into()))?;
+
+        let window_start = timestamp & !((1u64 << state.borrowed_bits) - 1);
+        let fresh = window_start << TIMESTAMP_SHIFT;
+
+        if fresh > state.last_value {
+            // A new window began; decide whether the previous one was calm.
+            if state.window_exhausted {
+                state.window_exhausted = false;
+                state.calm_windows = 0;
+            } else {
+                state.exhausted_windows = 0;
+                state.calm_windows += 1;
+                if state.borrowed_bits > 0
+                    && state.calm_windows >= self.options.exhaustion_threshold
+                {
+                    state.calm_windows = 0;
+                    state.borrowed_bits -= 1;
+                    self.notify(if state.borrowed_bits == 0 {
+                        AdaptiveEvent::Recovered
+                    } else {
+                        AdaptiveEvent::Relaxed {
+                            borrowed_bits: state.borrowed_bits,
+                        }
+                    });
+                }
+            }
+
+            let random = (self.rng)(RANDOM_BITS as u32)? as u64 & RANDOM_MASK;
+            state.last_value = fresh | random;
+            return Ok(Nano64::new(state.last_value));
+        }
+
+        if state.last_value == u64::MAX {
+            return Err(Nano64Error::Error(
+                "timestamp overflow after incrementing for adaptive generation".into(),
+            ));
+        }
+
+        let value = state.last_value + 1;
+        let last_window_start =
+            (state.last_value >> TIMESTAMP_SHIFT) & !((1u64 << state.borrowed_bits) - 1);
+        let window_end = last_window_start + (1u64 << state.borrowed_bits);
+        if (value >> TIMESTAMP_SHIFT) >= window_end {
+            // Sequence space of the current window ran out.
+            state.total_exhaustions += 1;
+            state.window_exhausted = true;
+            state.exhausted_windows += 1;
+            if state.exhausted_windows >= self.options.exhaustion_threshold
+                && state.borrowed_bits < self.options.max_borrowed_bits
+            {
+                state.exhausted_windows = 0;
+                state.borrowed_bits += 1;
+                self.notify(AdaptiveEvent::Degraded {
+                    borrowed_bits: state.borrowed_bits,
+                });
+            }
+        }
+
+        state.last_value = value;
+        Ok(Nano64::new(value))
+    }
+
+    pub fn stats(&self) -> AdaptiveStats {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        AdaptiveStats {
+            borrowed_bits: state.borrowed_bits,
+            degraded: state.borrowed_bits > 0,
+            total_exhaustions: state.total_exhaustions,
+        }
+    }
+
+    fn notify(&self, event: AdaptiveEvent) {
+        if let Some(hook) = self.hook {
+            hook(event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use crate::{AdaptiveEvent, AdaptiveOptions, AdaptiveSequencer, Nano64Error, RANDOM_MASK};
+
+    fn max_rng(_bits: u32) -> Result<u32, Nano64Error> {
+        Ok(RANDOM_MASK as u32)
+    }
+
+    #[test]
+    fn test_adaptive_strictly_increasing() {
+        let sequencer = AdaptiveSequencer::new(AdaptiveOptions::default(), None).unwrap();
+        let mut last = 0;
+        for i in 0..10_000 {
+            let id = sequencer.generate(1_000_000 + i / 100).unwrap();
+            assert!(id.u64_value() > last, "IDs not strictly increasing");
+            last = id.u64_value();
+        }
+    }
+
+    #[test]
+    fn test_adaptive_escalates_and_recovers() {
+        static DEGRADED: AtomicU32 = AtomicU32::new(0);
+        static RECOVERED: AtomicU32 = AtomicU32::new(0);
+        fn hook(event: AdaptiveEvent) {
+            match event {
+                AdaptiveEvent::Degraded { .. } => DEGRADED.fetch_add(1, Ordering::SeqCst),
+                AdaptiveEvent::Recovered => RECOVERED.fetch_add(1, Ordering::SeqCst),
+                AdaptiveEvent::Relaxed { .. } => 0,
+            };
+        }
+
+        let options = AdaptiveOptions {
+            exhaustion_threshold: 1,
+            max_borrowed_bits: 2,
+        };
+        let sequencer = AdaptiveSequencer::new(options, Some(max_rng))
+            .unwrap()
+            .with_hook(hook);
+
+        // Random starts at the top of the range, so a second ID in the same ms exhausts it.
+        sequencer.generate(1000).unwrap();
+        sequencer.generate(1000).unwrap();
+        let stats = sequencer.stats();
+        assert!(stats.degraded);
+        assert_eq!(stats.borrowed_bits, 1);
+        assert_eq!(stats.total_exhaustions, 1);
+        assert_eq!(DEGRADED.load(Ordering::SeqCst), 1);
+
+        // With a borrowed bit the sequence may spill into the low timestamp bit of the window.
+        let id = sequencer.generate(1001).unwrap();
+        assert_eq!(id.get_timestamp(), 1001);
+        assert_eq!(sequencer.stats().total_exhaustions, 1);
+
+        // Calm windows give the borrowed bit back.
+        sequencer.generate(1010).unwrap();
+        sequencer.generate(1020).unwrap();
+        assert!(!sequencer.stats().degraded);
+        assert_eq!(RECOVERED.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_adaptive_borrowed_bits_coarsen_timestamp() {
+        let options = AdaptiveOptions {
+            exhaustion_threshold: 1,
+            max_borrowed_bits: 3,
+        };
+        let sequencer = AdaptiveSequencer::new(options, Some(max_rng)).unwrap();
+        sequencer.generate(1024).unwrap();
+        sequencer.generate(1024).unwrap();
+        sequencer.generate(1025).unwrap();
+        assert_eq!(sequencer.stats().borrowed_bits, 1);
+        // 1027 falls into the [1026, 1028) window, so the ID is stamped with the window start.
+        let id = sequencer.generate(1027).unwrap();
+        assert_eq!(id.get_timestamp(), 1026);
+    }
+
+    #[test]
+    fn test_adaptive_rejects_invalid_options() {
+        let options = AdaptiveOptions {
+            exhaustion_threshold: 1,
+            max_borrowed_bits: 17,
+        };
+        assert!(AdaptiveSequencer::new(options, None).is_err());
+    }
+}
@@ -0,0 +1,26 @@
+// The AES-GCM variants the built-in `AesGcm` backend supports. The payload format is the same
+// for both; only the key length differs. Available without the `encryption` feature so that
+// `Nano64Error` has the same variants either way.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum GcmAlgorithm {
+    // For key-management systems that only issue 128-bit keys.
+    Aes128,
+    #[default]
+    Aes256,
+}
+
+impl GcmAlgorithm {
+    pub const fn key_length(&self) -> usize {
+        match self {
+            GcmAlgorithm::Aes128 => 16,
+            GcmAlgorithm::Aes256 => 32,
+        }
+    }
+
+    pub const fn name(&self) -> &'static str {
+        match self {
+            GcmAlgorithm::Aes128 => "AES-128-GCM",
+            GcmAlgorithm::Aes256 => "AES-256-GCM",
+        }
+    }
+}
@@ -0,0 +1,177 @@
+use crate::{ExhaustionPolicy, Nano64, Nano64Error, Nano64Generator};
+use futures_core::Stream;
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll, ready},
+    time::Duration,
+};
+use tokio::time::Sleep;
+
+// A `Nano64Generator` for async code. When a millisecond's random field is exhausted, monotonic
+// generation yields to the runtime until the next millisecond instead of spinning or borrowing
+// a future timestamp, so bursts get backpressure.
+//
+// `ClockBackwardsPolicy::WaitUntilCaughtUp` still blocks the calling thread; prefer
+// `ClampToLast` or `Error` for generators used here.
+pub struct AsyncNano64Generator {
+    inner: Nano64Generator,
+}
+
+impl Default for AsyncNano64Generator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Wraps a configured generator. Its exhaustion policy is replaced, since waiting is what this
+// type is for.
+impl From<Nano64Generator> for AsyncNano64Generator {
+    fn from(generator: Nano64Generator) -> Self {
+        Self {
+            inner: generator.with_exhaustion_policy(ExhaustionPolicy::Error),
+        }
+    }
+}
+
+impl AsyncNano64Generator {
+    pub fn new() -> Self {
+        Self::from(Nano64Generator::new())
+    }
+
+    // Non-monotonic generation never waits.
+    pub fn generate(&self) -> Result<Nano64, Nano64Error> {
+        self.inner.generate()
+    }
+
+    pub async fn generate_monotonic(&self) -> Result<Nano64, Nano64Error> {
+        loop {
+            match self.inner.generate_monotonic() {
+                Err(Nano64Error::RandomFieldExhausted { .. }) => {
+                    tokio::time::sleep(Duration::from_millis(1)).await
+                }
+                result => return result,
+            }
+        }
+    }
+
+    pub fn generator(&self) -> &Nano64Generator {
+        &self.inner
+    }
+
+    // Endless `Stream` of IDs from `generate`, for use with `StreamExt` combinators and stream
+    // middleware. Items are `Result`s, so it is also a `TryStream`.
+    pub fn stream(&self) -> Nano64Stream<'_> {
+        Nano64Stream {
+            generator: self,
+            monotonic: false,
+            sleep: None,
+        }
+    }
+
+    // Like `stream`, but monotonic: an exhausted millisecond makes the stream pending until the
+    // next one.
+    pub fn stream_monotonic(&self) -> Nano64Stream<'_> {
+        Nano64Stream {
+            generator: self,
+            monotonic: true,
+            sleep: None,
+        }
+    }
+}
+
+// Stream returned by `AsyncNano64Generator::stream` and `stream_monotonic`.
+pub struct Nano64Stream<'a> {
+    generator: &'a AsyncNano64Generator,
+    monotonic: bool,
+    sleep: Option<Pin<Box<Sleep>>>,
+}
+
+impl Stream for Nano64Stream<'_> {
+    type Item = Result<Nano64, Nano64Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            if let Some(sleep) = this.sleep.as_mut() {
+                ready!(sleep.as_mut().poll(cx));
+                this.sleep = None;
+            }
+            if !this.monotonic {
+                return Poll::Ready(Some(this.generator.generate()));
+            }
+            match this.generator.inner.generate_monotonic() {
+                Err(Nano64Error::RandomFieldExhausted { .. }) => {
+                    this.sleep = Some(Box::pin(tokio::time::sleep(Duration::from_millis(1))));
+                }
+                result => return Poll::Ready(Some(result)),
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (usize::MAX, None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        AsyncNano64Generator, MonotonicState, Nano64Generator, RANDOM_MASK,
+        testing::{MockClock, MockRng},
+    };
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_async_generator_waits_for_next_millisecond() {
+        let state = MonotonicState::new();
+        state.set_last(1000, RANDOM_MASK);
+        let clock = MockClock::new(1000);
+        let generator = AsyncNano64Generator::from(
+            Nano64Generator::new()
+                .with_clock(clock.clone())
+                .with_rng(MockRng::constant(3))
+                .with_monotonic_state(state),
+        );
+
+        let advance = async {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            clock.advance(1);
+        };
+        let (id, _) = tokio::join!(generator.generate_monotonic(), advance);
+        let id = id.unwrap();
+        assert_eq!((id.get_timestamp(), id.get_random()), (1001, 3));
+        assert_eq!(generator.generate().unwrap().get_timestamp(), 1001);
+    }
+
+    #[tokio::test]
+    async fn test_async_generator_streams() {
+        use futures_core::Stream;
+        use std::{future::poll_fn, pin::Pin};
+
+        let state = MonotonicState::new();
+        state.set_last(1000, RANDOM_MASK - 1);
+        let clock = MockClock::new(1000);
+        let generator = AsyncNano64Generator::from(
+            Nano64Generator::new()
+                .with_clock(clock.clone())
+                .with_rng(MockRng::constant(5))
+                .with_monotonic_state(state),
+        );
+
+        let mut plain = generator.stream();
+        let id = poll_fn(|cx| Pin::new(&mut plain).poll_next(cx)).await;
+        assert_eq!(id.unwrap().unwrap().get_random(), 5);
+
+        let mut ordered = generator.stream_monotonic();
+        let first = poll_fn(|cx| Pin::new(&mut ordered).poll_next(cx)).await;
+        assert_eq!(first.unwrap().unwrap().get_random(), RANDOM_MASK as u32);
+        let advance = async {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            clock.advance(1);
+        };
+        let (second, _) = tokio::join!(poll_fn(|cx| Pin::new(&mut ordered).poll_next(cx)), advance);
+        let second = second.unwrap().unwrap();
+        assert_eq!((second.get_timestamp(), second.get_random()), (1001, 5));
+    }
+}
@@ -0,0 +1,77 @@
+use crate::{AeadBackend, Nano64, Nano64EncryptionFactory, Nano64Error, mac::KeyedHmac};
+
+// BLIND_INDEX_LENGTH is the number of bytes of HMAC-SHA256 kept in a blind index.
+pub const BLIND_INDEX_LENGTH: usize = 16;
+// Shortest key `with_blind_index_key` accepts.
+pub const MIN_INDEX_KEY_LENGTH: usize = 16;
+
+// Blind indexes make encrypted IDs searchable. Payloads use random IVs, so the same ID never
+// encrypts to the same bytes and can't be looked up by its payload; store its blind index, a
+// keyed hash that is the same every time, in a separate column and query by that instead.
+impl<B: AeadBackend> Nano64EncryptionFactory<B> {
+    // Sets the key for `blind_index`. Keep it separate from the encryption key: the index
+    // column then reveals which rows share an ID, but nothing about the IDs themselves.
+    pub fn with_blind_index_key(mut self, key: &[u8]) -> Result<Self, Nano64Error> {
+        if key.len() < MIN_INDEX_KEY_LENGTH {
+            return Err(Nano64Error::Error(format!(
+                "blind index key must be at least {MIN_INDEX_KEY_LENGTH} bytes, got {}",
+                key.len()
+            )));
+        }
+        self.index_mac = Some(KeyedHmac::new(key)?);
+        Ok(self)
+    }
+
+    // The lookup value for `id`: truncated HMAC-SHA256 under the blind index key. Fails if the
+    // factory was built without `with_blind_index_key`.
+    pub fn blind_index(&self, id: &Nano64) -> Result<[u8; BLIND_INDEX_LENGTH], Nano64Error> {
+        let Some(mac) = &self.index_mac else {
+            return Err(Nano64Error::Error(
+                "no blind index key; build the factory with_blind_index_key".into(),
+            ));
+        };
+        let mut index = [0u8; BLIND_INDEX_LENGTH];
+        index.copy_from_slice(&mac.tag(&id.value.to_be_bytes())[..BLIND_INDEX_LENGTH]);
+        Ok(index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Nano64, Nano64EncryptionFactory};
+
+    #[test]
+    fn test_blind_index() {
+        let factory = Nano64EncryptionFactory::new(&[1u8; 32], None, None)
+            .unwrap()
+            .with_blind_index_key(b"index-key-0123456789")
+            .unwrap();
+        let id = Nano64::generate_default().unwrap();
+
+        // Deterministic even though the payloads differ.
+        let first = factory.encrypt(id).unwrap();
+        let second = factory.encrypt(id).unwrap();
+        assert_ne!(first.to_encrypted_bytes(), second.to_encrypted_bytes());
+        let index = factory.blind_index(&id).unwrap();
+        assert_eq!(factory.blind_index(&first.id).unwrap(), index);
+
+        let other_id = Nano64::new(id.u64_value() ^ 1);
+        assert_ne!(factory.blind_index(&other_id).unwrap(), index);
+
+        // Depends on the index key only.
+        let same_index_key = Nano64EncryptionFactory::new(&[2u8; 32], None, None)
+            .unwrap()
+            .with_blind_index_key(b"index-key-0123456789")
+            .unwrap();
+        assert_eq!(same_index_key.blind_index(&id).unwrap(), index);
+        let other_index_key = Nano64EncryptionFactory::new(&[1u8; 32], None, None)
+            .unwrap()
+            .with_blind_index_key(b"another-index-key-01")
+            .unwrap();
+        assert_ne!(other_index_key.blind_index(&id).unwrap(), index);
+
+        let unkeyed = Nano64EncryptionFactory::new(&[1u8; 32], None, None).unwrap();
+        assert!(unkeyed.blind_index(&id).is_err());
+        assert!(unkeyed.with_blind_index_key(b"short").is_err());
+    }
+}
@@ -0,0 +1,246 @@
+use crate::{GcmAlgorithm, IV_LENGTH, Nano64Error};
+use aes::{
+    Aes128, Aes256,
+    cipher::{BlockEncrypt, consts::U16, generic_array::GenericArray},
+};
+use aes_gcm::{
+    Aes128Gcm, Aes256Gcm, Key, Nonce,
+    aead::{Aead, KeyInit, Payload},
+};
+use ghash::{GHash, universal_hash::UniversalHash};
+use subtle::ConstantTimeEq;
+
+// TAG_LENGTH is the length of the authentication tag an `AeadBackend` appends to ciphertext.
+pub const TAG_LENGTH: usize = 16;
+
+// The cipher behind `Nano64EncryptionFactory`. Implement it to route encryption through a
+// mandated provider (ring, aws-lc-rs, an HSM, ...); the factory keeps owning the IV generation
+// and payload layout.
+pub trait AeadBackend: Send + Sync {
+    // Encrypts `plaintext` under the 12-byte `iv`, authenticating `aad` too. Returns the
+    // ciphertext (as long as `plaintext`) followed by a `TAG_LENGTH`-byte tag.
+    fn encrypt(
+        &self,
+        iv: &[u8; IV_LENGTH],
+        aad: &[u8],
+        plaintext: &[u8],
+    ) -> Result<Vec<u8>, Nano64Error>;
+
+    // Reverses `encrypt`. Must fail if the tag doesn't match.
+    fn decrypt(
+        &self,
+        iv: &[u8; IV_LENGTH],
+        aad: &[u8],
+        ciphertext: &[u8],
+    ) -> Result<Vec<u8>, Nano64Error>;
+
+    // Like `decrypt`, but for a `ciphertext` whose tag was cut down to `tag`, a prefix of the
+    // full tag. Must fail if `tag` doesn't match that prefix. Backends can't be expected to
+    // verify tags shorter than their AEAD library allows, so the default refuses.
+    fn decrypt_truncated(
+        &self,
+        iv: &[u8; IV_LENGTH],
+        aad: &[u8],
+        ciphertext: &[u8],
+        tag: &[u8],
+    ) -> Result<Vec<u8>, Nano64Error> {
+        let _ = (iv, aad, ciphertext, tag);
+        Err(Nano64Error::Error(
+            "this backend doesn't support truncated tags".into(),
+        ))
+    }
+}
+
+// Not boxed: the key schedules are within a few hundred bytes of each other, and boxing would
+// cost an allocation every time the backend is cloned. The bare block cipher rides along for
+// `decrypt_truncated`, which does GCM by hand.
+#[allow(clippy::large_enum_variant)]
+#[derive(Clone)]
+enum GcmCipher {
+    Aes128(Aes128Gcm, Aes128),
+    Aes256(Aes256Gcm, Aes256),
+}
+
+// The default `AeadBackend`, using the RustCrypto `aes-gcm` crate.
+#[derive(Clone)]
+pub struct AesGcm {
+    cipher: GcmCipher,
+}
+
+impl AesGcm {
+    // Fails with `InvalidKeyLength` unless `key` is `algorithm.key_length()` bytes.
+    pub fn new(algorithm: GcmAlgorithm, key: &[u8]) -> Result<Self, Nano64Error> {
+        if key.len() != algorithm.key_length() {
+            return Err(Nano64Error::InvalidKeyLength {
+                algorithm,
+                expected: algorithm.key_length(),
+                got: key.len(),
+            });
+        }
+        let cipher = match algorithm {
+            GcmAlgorithm::Aes128 => GcmCipher::Aes128(
+                Aes128Gcm::new(Key::<Aes128Gcm>::from_slice(key)),
+                Aes128::new(GenericArray::from_slice(key)),
+            ),
+            GcmAlgorithm::Aes256 => GcmCipher::Aes256(
+                Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key)),
+                Aes256::new(GenericArray::from_slice(key)),
+            ),
+        };
+        Ok(Self { cipher })
+    }
+
+    pub fn algorithm(&self) -> GcmAlgorithm {
+        match self.cipher {
+            GcmCipher::Aes128(..) => GcmAlgorithm::Aes128,
+            GcmCipher::Aes256(..) => GcmAlgorithm::Aes256,
+        }
+    }
+}
+
+impl AeadBackend for AesGcm {
+    fn encrypt(
+        &self,
+        iv: &[u8; IV_LENGTH],
+        aad: &[u8],
+        plaintext: &[u8],
+    ) -> Result<Vec<u8>, Nano64Error> {
+        let nonce = Nonce::from_slice(iv);
+        let payload = Payload {
+            msg: plaintext,
+            aad,
+        };
+        match &self.cipher {
+            GcmCipher::Aes128(gcm, _) => gcm.encrypt(nonce, payload),
+            GcmCipher::Aes256(gcm, _) => gcm.encrypt(nonce, payload),
+        }
+        .map_err(|e| Nano64Error::Error(format!("Error during encryption! {e}")))
+    }
+
+    fn decrypt(
+        &self,
+        iv: &[u8; IV_LENGTH],
+        aad: &[u8],
+        ciphertext: &[u8],
+    ) -> Result<Vec<u8>, Nano64Error> {
+        let nonce = Nonce::from_slice(iv);
+        let payload = Payload {
+            msg: ciphertext,
+            aad,
+        };
+        match &self.cipher {
+            GcmCipher::Aes128(gcm, _) => gcm.decrypt(nonce, payload),
+            GcmCipher::Aes256(gcm, _) => gcm.decrypt(nonce, payload),
+        }
+        .map_err(|_| Nano64Error::Error("decryption failed".into()))
+    }
+
+    fn decrypt_truncated(
+        &self,
+        iv: &[u8; IV_LENGTH],
+        aad: &[u8],
+        ciphertext: &[u8],
+        tag: &[u8],
+    ) -> Result<Vec<u8>, Nano64Error> {
+        match &self.cipher {
+            GcmCipher::Aes128(_, aes) => gcm_decrypt_truncated(aes, iv, aad, ciphertext, tag),
+            GcmCipher::Aes256(_, aes) => gcm_decrypt_truncated(aes, iv, aad, ciphertext, tag),
+        }
+    }
+}
+
+// GCM decryption per NIST SP 800-38D, done by hand because `aes-gcm` won't verify a tag shorter
+// than 12 bytes. The full tag is recomputed from the ciphertext and only its prefix compared, in
+// constant time, before anything is decrypted.
+fn gcm_decrypt_truncated(
+    aes: &impl BlockEncrypt<BlockSize = U16>,
+    iv: &[u8; IV_LENGTH],
+    aad: &[u8],
+    ciphertext: &[u8],
+    tag: &[u8],
+) -> Result<Vec<u8>, Nano64Error> {
+    if tag.is_empty() || tag.len() > TAG_LENGTH {
+        return Err(Nano64Error::Error("decryption failed".into()));
+    }
+    let counter_block = |counter: u32| {
+        let mut block = [0u8; 16];
+        block[..IV_LENGTH].copy_from_slice(iv);
+        block[IV_LENGTH..].copy_from_slice(&counter.to_be_bytes());
+        let mut block = GenericArray::from(block);
+        aes.encrypt_block(&mut block);
+        block
+    };
+
+    let mut hash_key = GenericArray::default();
+    aes.encrypt_block(&mut hash_key);
+    let mut ghash = GHash::new(&hash_key);
+    ghash.update_padded(aad);
+    ghash.update_padded(ciphertext);
+    let mut lengths = [0u8; 16];
+    lengths[..8].copy_from_slice(&(aad.len() as u64 * 8).to_be_bytes());
+    lengths[8..].copy_from_slice(&(ciphertext.len() as u64 * 8).to_be_bytes());
+    ghash.update(&[GenericArray::from(lengths)]);
+    let mut expected = ghash.finalize();
+    for (byte, mask) in expected.iter_mut().zip(counter_block(1)) {
+        *byte ^= mask;
+    }
+    if !bool::from(expected[..tag.len()].ct_eq(tag)) {
+        return Err(Nano64Error::Error("decryption failed".into()));
+    }
+
+    // Counter 1 went to the tag; the keystream starts at 2.
+    let mut plaintext = ciphertext.to_vec();
+    for (chunk, counter) in plaintext.chunks_mut(16).zip(2u32..) {
+        for (byte, key) in chunk.iter_mut().zip(counter_block(counter)) {
+            *byte ^= key;
+        }
+    }
+    Ok(plaintext)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decrypt_truncated() {
+        for algorithm in [GcmAlgorithm::Aes128, GcmAlgorithm::Aes256] {
+            let backend = AesGcm::new(algorithm, &vec![9u8; algorithm.key_length()]).unwrap();
+            let iv = [3u8; IV_LENGTH];
+            // Empty, partial-block, whole-block and multi-block plaintexts, with and without AAD.
+            for length in [0, 1, 8, 16, 17, 40] {
+                let plaintext: Vec<u8> = (0..length as u8).collect();
+                for aad in [&b""[..], b"context"] {
+                    let sealed = backend.encrypt(&iv, aad, &plaintext).unwrap();
+                    let (ciphertext, tag) = sealed.split_at(length);
+                    for tag_length in [1, 8, 16] {
+                        let tag = &tag[..tag_length];
+                        assert_eq!(
+                            backend
+                                .decrypt_truncated(&iv, aad, ciphertext, tag)
+                                .unwrap(),
+                            plaintext
+                        );
+                        let mut forged = tag.to_vec();
+                        forged[0] ^= 0x80;
+                        assert!(
+                            backend
+                                .decrypt_truncated(&iv, aad, ciphertext, &forged)
+                                .is_err()
+                        );
+                    }
+                    assert!(
+                        backend
+                            .decrypt_truncated(&iv, b"other", ciphertext, &tag[..8])
+                            .is_err()
+                    );
+                    assert!(
+                        backend
+                            .decrypt_truncated(&iv, aad, ciphertext, &[])
+                            .is_err()
+                    );
+                }
+            }
+        }
+    }
+}
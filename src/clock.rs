@@ -0,0 +1,199 @@
+use crate::{Nano64Error, system_time_ms};
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+// How often a background `CachedClock` refreshes its timestamp.
+const CACHED_CLOCK_REFRESH: Duration = Duration::from_millis(1);
+// How often an on-demand `CachedClock` re-reads the system clock to follow wall-clock steps.
+const CACHED_CLOCK_RESYNC_MS: u64 = 1000;
+
+// A source of wall-clock time in milliseconds since the UNIX epoch. Unlike `ClockImpl`, a clock
+// may carry state: a cached time, a simulated clock that advances per call, or a handle to a
+// disciplined time source. Implementations needing mutation should use interior mutability
+// (e.g. atomics), since generators share the clock across threads.
+//
+// Any `Fn() -> u64`, including every `ClockImpl`, is a clock.
+pub trait Clock: Send + Sync {
+    fn now_ms(&self) -> u64;
+}
+
+impl<F> Clock for F
+where
+    F: Fn() -> u64 + Send + Sync,
+{
+    fn now_ms(&self) -> u64 {
+        self()
+    }
+}
+
+// The operating system's wall clock. Unlike the default clock, it ignores `set_default_clock`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_ms(&self) -> u64 {
+        system_time_ms()
+    }
+}
+
+// A coarse clock that avoids a `SystemTime::now()` call per ID, for the background variant. Cheap
+// to clone; clones share one cached time.
+#[derive(Clone, Debug)]
+pub struct CachedClock {
+    source: CachedSource,
+}
+
+#[derive(Clone, Debug)]
+enum CachedSource {
+    // Refreshed by a background thread; a read is one atomic load.
+    Background(Arc<AtomicU64>),
+    // Counted forward from a monotonic `Instant`, re-anchored to the system clock periodically.
+    OnDemand(Arc<OnDemandClock>),
+}
+
+#[derive(Debug)]
+struct OnDemandClock {
+    start: Instant,
+    // Wall-clock ms at `start`, as of the last resync.
+    offset_ms: AtomicU64,
+    // Ms since `start` at which to resync next.
+    resync_at_ms: AtomicU64,
+}
+
+impl CachedClock {
+    // Starts a thread that refreshes the cached time every millisecond. The thread exits once
+    // every clone of the clock has been dropped.
+    pub fn spawn_background() -> Result<Self, Nano64Error> {
+        let now = Arc::new(AtomicU64::new(system_time_ms()));
+        let weak = Arc::downgrade(&now);
+        thread::Builder::new()
+            .name("nano64-cached-clock".into())
+            .spawn(move || {
+                while let Some(now) = weak.upgrade() {
+                    now.store(system_time_ms(), Ordering::Relaxed);
+                    drop(now);
+                    thread::sleep(CACHED_CLOCK_REFRESH);
+                }
+            })
+            .map_err(Nano64Error::Io)?;
+        Ok(Self {
+            source: CachedSource::Background(now),
+        })
+    }
+
+    // No background thread: time is derived from `Instant` deltas and the system clock is only
+    // consulted about once a second. This is not cheaper than `SystemClock`: every read still
+    // calls `Instant::now()`, which costs about as much as `SystemTime::now()` on common
+    // platforms. What it changes is that readings between resyncs follow the monotonic clock,
+    // so a wall-clock step shows up at most once a second. Use `spawn_background` for speed.
+    pub fn on_demand() -> Self {
+        Self {
+            source: CachedSource::OnDemand(Arc::new(OnDemandClock {
+                start: Instant::now(),
+                offset_ms: AtomicU64::new(system_time_ms()),
+                resync_at_ms: AtomicU64::new(CACHED_CLOCK_RESYNC_MS),
+            })),
+        }
+    }
+}
+
+impl Clock for CachedClock {
+    fn now_ms(&self) -> u64 {
+        match &self.source {
+            CachedSource::Background(now) => now.load(Ordering::Relaxed),
+            CachedSource::OnDemand(clock) => {
+                let elapsed_ms = clock.start.elapsed().as_millis() as u64;
+                if elapsed_ms >= clock.resync_at_ms.load(Ordering::Relaxed) {
+                    // Racing resyncs compute near-identical offsets, so last writer wins.
+                    let wall_ms = system_time_ms();
+                    clock
+                        .offset_ms
+                        .store(wall_ms.saturating_sub(elapsed_ms), Ordering::Relaxed);
+                    clock
+                        .resync_at_ms
+                        .store(elapsed_ms + CACHED_CLOCK_RESYNC_MS, Ordering::Relaxed);
+                    return wall_ms;
+                }
+                clock.offset_ms.load(Ordering::Relaxed) + elapsed_ms
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{CachedClock, Clock, Nano64Generator, SystemClock};
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    // Simulated time that advances 10ms per read.
+    struct SteppingClock(AtomicU64);
+
+    impl Clock for SteppingClock {
+        fn now_ms(&self) -> u64 {
+            self.0.fetch_add(10, Ordering::SeqCst)
+        }
+    }
+
+    #[test]
+    fn test_system_clock_is_recent() {
+        // 2020-01-01 in ms.
+        assert!(SystemClock.now_ms() > 1_577_836_800_000);
+    }
+
+    #[test]
+    fn test_stateful_clock_in_generator() {
+        let generator = Nano64Generator::new().with_clock(SteppingClock(AtomicU64::new(1000)));
+        assert_eq!(generator.generate().unwrap().get_timestamp(), 1000);
+        assert_eq!(generator.generate().unwrap().get_timestamp(), 1010);
+        assert_eq!(
+            generator.generate_monotonic().unwrap().get_timestamp(),
+            1020
+        );
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn test_closure_clock_in_encryption_factory() {
+        let now = std::sync::Arc::new(AtomicU64::new(5000));
+        let shared = now.clone();
+        let factory = crate::Nano64EncryptionFactory::new(&[3u8; 32], None, None)
+            .unwrap()
+            .with_clock(move || shared.load(Ordering::SeqCst));
+        assert_eq!(
+            factory.generate_encrypted_now().unwrap().id.get_timestamp(),
+            5000
+        );
+        now.store(6000, Ordering::SeqCst);
+        assert_eq!(
+            factory.generate_encrypted_now().unwrap().id.get_timestamp(),
+            6000
+        );
+    }
+
+    #[test]
+    fn test_cached_clocks_track_system_clock() {
+        let background = CachedClock::spawn_background().unwrap();
+        let on_demand = CachedClock::on_demand();
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        for clock in [background, on_demand] {
+            let system = SystemClock.now_ms();
+            let cached = clock.now_ms();
+            assert!(system.abs_diff(cached) <= 20, "{system} vs {cached}");
+            let generator = Nano64Generator::new().with_clock(clock.clone());
+            assert!(
+                generator
+                    .generate()
+                    .unwrap()
+                    .get_timestamp()
+                    .abs_diff(system)
+                    <= 20
+            );
+        }
+    }
+}
@@ -0,0 +1,176 @@
+use crate::{Nano64, Nano64Error};
+#[cfg(feature = "encryption")]
+use crate::{Nano64Encrypted, Nano64EncryptionFactory};
+use bytes::{Buf, BufMut, BytesMut};
+#[cfg(feature = "encryption")]
+use std::sync::Arc;
+use tokio_util::codec::{Decoder, Encoder};
+
+// Length of a single `Nano64Codec` frame.
+pub const NANO64_FRAME_LENGTH: usize = 8;
+// Largest encrypted payload `EncryptedPayloadCodec` will accept before treating the stream as
+// corrupt.
+#[cfg(feature = "encryption")]
+pub const MAX_ENCRYPTED_FRAME_LENGTH: usize = 1024;
+
+// Frames IDs as fixed 8-byte big-endian values.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Nano64Codec;
+
+impl Encoder<Nano64> for Nano64Codec {
+    type Error = Nano64Error;
+
+    fn encode(&mut self, id: Nano64, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        dst.put_slice(&id.to_bytes());
+        Ok(())
+    }
+}
+
+impl Decoder for Nano64Codec {
+    type Item = Nano64;
+    type Error = Nano64Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        if src.len() < NANO64_FRAME_LENGTH {
+            src.reserve(NANO64_FRAME_LENGTH - src.len());
+            return Ok(None);
+        }
+        Ok(Some(Nano64::new(src.get_u64())))
+    }
+}
+
+// Frames encrypted payloads as a big-endian u16 length followed by the payload bytes.
+// Decoded frames are decrypted with the codec's factory.
+#[cfg(feature = "encryption")]
+#[derive(Clone)]
+pub struct EncryptedPayloadCodec {
+    factory: Arc<Nano64EncryptionFactory>,
+}
+
+#[cfg(feature = "encryption")]
+impl EncryptedPayloadCodec {
+    pub fn new(factory: Arc<Nano64EncryptionFactory>) -> Self {
+        Self { factory }
+    }
+}
+
+#[cfg(feature = "encryption")]
+impl Encoder<Nano64Encrypted> for EncryptedPayloadCodec {
+    type Error = Nano64Error;
+
+    fn encode(&mut self, item: Nano64Encrypted, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        let payload = item.as_encrypted_bytes();
+        dst.reserve(2 + payload.len());
+        dst.put_u16(payload.len() as u16);
+        dst.put_slice(payload);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "encryption")]
+impl Decoder for EncryptedPayloadCodec {
+    type Item = Nano64Encrypted;
+    type Error = Nano64Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        if src.len() < 2 {
+            return Ok(None);
+        }
+
+        let len = u16::from_be_bytes([src[0], src[1]]) as usize;
+        if len > MAX_ENCRYPTED_FRAME_LENGTH {
+            return Err(Nano64Error::Error(format!(
+                "encrypted frame length {len} exceeds maximum of {MAX_ENCRYPTED_FRAME_LENGTH}"
+            )));
+        }
+
+        if src.len() < 2 + len {
+            src.reserve(2 + len - src.len());
+            return Ok(None);
+        }
+
+        src.advance(2);
+        let frame = src.split_to(len);
+        self.factory.from_encrypted_bytes(&frame).map(Some)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "encryption")]
+    use std::sync::Arc;
+
+    use bytes::{BufMut, BytesMut};
+    use tokio_util::codec::{Decoder, Encoder};
+
+    #[cfg(feature = "encryption")]
+    use crate::{EncryptedPayloadCodec, Nano64EncryptionFactory};
+    use crate::{Nano64, Nano64Codec};
+
+    #[cfg(feature = "encryption")]
+    fn factory() -> Arc<Nano64EncryptionFactory> {
+        let key: [u8; 32] = [
+            1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24,
+            25, 26, 27, 28, 29, 30, 31, 32,
+        ];
+        Arc::new(Nano64EncryptionFactory::new(&key, None, None).unwrap())
+    }
+
+    #[test]
+    fn test_nano64_codec_roundtrip() {
+        let mut codec = Nano64Codec;
+        let mut buf = BytesMut::new();
+        let ids = [
+            Nano64::new(0),
+            Nano64::new(0x123456789ABCDEF0),
+            Nano64::new(u64::MAX),
+        ];
+        for id in ids {
+            codec.encode(id, &mut buf).unwrap();
+        }
+        assert_eq!(buf.len(), 24);
+        for id in ids {
+            assert!(codec.decode(&mut buf).unwrap().unwrap().equals(&id));
+        }
+        assert!(codec.decode(&mut buf).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_nano64_codec_partial_frame() {
+        let mut codec = Nano64Codec;
+        let mut buf = BytesMut::from(&[0x12, 0x34, 0x56][..]);
+        assert!(codec.decode(&mut buf).unwrap().is_none());
+        buf.put_slice(&[0x78, 0x9A, 0xBC, 0xDE, 0xF0]);
+        let id = codec.decode(&mut buf).unwrap().unwrap();
+        assert_eq!(id.u64_value(), 0x123456789ABCDEF0);
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn test_encrypted_payload_codec_roundtrip() {
+        let factory = factory();
+        let mut codec = EncryptedPayloadCodec::new(factory.clone());
+        let encrypted = factory.generate_encrypted_now().unwrap();
+        let mut buf = BytesMut::new();
+        codec.encode(encrypted.clone(), &mut buf).unwrap();
+
+        // Feed the frame one byte at a time to exercise partial reads.
+        let mut partial = BytesMut::new();
+        let mut decoded = None;
+        for byte in buf.iter() {
+            assert!(decoded.is_none());
+            partial.put_u8(*byte);
+            decoded = codec.decode(&mut partial).unwrap();
+        }
+        assert!(decoded.unwrap().id.equals(&encrypted.id));
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn test_encrypted_payload_codec_rejects_oversized_frame() {
+        let mut codec = EncryptedPayloadCodec::new(factory());
+        let mut buf = BytesMut::new();
+        buf.put_u16(u16::MAX);
+        assert!(codec.decode(&mut buf).is_err());
+    }
+}
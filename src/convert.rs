@@ -0,0 +1,188 @@
+// Adapters between Nano64 and other 64-bit ID schemes, for migrations that need to keep rough
+// time ordering across the cutover.
+use crate::{MAX_TIMESTAMP, Nano64, Nano64Error, RANDOM_BITS, RANDOM_MASK};
+
+// Where a snowflake ID keeps its timestamp. Everything below `timestamp_shift` (worker, process,
+// sequence, ...) is treated as opaque instance bits.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SnowflakeLayout {
+    pub timestamp_bits: u32,
+    pub timestamp_shift: u32,
+}
+
+impl SnowflakeLayout {
+    // 41-bit timestamp, 10-bit machine, 12-bit sequence.
+    pub const TWITTER: SnowflakeLayout = SnowflakeLayout {
+        timestamp_bits: 41,
+        timestamp_shift: 22,
+    };
+    // 42-bit timestamp, 5-bit worker, 5-bit process, 12-bit increment.
+    pub const DISCORD: SnowflakeLayout = SnowflakeLayout {
+        timestamp_bits: 42,
+        timestamp_shift: 22,
+    };
+
+    fn validate(&self) -> Result<(), Nano64Error> {
+        if self.timestamp_bits == 0
+            || self
+                .timestamp_bits
+                .checked_add(self.timestamp_shift)
+                .is_none_or(|total| total > 64)
+        {
+            return Err(Nano64Error::Error(format!(
+                "invalid snowflake layout: {} timestamp bits at shift {}",
+                self.timestamp_bits, self.timestamp_shift
+            )));
+        }
+        Ok(())
+    }
+}
+
+pub const TWITTER_EPOCH_MS: u64 = 1_288_834_974_657;
+pub const DISCORD_EPOCH_MS: u64 = 1_420_070_400_000;
+
+// Result of a conversion. `exact` is false when instance bits had to be dropped, in which
+// case converting back will not reproduce the original ID.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Converted<T> {
+    pub value: T,
+    pub exact: bool,
+}
+
+// Maps a snowflake to a Nano64 with the same millisecond. The low 20 instance bits become the
+// random field; higher instance bits (usually worker ids) are dropped and reported via `exact`.
+pub fn from_snowflake(
+    id: u64,
+    epoch_ms: u64,
+    layout: SnowflakeLayout,
+) -> Result<Converted<Nano64>, Nano64Error> {
+    layout.validate()?;
+    let ts_mask = low_mask(layout.timestamp_bits);
+    let timestamp = ((id >> layout.timestamp_shift) & ts_mask)
+        .checked_add(epoch_ms)
+        .filter(|ts| *ts <= MAX_TIMESTAMP)
+        .ok_or(Nano64Error::TimeStampExceedsBitRange(
+            (id >> layout.timestamp_shift).saturating_add(epoch_ms),
+        ))?;
+    let instance = id & low_mask(layout.timestamp_shift);
+    let random = instance & RANDOM_MASK;
+    Ok(Converted {
+        value: Nano64::from_parts(timestamp, random as u32)?,
+        exact: instance == random,
+    })
+}
+
+// Maps a Nano64 to a snowflake with the same millisecond. The random field fills the instance
+// bits; if there are fewer than 20 its high bits are dropped and reported via `exact`.
+// Fails if the timestamp is before `epoch_ms` or doesn't fit the layout's timestamp bits.
+pub fn to_snowflake(
+    id: Nano64,
+    epoch_ms: u64,
+    layout: SnowflakeLayout,
+) -> Result<Converted<u64>, Nano64Error> {
+    layout.validate()?;
+    let timestamp = id.get_timestamp();
+    let offset = timestamp.checked_sub(epoch_ms).ok_or_else(|| {
+        Nano64Error::Error(format!(
+            "timestamp {timestamp} is before the snowflake epoch {epoch_ms}"
+        ))
+    })?;
+    if offset > low_mask(layout.timestamp_bits) {
+        return Err(Nano64Error::Error(format!(
+            "timestamp {timestamp} does not fit in {} snowflake timestamp bits",
+            layout.timestamp_bits
+        )));
+    }
+    let random = id.get_random() as u64;
+    let instance = random & low_mask(layout.timestamp_shift.min(RANDOM_BITS as u32));
+    Ok(Converted {
+        value: (offset << layout.timestamp_shift) | instance,
+        exact: instance == random,
+    })
+}
+
+fn low_mask(bits: u32) -> u64 {
+    if bits >= 64 {
+        u64::MAX
+    } else {
+        (1 << bits) - 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_snowflake_twitter() {
+        // Timestamp 1382971839 ms after the Twitter epoch, worker 1, sequence 5.
+        let snowflake = (1_382_971_839u64 << 22) | (1 << 12) | 5;
+        let converted =
+            from_snowflake(snowflake, TWITTER_EPOCH_MS, SnowflakeLayout::TWITTER).unwrap();
+        assert!(converted.exact);
+        assert_eq!(
+            converted.value.get_timestamp(),
+            TWITTER_EPOCH_MS + 1_382_971_839
+        );
+        assert_eq!(converted.value.get_random(), (1 << 12) | 5);
+
+        let back =
+            to_snowflake(converted.value, TWITTER_EPOCH_MS, SnowflakeLayout::TWITTER).unwrap();
+        assert_eq!(
+            back,
+            Converted {
+                value: snowflake,
+                exact: true
+            }
+        );
+    }
+
+    #[test]
+    fn test_snowflake_precision_loss_is_reported() {
+        // Worker id 0x3FF uses instance bits above the 20 Nano64 can carry.
+        let snowflake = (1000u64 << 22) | (0x3FF << 12) | 7;
+        let converted =
+            from_snowflake(snowflake, DISCORD_EPOCH_MS, SnowflakeLayout::DISCORD).unwrap();
+        assert!(!converted.exact);
+        let back =
+            to_snowflake(converted.value, DISCORD_EPOCH_MS, SnowflakeLayout::DISCORD).unwrap();
+        assert_ne!(back.value, snowflake);
+
+        // Fewer instance bits than random bits drops the high random bits.
+        let narrow = SnowflakeLayout {
+            timestamp_bits: 41,
+            timestamp_shift: 8,
+        };
+        let id = Nano64::from_parts(TWITTER_EPOCH_MS + 10, 0x1FF).unwrap();
+        let converted = to_snowflake(id, TWITTER_EPOCH_MS, narrow).unwrap();
+        assert!(!converted.exact);
+        assert_eq!(converted.value, (10 << 8) | 0xFF);
+    }
+
+    #[test]
+    fn test_snowflake_ordering_across_cutover() {
+        let old = (500u64 << 22) | 4095;
+        let new = Nano64::from_parts(TWITTER_EPOCH_MS + 501, 0).unwrap();
+        let old = from_snowflake(old, TWITTER_EPOCH_MS, SnowflakeLayout::TWITTER)
+            .unwrap()
+            .value;
+        assert!(old < new);
+    }
+
+    #[test]
+    fn test_snowflake_rejects_out_of_range() {
+        let id = Nano64::from_parts(TWITTER_EPOCH_MS - 1, 0).unwrap();
+        assert!(to_snowflake(id, TWITTER_EPOCH_MS, SnowflakeLayout::TWITTER).is_err());
+        let bad = SnowflakeLayout {
+            timestamp_bits: 50,
+            timestamp_shift: 22,
+        };
+        assert!(from_snowflake(0, 0, bad).is_err());
+        let huge = SnowflakeLayout {
+            timestamp_bits: u32::MAX,
+            timestamp_shift: 1,
+        };
+        assert!(from_snowflake(0, 0, huge).is_err());
+        assert!(from_snowflake(u64::MAX, u64::MAX, SnowflakeLayout::TWITTER).is_err());
+    }
+}
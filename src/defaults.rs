@@ -0,0 +1,106 @@
+use crate::{Clock, Nano64Error, RandomNumberGeneratorImpl, RandomSource};
+use std::sync::{Mutex, OnceLock};
+
+// Process-wide replacements for the system clock and OS RNG. Every function that falls back to
+// the default clock or RNG (`generate_default`, `*_now`, `rng: None`, `Nano64Generator::new`,
+// ...) picks these up once installed.
+static DEFAULT_CLOCK: OnceLock<Box<dyn Clock>> = OnceLock::new();
+static DEFAULT_RNG: OnceLock<DefaultRng> = OnceLock::new();
+
+// An installed default RNG. Plain functions are called directly; any other source sits behind
+// one lock that every thread shares.
+pub(crate) enum DefaultRng {
+    Fn(RandomNumberGeneratorImpl),
+    Source(Mutex<Box<dyn RandomSource>>),
+}
+
+impl DefaultRng {
+    pub(crate) fn random_bits(&self, bits: u32) -> Result<u32, Nano64Error> {
+        match self {
+            DefaultRng::Fn(rng) => rng(bits),
+            DefaultRng::Source(rng) => rng
+                .lock()
+                .map_err(|_| Nano64Error::Error("Error unlocking rng".into()))?
+                .random_bits(bits),
+        }
+    }
+}
+
+// Installs the process-wide default clock, e.g. a simulated clock. Can only be set once; install
+// it at startup, before the first ID is generated.
+pub fn set_default_clock(clock: impl Clock + 'static) -> Result<(), Nano64Error> {
+    DEFAULT_CLOCK
+        .set(Box::new(clock))
+        .map_err(|_| Nano64Error::DefaultAlreadySet("clock"))
+}
+
+// Installs the process-wide default RNG, e.g. a hardware RNG. Can only be set once. The source
+// must not itself fall back to the default RNG, or the first call deadlocks. Every call goes
+// through one process-wide lock; install plain functions with `set_default_rng_fn` instead.
+pub fn set_default_rng(rng: impl RandomSource + 'static) -> Result<(), Nano64Error> {
+    install_rng(DefaultRng::Source(Mutex::new(Box::new(rng))))
+}
+
+// `set_default_rng` for a plain function such as `pooled_rng`, which is then called without
+// taking a lock. Either one can be called, once.
+pub fn set_default_rng_fn(rng: RandomNumberGeneratorImpl) -> Result<(), Nano64Error> {
+    install_rng(DefaultRng::Fn(rng))
+}
+
+fn install_rng(rng: DefaultRng) -> Result<(), Nano64Error> {
+    DEFAULT_RNG
+        .set(rng)
+        .map_err(|_| Nano64Error::DefaultAlreadySet("RNG"))
+}
+
+pub(crate) fn installed_clock() -> Option<&'static dyn Clock> {
+    DEFAULT_CLOCK.get().map(|clock| clock.as_ref())
+}
+
+pub(crate) fn installed_rng() -> Option<&'static DefaultRng> {
+    DEFAULT_RNG.get()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Nano64, Nano64Error, RngSource, SystemClock, pooled_rng, set_default_clock,
+        set_default_rng, set_default_rng_fn,
+    };
+    use rand::{SeedableRng, rngs::StdRng};
+
+    // Defaults are process-wide and can't be uninstalled, so this only installs equivalents of
+    // the built-in ones to keep the other tests unaffected.
+    #[test]
+    fn test_defaults_can_only_be_set_once() {
+        let _ = set_default_clock(SystemClock);
+        assert!(matches!(
+            set_default_clock(SystemClock),
+            Err(Nano64Error::DefaultAlreadySet("clock"))
+        ));
+
+        let _ = set_default_rng(RngSource(StdRng::from_os_rng()));
+        assert!(matches!(
+            set_default_rng(RngSource(StdRng::from_os_rng())),
+            Err(Nano64Error::DefaultAlreadySet("RNG"))
+        ));
+        assert!(matches!(
+            set_default_rng_fn(pooled_rng),
+            Err(Nano64Error::DefaultAlreadySet("RNG"))
+        ));
+
+        let a = Nano64::generate_default().unwrap();
+        let b = Nano64::generate_batch(300, None).unwrap();
+        assert!(a.get_timestamp() <= b[0].get_timestamp());
+    }
+
+    #[test]
+    fn test_default_rng_fn_is_called_directly() {
+        use super::DefaultRng;
+
+        fn fixed(_bits: u32) -> Result<u32, Nano64Error> {
+            Ok(0x12345)
+        }
+        assert_eq!(DefaultRng::Fn(fixed).random_bits(20).unwrap(), 0x12345);
+    }
+}
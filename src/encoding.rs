@@ -0,0 +1,839 @@
+use crate::{MAX_TIMESTAMP, Nano64, RANDOM_BITS, RANDOM_MASK, TIMESTAMP_SHIFT, errors::*};
+
+mod hex;
+pub use hex::*;
+
+// Fixed-width positional encoding of a u64 over an ASCII-ordered alphabet. Every encoding in this
+// module is built on it, so they share padding, validation and error reporting. Because the
+// alphabets are in ASCII order and the output is zero-padded, encoded strings sort like the
+// values they encode.
+pub(crate) struct FixedRadix {
+    pub(crate) name: &'static str,
+    pub(crate) alphabet: &'static [u8],
+    pub(crate) width: usize,
+}
+
+impl FixedRadix {
+    pub(crate) fn encode(&self, value: u64) -> String {
+        let base = self.alphabet.len() as u64;
+        let mut buf = vec![self.alphabet[0]; self.width];
+        let mut rest = value;
+        for slot in buf.iter_mut().rev() {
+            *slot = self.alphabet[(rest % base) as usize];
+            rest /= base;
+        }
+        // Alphabets are ASCII.
+        String::from_utf8(buf).expect("alphabet is always ASCII")
+    }
+
+    pub(crate) fn decode(&self, s: &str) -> Result<u64, Nano64Error> {
+        if s.len() != self.width {
+            return Err(Nano64Error::InvalidEncodedLength {
+                encoding: self.name,
+                expected: self.width,
+                got: s.len(),
+            });
+        }
+        let base = self.alphabet.len() as u64;
+        let mut value: u64 = 0;
+        for c in s.chars() {
+            let digit = self.digit(c).ok_or(Nano64Error::InvalidEncodedChar {
+                encoding: self.name,
+                character: c,
+            })?;
+            value = value
+                .checked_mul(base)
+                .and_then(|v| v.checked_add(digit))
+                .ok_or(Nano64Error::EncodedValueOverflow(self.name))?;
+        }
+        Ok(value)
+    }
+
+    fn digit(&self, c: char) -> Option<u64> {
+        if !c.is_ascii() {
+            return None;
+        }
+        self.alphabet
+            .binary_search(&(c as u8))
+            .ok()
+            .map(|i| i as u64)
+    }
+}
+
+// Number of characters produced by `Base62::encode`. 62^11 > 2^64, so every u64 fits.
+pub const BASE62_LENGTH: usize = 11;
+
+const BASE62: FixedRadix = FixedRadix {
+    name: "base62",
+    alphabet: b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz",
+    width: BASE62_LENGTH,
+};
+
+// Encodes 64-bit values as fixed-width, zero-padded Base62 for compact URL tokens.
+pub struct Base62;
+
+impl Base62 {
+    pub fn encode(value: u64) -> String {
+        BASE62.encode(value)
+    }
+
+    pub fn decode(s: &str) -> Result<u64, Nano64Error> {
+        BASE62.decode(s)
+    }
+}
+
+// Number of characters produced by `Base58Fixed::encode`, and the most `Base58::encode`
+// produces. 58^11 > 2^64, so every u64 fits.
+pub const BASE58_LENGTH: usize = 11;
+
+const BASE58: FixedRadix = FixedRadix {
+    name: "base58",
+    alphabet: b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz",
+    width: BASE58_LENGTH,
+};
+
+// Standard Base58 with the Bitcoin alphabet (no 0, O, I or l) over the value's 8 big-endian
+// bytes, so output matches other Base58 implementations: each leading zero byte becomes a `1`
+// and the rest is the number in base 58. Between 8 and 11 characters, and doesn't sort like the
+// values; see `Base58Fixed` for that.
+pub struct Base58;
+
+impl Base58 {
+    pub fn encode(value: u64) -> String {
+        let zero_bytes = value.leading_zeros() as usize / 8;
+        let digits = BASE58.encode(value);
+        let significant = digits.trim_start_matches('1');
+        format!("{}{significant}", "1".repeat(zero_bytes))
+    }
+
+    // Only accepts encodings of exactly 8 bytes.
+    pub fn decode(s: &str) -> Result<u64, Nano64Error> {
+        let rest = s.trim_start_matches('1');
+        let zero_bytes = s.len() - rest.len();
+        if rest.len() > BASE58_LENGTH {
+            return Err(Nano64Error::EncodedValueOverflow(BASE58.name));
+        }
+        let value = BASE58.decode(&format!("{rest:1>BASE58_LENGTH$}"))?;
+        let bytes = 8 - value.leading_zeros() as usize / 8 + zero_bytes;
+        if bytes != 8 {
+            return Err(Nano64Error::InvalidByteLength {
+                expected: 8,
+                got: bytes,
+            });
+        }
+        Ok(value)
+    }
+}
+
+// Fixed-width Base58 over the Bitcoin alphabet, padded with the zero digit `1` to
+// `BASE58_LENGTH` characters, so encoded strings sort like the values. Not the standard byte
+// encoding: other Base58 decoders read an extra leading zero byte for every `1` of padding.
+pub struct Base58Fixed;
+
+impl Base58Fixed {
+    pub fn encode(value: u64) -> String {
+        BASE58.encode(value)
+    }
+
+    pub fn decode(s: &str) -> Result<u64, Nano64Error> {
+        BASE58.decode(s)
+    }
+}
+
+// Number of characters produced by `Base32Hex::encode`. 32^13 = 2^65, so every u64 fits.
+pub const BASE32HEX_LENGTH: usize = 13;
+
+const BASE32HEX: FixedRadix = FixedRadix {
+    name: "base32hex",
+    alphabet: b"0123456789ABCDEFGHIJKLMNOPQRSTUV",
+    width: BASE32HEX_LENGTH,
+};
+
+// Fixed-width, zero-padded base32hex (RFC 4648 section 7 alphabet, no padding characters).
+//
+// Guarantee: for any `a` and `b`, `encode(a).cmp(&encode(b)) == a.cmp(&b)` under plain byte-wise
+// string comparison. Use it for keys in stores that only order lexically (S3, DynamoDB, LevelDB).
+// Decoding is case-sensitive so that only the ordered uppercase form is ever accepted.
+pub struct Base32Hex;
+
+impl Base32Hex {
+    pub fn encode(value: u64) -> String {
+        BASE32HEX.encode(value)
+    }
+
+    pub fn decode(s: &str) -> Result<u64, Nano64Error> {
+        BASE32HEX.decode(s)
+    }
+}
+
+// Length of a ULID string.
+pub const ULID_LENGTH: usize = 26;
+
+const CROCKFORD_BASE32: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+// ULIDs carry 80 random bits; a Nano64's 20 random bits go at the top, the rest are zero.
+const ULID_RANDOM_BITS: u32 = 80;
+const ULID_PADDING_BITS: u32 = ULID_RANDOM_BITS - RANDOM_BITS as u32;
+
+// Renders a Nano64 as a 26-char ULID (Crockford base32, 48-bit ms timestamp + 80 random bits)
+// for tooling that only understands ULIDs. The timestamp is carried as-is, so ULID ordering and
+// timestamps match the ID. Only ULIDs that came from a Nano64 (timestamp within 44 bits and the
+// low 60 random bits zero) decode back; anything else is rejected rather than truncated.
+pub struct Ulid;
+
+impl Ulid {
+    pub fn encode(value: u64) -> String {
+        let timestamp = (value >> TIMESTAMP_SHIFT) as u128;
+        let random = (value & RANDOM_MASK) as u128;
+        let bits = (timestamp << ULID_RANDOM_BITS) | (random << ULID_PADDING_BITS);
+        (0..ULID_LENGTH)
+            .rev()
+            .map(|i| CROCKFORD_BASE32[((bits >> (i * 5)) & 0x1F) as usize] as char)
+            .collect()
+    }
+
+    // Case-insensitive, as the ULID spec requires.
+    pub fn decode(s: &str) -> Result<u64, Nano64Error> {
+        if s.len() != ULID_LENGTH {
+            return Err(Nano64Error::InvalidEncodedLength {
+                encoding: "ULID",
+                expected: ULID_LENGTH,
+                got: s.len(),
+            });
+        }
+        let mut bits: u128 = 0;
+        for (i, c) in s.chars().enumerate() {
+            let upper = c.to_ascii_uppercase() as u8;
+            let digit = CROCKFORD_BASE32
+                .iter()
+                .position(|&d| c.is_ascii() && d == upper)
+                .filter(|&d| i > 0 || d < 8)
+                .ok_or(Nano64Error::InvalidEncodedChar {
+                    encoding: "ULID",
+                    character: c,
+                })?;
+            bits = (bits << 5) | digit as u128;
+        }
+        let timestamp = (bits >> ULID_RANDOM_BITS) as u64;
+        if timestamp > MAX_TIMESTAMP {
+            return Err(Nano64Error::TimeStampExceedsBitRange(timestamp));
+        }
+        if bits & ((1 << ULID_PADDING_BITS) - 1) != 0 {
+            return Err(Nano64Error::Error(
+                "ULID has random bits a Nano64 cannot carry".into(),
+            ));
+        }
+        let random = ((bits >> ULID_PADDING_BITS) as u64) & RANDOM_MASK;
+        Ok((timestamp << TIMESTAMP_SHIFT) | random)
+    }
+}
+
+// Longest LEB128 encoding of a u64.
+pub const MAX_VARINT_LENGTH: usize = 10;
+
+// Unsigned LEB128, as used for protobuf varints. Recent IDs have the top bits set, so expect
+// the full 10 bytes unless values are small (e.g. differences between IDs).
+pub struct Varint;
+
+impl Varint {
+    pub fn encode(value: u64) -> Vec<u8> {
+        let mut out = Vec::with_capacity(MAX_VARINT_LENGTH);
+        let mut rest = value;
+        while rest >= 0x80 {
+            out.push((rest as u8 & 0x7F) | 0x80);
+            rest >>= 7;
+        }
+        out.push(rest as u8);
+        out
+    }
+
+    // Decodes one varint from the start of `bytes`, returning the value and bytes consumed.
+    // Trailing bytes are left for the caller, so this can walk a stream.
+    pub fn decode(bytes: &[u8]) -> Result<(u64, usize), Nano64Error> {
+        let mut value: u64 = 0;
+        for (i, byte) in bytes.iter().take(MAX_VARINT_LENGTH).enumerate() {
+            let payload = (byte & 0x7F) as u64;
+            // The 10th byte may only carry the single top bit.
+            if i == MAX_VARINT_LENGTH - 1 && payload > 1 {
+                return Err(Nano64Error::EncodedValueOverflow("varint"));
+            }
+            value |= payload << (i * 7);
+            if byte & 0x80 == 0 {
+                return Ok((value, i + 1));
+            }
+        }
+        if bytes.len() >= MAX_VARINT_LENGTH {
+            return Err(Nano64Error::EncodedValueOverflow("varint"));
+        }
+        Err(Nano64Error::Error("truncated varint".into()))
+    }
+}
+
+const RADIX_DIGITS: &[u8; 36] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+
+// Variable-width conversion to and from any radix in 2..=36, lowercase, without padding or sign.
+// Parsing is case-insensitive.
+pub struct Radix;
+
+impl Radix {
+    pub fn encode(value: u64, radix: u32) -> Result<String, Nano64Error> {
+        check_radix(radix)?;
+        let radix = radix as u64;
+        let mut digits = Vec::with_capacity(64);
+        let mut rest = value;
+        loop {
+            digits.push(RADIX_DIGITS[(rest % radix) as usize]);
+            rest /= radix;
+            if rest == 0 {
+                break;
+            }
+        }
+        digits.reverse();
+        // Digits are ASCII.
+        Ok(String::from_utf8(digits).expect("radix digits are always ASCII"))
+    }
+
+    pub fn decode(s: &str, radix: u32) -> Result<u64, Nano64Error> {
+        check_radix(radix)?;
+        if s.is_empty() {
+            return Err(Nano64Error::InvalidEncodedLength {
+                encoding: "radix",
+                expected: 1,
+                got: 0,
+            });
+        }
+        let mut value: u64 = 0;
+        for c in s.chars() {
+            let digit = c.to_digit(radix).ok_or(Nano64Error::InvalidEncodedChar {
+                encoding: "radix",
+                character: c,
+            })?;
+            value = value
+                .checked_mul(radix as u64)
+                .and_then(|v| v.checked_add(digit as u64))
+                .ok_or(Nano64Error::EncodedValueOverflow("radix"))?;
+        }
+        Ok(value)
+    }
+}
+
+fn check_radix(radix: u32) -> Result<(), Nano64Error> {
+    if !(2..=36).contains(&radix) {
+        return Err(Nano64Error::InvalidRadix(radix));
+    }
+    Ok(())
+}
+
+// Crockford's check symbols: 32 base32 digits plus five extra symbols for a mod-37 checksum.
+const CHECK_SYMBOLS: &[u8; 37] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ*~$=U";
+
+// Canonical hex followed by one mod-37 check symbol, e.g. `0123456789A-BCDEF` + `Q`.
+//
+// 37 is prime and larger than any hex digit, so every single-character typo and every swap of
+// two adjacent digits changes the checksum. The 18-char form is also rejected by the plain hex
+// parser, so a checked ID is never silently read as a different one.
+pub struct CheckedHex;
+
+impl CheckedHex {
+    pub fn encode(value: u64) -> String {
+        let mut out = Nano64::new(value).to_hex();
+        out.push(check_symbol(value));
+        out
+    }
+
+    pub fn decode(s: &str) -> Result<u64, Nano64Error> {
+        let Some(last) = s.chars().last() else {
+            return Err(Nano64Error::InvalidEncodedLength {
+                encoding: "checked hex",
+                expected: CHECKED_HEX_LENGTH,
+                got: 0,
+            });
+        };
+        let value = s[..s.len() - last.len_utf8()]
+            .parse::<Nano64>()?
+            .u64_value();
+        let got = last.to_ascii_uppercase();
+        let expected = check_symbol(value);
+        if got != expected {
+            return Err(Nano64Error::CheckDigitMismatch { expected, got });
+        }
+        Ok(value)
+    }
+}
+
+// Length of `CheckedHex::encode` output.
+pub const CHECKED_HEX_LENGTH: usize = 18;
+
+fn check_symbol(value: u64) -> char {
+    CHECK_SYMBOLS[(value % 37) as usize] as char
+}
+
+// Length of an 8-byte value as unpadded base64url.
+pub const SLUG_LENGTH: usize = 11;
+
+const BASE64URL_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+// Unpadded base64url (RFC 4648 section 5) for arbitrary bytes. Unlike the radix encodings above,
+// output does not sort like the input. Decoding is strict: padding, other alphabets and
+// non-zero trailing bits are rejected, so each input has exactly one accepted encoding.
+pub struct Base64Url;
+
+impl Base64Url {
+    pub fn encode(bytes: &[u8]) -> String {
+        let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+        for chunk in bytes.chunks(3) {
+            let mut group = [0u8; 3];
+            group[..chunk.len()].copy_from_slice(chunk);
+            let n = u32::from_be_bytes([0, group[0], group[1], group[2]]);
+            for i in 0..=chunk.len() {
+                out.push(BASE64URL_ALPHABET[((n >> (18 - i * 6)) & 0x3F) as usize] as char);
+            }
+        }
+        out
+    }
+
+    pub fn decode(s: &str) -> Result<Vec<u8>, Nano64Error> {
+        if s.len() % 4 == 1 {
+            return Err(Nano64Error::InvalidEncodedLength {
+                encoding: "base64url",
+                expected: s.len() - 1,
+                got: s.len(),
+            });
+        }
+        let mut out = Vec::with_capacity(s.len() * 3 / 4);
+        let mut acc: u32 = 0;
+        let mut bits = 0;
+        for c in s.chars() {
+            let sextet = match c {
+                'A'..='Z' => c as u32 - 'A' as u32,
+                'a'..='z' => c as u32 - 'a' as u32 + 26,
+                '0'..='9' => c as u32 - '0' as u32 + 52,
+                '-' => 62,
+                '_' => 63,
+                _ => {
+                    return Err(Nano64Error::InvalidEncodedChar {
+                        encoding: "base64url",
+                        character: c,
+                    });
+                }
+            };
+            acc = (acc << 6) | sextet;
+            bits += 6;
+            if bits >= 8 {
+                bits -= 8;
+                out.push((acc >> bits) as u8);
+                acc &= (1 << bits) - 1;
+            }
+        }
+        if acc != 0 {
+            return Err(Nano64Error::Error(
+                "base64url input has non-zero trailing bits".into(),
+            ));
+        }
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        BASE32HEX_LENGTH, BASE58_LENGTH, BASE62_LENGTH, Base32Hex, Base58, Base58Fixed, Base62,
+        Base64Url, CHECKED_HEX_LENGTH, CheckedHex, MAX_VARINT_LENGTH, Nano64, Nano64Error, Radix,
+        SLUG_LENGTH, ULID_LENGTH, Ulid, Varint,
+    };
+
+    #[test]
+    fn test_base62_round_trip() {
+        for value in [0, 1, 61, 62, u64::MAX] {
+            let encoded = Base62::encode(value);
+            assert_eq!(encoded.len(), BASE62_LENGTH);
+            assert_eq!(Base62::decode(&encoded).unwrap(), value);
+        }
+        assert_eq!(Base62::encode(0), "00000000000");
+        assert_eq!(Base62::encode(u64::MAX), "LygHa16AHYF");
+        for _ in 0..1000 {
+            let id = Nano64::generate_default().unwrap();
+            assert_eq!(Nano64::from_base62(&id.to_base62()).unwrap(), id);
+        }
+    }
+
+    #[test]
+    fn test_base62_preserves_order() {
+        let mut ids: Vec<Nano64> = (0..200)
+            .map(|_| Nano64::generate_default().unwrap())
+            .collect();
+        ids.push(Nano64::MIN);
+        ids.push(Nano64::MAX);
+        let mut tokens: Vec<String> = ids.iter().map(|id| id.to_base62()).collect();
+        ids.sort();
+        tokens.sort();
+        let decoded: Vec<Nano64> = tokens
+            .iter()
+            .map(|t| Nano64::from_base62(t).unwrap())
+            .collect();
+        assert_eq!(ids, decoded);
+    }
+
+    #[test]
+    fn test_base62_rejects_invalid() {
+        assert!(matches!(
+            Base62::decode("abc"),
+            Err(Nano64Error::InvalidEncodedLength { got: 3, .. })
+        ));
+        assert!(matches!(
+            Base62::decode("0000000000-"),
+            Err(Nano64Error::InvalidEncodedChar { character: '-', .. })
+        ));
+        // One past "LygHa16AHYF" (u64::MAX).
+        assert!(matches!(
+            Base62::decode("LygHa16AHYG"),
+            Err(Nano64Error::EncodedValueOverflow(_))
+        ));
+    }
+
+    #[test]
+    fn test_base58_round_trip() {
+        // Same output as any Base58 encoder given the 8 big-endian bytes.
+        assert_eq!(Base58::encode(0), "11111111");
+        assert_eq!(Base58::encode(57), "1111111z");
+        assert_eq!(Base58::encode(58), "111111121");
+        assert_eq!(Base58::encode(1 << 56), "Ahg1opVcGX");
+        assert_eq!(Base58::encode(u64::MAX), "jpXCZedGfVQ");
+        for value in [0, 1, 57, 58, 1 << 56, u64::MAX] {
+            assert_eq!(Base58::decode(&Base58::encode(value)).unwrap(), value);
+        }
+        for _ in 0..1000 {
+            let id = Nano64::generate_default().unwrap();
+            assert_eq!(Nano64::from_base58(&id.to_base58()).unwrap(), id);
+        }
+
+        // Encodings of more or fewer than 8 bytes.
+        for bad in ["", "1111111", "2", "111111111", "11111111z"] {
+            assert!(
+                matches!(
+                    Base58::decode(bad),
+                    Err(Nano64Error::InvalidByteLength { expected: 8, .. })
+                ),
+                "{bad}"
+            );
+        }
+        assert!(Base58::decode("jpXCZedGfVR").is_err());
+        assert!(Base58::decode("jpXCZedGfVQ1").is_err());
+    }
+
+    #[test]
+    fn test_base58_fixed_round_trip() {
+        assert_eq!(Base58Fixed::encode(0), "11111111111");
+        assert_eq!(Base58Fixed::encode(57), "1111111111z");
+        assert_eq!(Base58Fixed::encode(58), "11111111121");
+        for value in [0, 1, 57, 58, u64::MAX] {
+            let encoded = Base58Fixed::encode(value);
+            assert_eq!(encoded.len(), BASE58_LENGTH);
+            assert_eq!(Base58Fixed::decode(&encoded).unwrap(), value);
+        }
+        let ids: Vec<Nano64> = (0..100)
+            .map(|_| Nano64::generate_default().unwrap())
+            .collect();
+        for pair in ids.windows(2) {
+            assert_eq!(
+                pair[0].to_base58_fixed().cmp(&pair[1].to_base58_fixed()),
+                pair[0].cmp(&pair[1])
+            );
+        }
+        for id in ids {
+            assert_eq!(
+                Nano64::from_base58_fixed(&id.to_base58_fixed()).unwrap(),
+                id
+            );
+        }
+    }
+
+    #[test]
+    fn test_base58_rejects_ambiguous_chars() {
+        for bad in ['0', 'O', 'I', 'l'] {
+            let s = format!("1111111111{bad}");
+            assert!(matches!(
+                Base58Fixed::decode(&s),
+                Err(Nano64Error::InvalidEncodedChar { character, encoding: "base58" }) if character == bad
+            ));
+        }
+        assert!(matches!(
+            Base58Fixed::decode("zzzzzzzzzzz"),
+            Err(Nano64Error::EncodedValueOverflow("base58"))
+        ));
+        assert!(matches!(
+            Base58::decode("zzzzzzzzzzz"),
+            Err(Nano64Error::EncodedValueOverflow("base58"))
+        ));
+        assert!(matches!(
+            Base58::decode("11O"),
+            Err(Nano64Error::InvalidEncodedChar { character: 'O', .. })
+        ));
+    }
+
+    #[test]
+    fn test_base64url() {
+        // RFC 4648 test vectors, unpadded.
+        for (raw, encoded) in [
+            ("", ""),
+            ("f", "Zg"),
+            ("fo", "Zm8"),
+            ("foo", "Zm9v"),
+            ("foob", "Zm9vYg"),
+            ("fooba", "Zm9vYmE"),
+            ("foobar", "Zm9vYmFy"),
+        ] {
+            assert_eq!(Base64Url::encode(raw.as_bytes()), encoded);
+            assert_eq!(Base64Url::decode(encoded).unwrap(), raw.as_bytes());
+        }
+        assert_eq!(Base64Url::encode(&[0xFB, 0xFF]), "-_8");
+        assert!(Base64Url::decode("Zg==").is_err());
+        assert!(Base64Url::decode("Zh").is_err());
+        assert!(Base64Url::decode("Z").is_err());
+        assert!(Base64Url::decode("+/8").is_err());
+    }
+
+    #[test]
+    fn test_nano64_slug() {
+        let id = Nano64::new(0x0123456789ABCDEF);
+        assert_eq!(id.to_slug(), "ASNFZ4mrze8");
+        for _ in 0..1000 {
+            let id = Nano64::generate_default().unwrap();
+            let slug = id.to_slug();
+            assert_eq!(slug.len(), SLUG_LENGTH);
+            assert_eq!(Nano64::from_slug(&slug).unwrap(), id);
+        }
+        assert!(matches!(
+            Nano64::from_slug("ASNFZ4mr"),
+            Err(Nano64Error::InvalidByteLength {
+                expected: 8,
+                got: 6
+            })
+        ));
+    }
+
+    #[test]
+    fn test_radix() {
+        assert_eq!(Radix::encode(0, 2).unwrap(), "0");
+        assert_eq!(Radix::encode(255, 2).unwrap(), "11111111");
+        assert_eq!(Radix::encode(u64::MAX, 36).unwrap(), "3w5e11264sgsf");
+        assert_eq!(Radix::decode("3W5E11264SGSF", 36).unwrap(), u64::MAX);
+        for radix in 2..=36 {
+            for value in [0, 1, 35, 1 << 40, u64::MAX] {
+                let encoded = Radix::encode(value, radix).unwrap();
+                assert_eq!(encoded, format_radix_reference(value, radix));
+                assert_eq!(Radix::decode(&encoded, radix).unwrap(), value);
+            }
+        }
+    }
+
+    fn format_radix_reference(value: u64, radix: u32) -> String {
+        let mut out = String::new();
+        let mut rest = value;
+        loop {
+            out.insert(
+                0,
+                char::from_digit((rest % radix as u64) as u32, radix).unwrap(),
+            );
+            rest /= radix as u64;
+            if rest == 0 {
+                return out;
+            }
+        }
+    }
+
+    #[test]
+    fn test_radix_rejects_invalid() {
+        assert!(matches!(
+            Radix::encode(1, 1),
+            Err(Nano64Error::InvalidRadix(1))
+        ));
+        assert!(matches!(
+            Radix::decode("1", 37),
+            Err(Nano64Error::InvalidRadix(37))
+        ));
+        assert!(Radix::decode("", 10).is_err());
+        assert!(Radix::decode("+1", 10).is_err());
+        assert!(Radix::decode("2", 2).is_err());
+        assert!(matches!(
+            Radix::decode("18446744073709551616", 10),
+            Err(Nano64Error::EncodedValueOverflow(_))
+        ));
+        let id = Nano64::generate_default().unwrap();
+        assert_eq!(
+            Nano64::from_string_radix(&id.to_string_radix(36).unwrap(), 36).unwrap(),
+            id
+        );
+    }
+
+    #[test]
+    fn test_checked_hex_round_trip() {
+        assert_eq!(CheckedHex::encode(0), "00000000000-000000");
+        assert_eq!(CheckedHex::encode(36), "00000000000-00024U");
+        for _ in 0..1000 {
+            let id = Nano64::generate_default().unwrap();
+            let checked = id.to_checked_hex();
+            assert_eq!(checked.len(), CHECKED_HEX_LENGTH);
+            assert_eq!(Nano64::from_checked_hex(&checked).unwrap(), id);
+            assert_eq!(
+                Nano64::from_checked_hex(&checked.to_lowercase()).unwrap(),
+                id
+            );
+            // Not accepted as plain hex.
+            assert!(checked.parse::<Nano64>().is_err());
+        }
+    }
+
+    #[test]
+    fn test_checked_hex_detects_typos() {
+        let checked = Nano64::new(0x0123456789ABCDEF).to_checked_hex();
+        let chars: Vec<char> = checked.chars().collect();
+        let digits: Vec<usize> = (0..17).filter(|&i| chars[i] != '-').collect();
+        // Every single-digit substitution.
+        for &i in &digits {
+            for sub in "0123456789ABCDEF".chars().filter(|&c| c != chars[i]) {
+                let mut typo = chars.clone();
+                typo[i] = sub;
+                let typo: String = typo.into_iter().collect();
+                assert!(CheckedHex::decode(&typo).is_err(), "{typo} accepted");
+            }
+        }
+        // Every adjacent transposition of distinct digits.
+        for pair in digits.windows(2) {
+            if chars[pair[0]] != chars[pair[1]] {
+                let mut typo = chars.clone();
+                typo.swap(pair[0], pair[1]);
+                let typo: String = typo.into_iter().collect();
+                assert!(matches!(
+                    CheckedHex::decode(&typo),
+                    Err(Nano64Error::CheckDigitMismatch { .. })
+                ));
+            }
+        }
+        assert!(CheckedHex::decode("").is_err());
+    }
+
+    #[test]
+    fn test_base32hex_order_matches_numeric_order() {
+        assert_eq!(Base32Hex::encode(0), "0000000000000");
+        assert_eq!(Base32Hex::encode(u64::MAX), "FVVVVVVVVVVVV");
+        let mut values: Vec<u64> = (0..64).map(|i| 1u64 << i).collect();
+        values.extend((0..64).map(|i| (1u64 << i) - 1));
+        values.push(u64::MAX);
+        values.extend((0..500).map(|_| Nano64::generate_default().unwrap().u64_value()));
+        for a in &values {
+            let ea = Base32Hex::encode(*a);
+            assert_eq!(ea.len(), BASE32HEX_LENGTH);
+            assert_eq!(Base32Hex::decode(&ea).unwrap(), *a);
+        }
+        for pair in values.windows(2) {
+            let (ea, eb) = (Base32Hex::encode(pair[0]), Base32Hex::encode(pair[1]));
+            assert_eq!(ea.cmp(&eb), pair[0].cmp(&pair[1]));
+        }
+        let id = Nano64::generate_default().unwrap();
+        assert_eq!(
+            Nano64::from_sortable_string(&id.to_sortable_string()).unwrap(),
+            id
+        );
+    }
+
+    #[test]
+    fn test_base32hex_rejects_invalid() {
+        assert!(matches!(
+            Base32Hex::decode("G000000000000"),
+            Err(Nano64Error::EncodedValueOverflow(_))
+        ));
+        assert!(Base32Hex::decode("000000000000v").is_err());
+        assert!(Base32Hex::decode("000000000000W").is_err());
+        assert!(Base32Hex::decode("0").is_err());
+    }
+
+    #[test]
+    fn test_ulid_round_trip() {
+        assert_eq!(Ulid::encode(0), "00000000000000000000000000");
+        let id = Nano64::from_parts(1_469_918_176_385, 0xFFFFF).unwrap();
+        // Timestamp part matches the ULID spec example "01ARYZ6S41...".
+        assert_eq!(id.to_ulid(), "01ARYZ6S41ZZZZ000000000000");
+        assert_eq!(Nano64::from_ulid(&id.to_ulid()).unwrap(), id);
+        assert_eq!(Nano64::from_ulid(&id.to_ulid().to_lowercase()).unwrap(), id);
+
+        let mut ids: Vec<Nano64> = (0..200)
+            .map(|_| Nano64::generate_default().unwrap())
+            .collect();
+        ids.push(Nano64::MAX);
+        for id in &ids {
+            let ulid = id.to_ulid();
+            assert_eq!(ulid.len(), ULID_LENGTH);
+            assert_eq!(Nano64::from_ulid(&ulid).unwrap(), *id);
+        }
+        let mut ulids: Vec<String> = ids.iter().map(|id| id.to_ulid()).collect();
+        ids.sort();
+        ulids.sort();
+        assert_eq!(ulids, ids.iter().map(|id| id.to_ulid()).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_ulid_rejects_foreign_values() {
+        // A real ULID with random bits past the first 20.
+        assert!(Ulid::decode("01ARYZ6S41TSV4RRFFQ69G5FAV").is_err());
+        // 48-bit timestamp beyond the 44-bit range.
+        assert!(matches!(
+            Ulid::decode("7ZZZZZZZZZ0000000000000000"),
+            Err(Nano64Error::TimeStampExceedsBitRange(_))
+        ));
+        // First character above 7 would overflow 128 bits.
+        assert!(Ulid::decode("80000000000000000000000000").is_err());
+        assert!(Ulid::decode("0000000000000000000000000U").is_err());
+        assert!(Ulid::decode("0000").is_err());
+        // Non-ASCII must not alias an ASCII digit after truncation ('Ł' is U+0141, 2 bytes).
+        assert!(Ulid::decode("000000000000000000000000Ł").is_err());
+    }
+
+    #[test]
+    fn test_varint_boundaries() {
+        let cases: [(u64, &[u8]); 6] = [
+            (0, &[0x00]),
+            (1, &[0x01]),
+            (127, &[0x7F]),
+            (128, &[0x80, 0x01]),
+            (300, &[0xAC, 0x02]),
+            (
+                u64::MAX,
+                &[0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x01],
+            ),
+        ];
+        for (value, encoded) in cases {
+            assert_eq!(Varint::encode(value), encoded);
+            assert_eq!(Varint::decode(encoded).unwrap(), (value, encoded.len()));
+        }
+        for shift in 0..64 {
+            for value in [1u64 << shift, (1u64 << shift) - 1] {
+                let encoded = Varint::encode(value);
+                assert!(encoded.len() <= MAX_VARINT_LENGTH);
+                assert_eq!(Varint::decode(&encoded).unwrap().0, value);
+            }
+        }
+    }
+
+    #[test]
+    fn test_varint_stream_and_errors() {
+        let a = Nano64::generate_default().unwrap();
+        let b = Nano64::new(5);
+        let mut stream = a.to_varint();
+        stream.extend(b.to_varint());
+        let (first, used) = Nano64::from_varint(&stream).unwrap();
+        assert_eq!(first, a);
+        assert_eq!(Nano64::from_varint(&stream[used..]).unwrap(), (b, 1));
+
+        assert!(Varint::decode(&[]).is_err());
+        assert!(Varint::decode(&[0x80, 0x80]).is_err());
+        assert!(matches!(
+            Varint::decode(&[0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x02]),
+            Err(Nano64Error::EncodedValueOverflow(_))
+        ));
+        assert!(matches!(
+            Varint::decode(&[0x80; 11]),
+            Err(Nano64Error::EncodedValueOverflow(_))
+        ));
+    }
+}
@@ -0,0 +1,185 @@
+use crate::errors::*;
+use hex::FromHex;
+use std::str;
+
+// Lookup table for allocation-free hex encoding.
+pub(crate) const HEX_UPPER: &[u8; 16] = b"0123456789ABCDEF";
+pub(crate) const HEX_LOWER: &[u8; 16] = b"0123456789abcdef";
+
+// Output options for `Nano64::to_hex_with`. The default matches `to_hex`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HexFormat {
+    pub uppercase: bool,
+    // Separator inserted after `separator_position` digits. `None` for a plain 16-digit string.
+    pub separator: Option<char>,
+    // Number of digits before the separator. Values outside 1..16 omit the separator.
+    pub separator_position: usize,
+    // Prepend `0x`.
+    pub prefix: bool,
+}
+
+impl Default for HexFormat {
+    fn default() -> Self {
+        Self {
+            uppercase: true,
+            separator: Some('-'),
+            separator_position: 11,
+            prefix: false,
+        }
+    }
+}
+
+impl HexFormat {
+    // 16 digits, no separator or prefix.
+    pub fn plain() -> Self {
+        Self {
+            separator: None,
+            ..Self::default()
+        }
+    }
+
+    pub(crate) fn format(&self, value: u64) -> String {
+        let mut out = String::with_capacity(2 + 16 + 4);
+        if self.prefix {
+            out.push_str("0x");
+        }
+        let digits: &[u8; 16] = if self.uppercase { HEX_UPPER } else { HEX_LOWER };
+        for i in 0..16 {
+            if i == self.separator_position
+                && (1..16).contains(&self.separator_position)
+                && let Some(sep) = self.separator
+            {
+                out.push(sep);
+            }
+            out.push(digits[((value >> (60 - i * 4)) & 0xF) as usize] as char);
+        }
+        out
+    }
+}
+
+pub struct Hex;
+
+impl Hex {
+    pub fn from_bytes(bytes: &[u8]) -> String {
+        hex::encode_upper(bytes)
+    }
+
+    pub fn to_bytes(hex_str: &str) -> Result<Vec<u8>, Nano64Error> {
+        Self::digits_to_bytes(hex_str.strip_prefix("0x").unwrap_or(hex_str))
+    }
+
+    // Like `to_bytes`, also accepting surrounding whitespace and a `0X` prefix, the input a
+    // person is likely to paste. At most one prefix is stripped.
+    #[cfg(feature = "encryption")]
+    pub(crate) fn to_bytes_lenient(hex_str: &str) -> Result<Vec<u8>, Nano64Error> {
+        let h = hex_str.trim();
+        let digits = match h.get(..2) {
+            Some(prefix) if prefix.eq_ignore_ascii_case("0x") => &h[2..],
+            _ => h,
+        };
+        Self::digits_to_bytes(digits)
+    }
+
+    // Only the exact form `from_bytes` writes: uppercase digits, no prefix or whitespace.
+    #[cfg(feature = "encryption")]
+    pub(crate) fn to_bytes_strict(hex_str: &str) -> Result<Vec<u8>, Nano64Error> {
+        if let Some(c) = hex_str
+            .chars()
+            .find(|c| !matches!(c, '0'..='9' | 'A'..='F'))
+        {
+            return Err(Nano64Error::InvalidEncodedChar {
+                encoding: "uppercase hex",
+                character: c,
+            });
+        }
+        Self::to_bytes(hex_str)
+    }
+
+    fn digits_to_bytes(h: &str) -> Result<Vec<u8>, Nano64Error> {
+        if !h.len().is_multiple_of(2) {
+            return Err(Nano64Error::HexStringNotEvenCharacters);
+        }
+        Vec::from_hex(h).map_err(|_| Nano64Error::HexStringContainsNonHexChars)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Hex, HexFormat, Nano64Error};
+
+    #[test]
+    fn test_deserialize() {
+        let og_string = "ABCD";
+        let og_string_bytes = Hex::to_bytes(og_string).unwrap();
+        let og_string_deserial = Hex::from_bytes(&og_string_bytes);
+        assert!(og_string_deserial.starts_with(og_string));
+    }
+
+    #[test]
+    fn test_to_bytes_valid_hex() {
+        let hex = "0x12AB34";
+        let bytes = Hex::to_bytes(hex).unwrap();
+        assert_eq!(bytes, [0x12, 0xAB, 0x34]);
+    }
+
+    #[test]
+    fn test_to_bytes_no_prefix() {
+        let hex = "12AB34";
+        let bytes = Hex::to_bytes(hex).unwrap();
+        assert_eq!(bytes, [0x12, 0xAB, 0x34]);
+    }
+
+    #[test]
+    fn test_to_bytes_odd_length() {
+        let hex = "123";
+        let err = Hex::to_bytes(hex).unwrap_err();
+        assert!(matches!(err, Nano64Error::HexStringNotEvenCharacters));
+    }
+
+    #[test]
+    fn test_to_bytes_non_hex_chars() {
+        let hex = "12G4";
+        let err = Hex::to_bytes(hex).unwrap_err();
+        assert!(matches!(err, Nano64Error::HexStringContainsNonHexChars));
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn test_to_bytes_lenient() {
+        for input in ["12ab", "0x12AB", "0X12ab", " 0x12ab\n"] {
+            assert_eq!(
+                Hex::to_bytes_lenient(input).unwrap(),
+                [0x12, 0xAB],
+                "{input:?}"
+            );
+        }
+        // Only one prefix comes off.
+        for input in ["0x0x12ab", "0X0x12ab", "0X0X12ab", "0x 12ab"] {
+            assert!(Hex::to_bytes_lenient(input).is_err(), "{input:?}");
+        }
+    }
+
+    #[test]
+    fn test_hex_format() {
+        let value = 0x0123456789ABCDEF;
+        assert_eq!(HexFormat::default().format(value), "0123456789A-BCDEF");
+        assert_eq!(HexFormat::plain().format(value), "0123456789ABCDEF");
+        let custom = HexFormat {
+            uppercase: false,
+            separator: Some('_'),
+            separator_position: 4,
+            prefix: true,
+        };
+        assert_eq!(custom.format(value), "0x0123_456789abcdef");
+        let out_of_range = HexFormat {
+            separator_position: 16,
+            ..HexFormat::default()
+        };
+        assert_eq!(out_of_range.format(value), "0123456789ABCDEF");
+        let leading = HexFormat {
+            separator_position: 0,
+            ..HexFormat::default()
+        };
+        assert_eq!(leading.format(value), "0123456789ABCDEF");
+    }
+}
@@ -0,0 +1,120 @@
+use crate::Nano64Error;
+use rand::{RngCore, SeedableRng, rngs::StdRng};
+use std::cell::RefCell;
+
+// Bytes of randomness generated per refill.
+const POOL_SIZE: usize = 16 * 1024;
+// Refills between reseeds of the pool's CSPRNG from the OS (1 MiB of output).
+const RESEED_AFTER_REFILLS: u32 = 64;
+
+// Per-thread buffer of CSPRNG output, handed out a few bits at a time. Served bytes are zeroed so
+// the buffer never holds randomness that was already used.
+pub(crate) struct EntropyPool {
+    rng: StdRng,
+    buf: Box<[u8]>,
+    pos: usize,
+    reservoir: u64,
+    reservoir_bits: u32,
+    refills: u32,
+}
+
+impl EntropyPool {
+    pub(crate) fn new() -> Self {
+        Self {
+            rng: StdRng::from_os_rng(),
+            buf: vec![0u8; POOL_SIZE].into_boxed_slice(),
+            // Empty, so the first request refills.
+            pos: POOL_SIZE,
+            reservoir: 0,
+            reservoir_bits: 0,
+            refills: 0,
+        }
+    }
+
+    // `bits` must be 1-32.
+    pub(crate) fn take(&mut self, bits: u32) -> u32 {
+        while self.reservoir_bits < bits {
+            if self.pos == self.buf.len() {
+                self.refill();
+            }
+            self.reservoir |= (self.buf[self.pos] as u64) << self.reservoir_bits;
+            self.buf[self.pos] = 0;
+            self.pos += 1;
+            self.reservoir_bits += 8;
+        }
+        let value = self.reservoir & ((1u64 << bits) - 1);
+        self.reservoir >>= bits;
+        self.reservoir_bits -= bits;
+        value as u32
+    }
+
+    fn refill(&mut self) {
+        if self.refills == RESEED_AFTER_REFILLS {
+            self.rng = StdRng::from_os_rng();
+            self.refills = 0;
+        }
+        self.rng.fill_bytes(&mut self.buf);
+        self.refills += 1;
+        self.pos = 0;
+    }
+}
+
+thread_local! {
+    static ENTROPY_POOL: RefCell<EntropyPool> = RefCell::new(EntropyPool::new());
+}
+
+// Cryptographically-secure RNG backed by a ChaCha CSPRNG per thread, so threads never contend
+// on a shared entropy source. OS and CSPRNG calls are amortized by buffering 16 KiB of output
+// and slicing each request out of it, reseeding from the OS every 1 MiB. A much faster drop-in
+// for the default RNG at millions of IDs per second: pass `Some(pooled_rng)`,
+// `with_rng(pooled_rng)` or `set_default_rng_fn(pooled_rng)`, or call `Nano64::generate_fast()`.
+// `bits` must be in the 1-32 range.
+pub fn pooled_rng(bits: u32) -> Result<u32, Nano64Error> {
+    if bits == 0 || bits > 32 {
+        return Err(Nano64Error::RNGOutOfBounds(bits));
+    }
+    Ok(ENTROPY_POOL.with_borrow_mut(|pool| pool.take(bits)))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Nano64, Nano64Error, entropy_pool::EntropyPool, pooled_rng};
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_pooled_rng_respects_bits() {
+        for bits in [1, 7, 20, 32] {
+            for _ in 0..100 {
+                let value = pooled_rng(bits).unwrap() as u64;
+                assert!(value < (1u64 << bits));
+            }
+        }
+        assert!(matches!(pooled_rng(0), Err(Nano64Error::RNGOutOfBounds(0))));
+        assert!(matches!(
+            pooled_rng(33),
+            Err(Nano64Error::RNGOutOfBounds(33))
+        ));
+    }
+
+    #[test]
+    fn test_entropy_pool_refills_and_reseeds() {
+        let mut pool = EntropyPool::new();
+        // Enough 20-bit draws to go through several refills and one reseed.
+        let draws = super::POOL_SIZE * 8 / 20 * (super::RESEED_AFTER_REFILLS as usize + 2);
+        let mut ones = 0u64;
+        for _ in 0..draws {
+            ones += pool.take(20).count_ones() as u64;
+        }
+        let mean = ones as f64 / draws as f64;
+        assert!((mean - 10.0).abs() < 0.05, "mean set bits {mean}");
+        assert!(pool.refills >= 1 && pool.refills <= super::RESEED_AFTER_REFILLS);
+        assert!(pool.buf[..pool.pos].iter().all(|&b| b == 0));
+    }
+
+    #[test]
+    fn test_pooled_rng_generates_distinct_ids() {
+        let ids = Nano64::generate_batch(1000, Some(pooled_rng)).unwrap();
+        let random: HashSet<u32> = ids.iter().map(|id| id.get_random()).collect();
+        assert!(random.len() > 900);
+    }
+}
@@ -0,0 +1,284 @@
+use crate::{MAX_TIMESTAMP, Nano64, Nano64Error, RANDOM_MASK, TIMESTAMP_SHIFT};
+use std::{
+    ops::RangeInclusive,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+// How long one step of the timestamp field lasts. Coarser ticks make every tick's 2^20 random
+// values go further (fewer collisions at the same ID rate) and stretch the ~557-year horizon of
+// the 44-bit field by the same factor, at the cost of ordering only to within a tick.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum TimestampPrecision {
+    #[default]
+    Millis,
+    TenMillis,
+    Seconds,
+}
+
+impl TimestampPrecision {
+    pub const fn tick_ms(&self) -> u64 {
+        match self {
+            TimestampPrecision::Millis => 1,
+            TimestampPrecision::TenMillis => 10,
+            TimestampPrecision::Seconds => 1000,
+        }
+    }
+}
+
+// The moment an ID timestamp of zero refers to, and the unit it counts in. IDs record neither,
+// so every generator, validator and decoder that handles them must be configured with the same
+// epoch. Moving the epoch forward spends the 44-bit timestamp budget (~557 years at millisecond
+// precision) on the future instead of on 1970-2020.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Epoch {
+    unix_ms: u64,
+    precision: TimestampPrecision,
+}
+
+impl Epoch {
+    // 1970-01-01T00:00:00Z, the default.
+    pub const UNIX: Epoch = Epoch::from_unix_ms(0);
+    // 2020-01-01T00:00:00Z.
+    pub const Y2020: Epoch = Epoch::from_unix_ms(1_577_836_800_000);
+
+    pub const fn from_unix_ms(unix_ms: u64) -> Self {
+        Self {
+            unix_ms,
+            precision: TimestampPrecision::Millis,
+        }
+    }
+
+    // The same epoch with timestamps counting in `precision` ticks instead of milliseconds.
+    pub const fn with_precision(mut self, precision: TimestampPrecision) -> Self {
+        self.precision = precision;
+        self
+    }
+
+    pub const fn precision(&self) -> TimestampPrecision {
+        self.precision
+    }
+
+    // Sub-millisecond precision is truncated. Times before the UNIX epoch are rejected.
+    pub fn from_system_time(time: SystemTime) -> Result<Self, Nano64Error> {
+        let since_epoch = time
+            .duration_since(UNIX_EPOCH)
+            .map_err(|_| Nano64Error::Error("epoch is before the UNIX epoch".into()))?;
+        let unix_ms = u64::try_from(since_epoch.as_millis())
+            .map_err(|_| Nano64Error::TimeStampExceedsBitRange(u64::MAX))?;
+        Ok(Self::from_unix_ms(unix_ms))
+    }
+
+    pub const fn unix_ms(&self) -> u64 {
+        self.unix_ms
+    }
+
+    // Converts milliseconds since the UNIX epoch to an ID timestamp: ticks since this epoch,
+    // rounded down.
+    pub fn to_timestamp(&self, unix_ms: u64) -> Result<u64, Nano64Error> {
+        let since_epoch =
+            unix_ms
+                .checked_sub(self.unix_ms)
+                .ok_or(Nano64Error::TimeStampBeforeEpoch {
+                    unix_ms,
+                    epoch_ms: self.unix_ms,
+                })?;
+        let timestamp = since_epoch / self.precision.tick_ms();
+        if timestamp > MAX_TIMESTAMP {
+            return Err(Nano64Error::TimeStampExceedsBitRange(timestamp));
+        }
+        Ok(timestamp)
+    }
+
+    // Converts an ID timestamp relative to this epoch to milliseconds since the UNIX epoch,
+    // i.e. the start of its tick.
+    pub fn to_unix_ms(&self, timestamp: u64) -> u64 {
+        self.unix_ms
+            .saturating_add(timestamp.saturating_mul(self.precision.tick_ms()))
+    }
+
+    // The ID's timestamp in milliseconds since the UNIX epoch. `Nano64::get_timestamp` reports
+    // the raw field, i.e. ticks since this epoch.
+    pub fn timestamp_ms(&self, id: &Nano64) -> u64 {
+        self.to_unix_ms(id.get_timestamp())
+    }
+
+    // Like `Nano64::to_date`, for an ID generated with this epoch. Fails if the platform's
+    // `SystemTime` can't represent the result, which a far-off epoch can cause.
+    pub fn to_date(&self, id: &Nano64) -> Result<SystemTime, Nano64Error> {
+        unix_ms_to_system_time(self.to_unix_ms(id.get_timestamp()))
+    }
+
+    // Latest moment this epoch can represent. Fails like `to_date`.
+    pub fn max_date(&self) -> Result<SystemTime, Nano64Error> {
+        unix_ms_to_system_time(self.to_unix_ms(MAX_TIMESTAMP))
+    }
+
+    // Smallest and largest IDs whose timestamps fall within `start..=end`, e.g. for a
+    // `BETWEEN` query on an ID column.
+    pub fn id_range(
+        &self,
+        start: SystemTime,
+        end: SystemTime,
+    ) -> Result<RangeInclusive<Nano64>, Nano64Error> {
+        if start > end {
+            return Err(Nano64Error::TimeStampRangeError);
+        }
+        let start = self.to_timestamp(system_time_ms(start)?)?;
+        let end = self.to_timestamp(system_time_ms(end)?)?;
+        Ok(Nano64::new(start << TIMESTAMP_SHIFT)
+            ..=Nano64::new((end << TIMESTAMP_SHIFT) | RANDOM_MASK))
+    }
+}
+
+fn system_time_ms(time: SystemTime) -> Result<u64, Nano64Error> {
+    let since_epoch = time
+        .duration_since(UNIX_EPOCH)
+        .map_err(|_| Nano64Error::Error("time is before the UNIX epoch".into()))?;
+    Ok(u64::try_from(since_epoch.as_millis()).unwrap_or(u64::MAX))
+}
+
+fn unix_ms_to_system_time(unix_ms: u64) -> Result<SystemTime, Nano64Error> {
+    UNIX_EPOCH
+        .checked_add(Duration::from_millis(unix_ms))
+        .ok_or_else(|| {
+            Nano64Error::Error(format!(
+                "{unix_ms}ms after the UNIX epoch is not a SystemTime"
+            ))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Epoch, Nano64, Nano64Error, Nano64Generator, ValidationPolicy, testing::MockClock,
+    };
+    use std::time::{Duration, UNIX_EPOCH};
+
+    const JAN_2025_MS: u64 = 1_735_689_600_000;
+
+    #[test]
+    fn test_epoch_round_trip() {
+        let epoch = Epoch::Y2020;
+        let timestamp = epoch.to_timestamp(JAN_2025_MS).unwrap();
+        assert_eq!(timestamp, JAN_2025_MS - 1_577_836_800_000);
+        assert_eq!(epoch.to_unix_ms(timestamp), JAN_2025_MS);
+        assert!(matches!(
+            epoch.to_timestamp(1_000),
+            Err(Nano64Error::TimeStampBeforeEpoch { unix_ms: 1_000, .. })
+        ));
+        assert_eq!(
+            Epoch::from_system_time(UNIX_EPOCH + Duration::from_millis(1_577_836_800_000)).unwrap(),
+            Epoch::Y2020
+        );
+        assert!(epoch.max_date().unwrap() > Nano64::LAYOUT.max_date());
+        assert_eq!(Epoch::default(), Epoch::UNIX);
+    }
+
+    #[test]
+    fn test_generator_with_epoch() {
+        let generator = Nano64Generator::new()
+            .with_clock(MockClock::new(JAN_2025_MS))
+            .with_epoch(Epoch::Y2020);
+        assert_eq!(generator.epoch(), Epoch::Y2020);
+        let id = generator.generate().unwrap();
+        assert_eq!(
+            id.get_timestamp(),
+            Epoch::Y2020.to_timestamp(JAN_2025_MS).unwrap()
+        );
+        assert_eq!(
+            Epoch::Y2020.to_date(&id).unwrap(),
+            UNIX_EPOCH + Duration::from_millis(JAN_2025_MS)
+        );
+        assert_eq!(
+            generator.generate_monotonic().unwrap().get_timestamp(),
+            id.get_timestamp()
+        );
+
+        let before = Nano64Generator::new()
+            .with_clock(MockClock::new(1_000))
+            .with_epoch(Epoch::Y2020);
+        assert!(before.generate().is_err());
+    }
+
+    #[test]
+    fn test_id_range() {
+        let epoch = Epoch::Y2020;
+        let start = UNIX_EPOCH + Duration::from_millis(JAN_2025_MS);
+        let end = start + Duration::from_millis(10);
+        let range = epoch.id_range(start, end).unwrap();
+        let first = epoch.to_timestamp(JAN_2025_MS).unwrap();
+        assert_eq!(*range.start(), Nano64::from_parts(first, 0).unwrap());
+        assert_eq!(
+            *range.end(),
+            Nano64::from_parts(first + 10, crate::RANDOM_MASK as u32).unwrap()
+        );
+        assert!(matches!(
+            epoch.id_range(end, start),
+            Err(Nano64Error::TimeStampRangeError)
+        ));
+    }
+
+    #[test]
+    fn test_validation_detects_mixed_epochs() {
+        let unix_id = Nano64::generate_default().unwrap();
+        assert!(unix_id.validate(&ValidationPolicy::default()).is_ok());
+        // Read with a 2020 epoch, a UNIX-epoch ID lands ~50 years in the future.
+        let policy = ValidationPolicy {
+            epoch: Epoch::Y2020,
+            ..Default::default()
+        };
+        assert!(matches!(
+            unix_id.validate(&policy),
+            Err(Nano64Error::TimeStampInFuture { .. })
+        ));
+        let custom = Nano64Generator::new().with_epoch(Epoch::Y2020);
+        assert!(custom.generate().unwrap().validate(&policy).is_ok());
+    }
+
+    #[test]
+    fn test_reduced_precision() {
+        use crate::TimestampPrecision;
+
+        let epoch = Epoch::Y2020.with_precision(TimestampPrecision::Seconds);
+        assert_eq!(epoch.precision(), TimestampPrecision::Seconds);
+        let generator = Nano64Generator::new()
+            .with_clock(MockClock::new(JAN_2025_MS + 999))
+            .with_epoch(epoch);
+        let id = generator.generate().unwrap();
+        assert_eq!(
+            id.get_timestamp(),
+            (JAN_2025_MS - Epoch::Y2020.unix_ms()) / 1000
+        );
+        assert_eq!(epoch.timestamp_ms(&id), JAN_2025_MS);
+        assert_eq!(
+            epoch.to_date(&id).unwrap(),
+            UNIX_EPOCH + Duration::from_millis(JAN_2025_MS)
+        );
+
+        let ten_ms = Epoch::UNIX.with_precision(TimestampPrecision::TenMillis);
+        assert_eq!(ten_ms.to_timestamp(1_234_567).unwrap(), 123_456);
+        assert_eq!(ten_ms.to_unix_ms(123_456), 1_234_560);
+        // Coarser ticks stretch the horizon by the same factor.
+        let horizon = |e: Epoch| {
+            e.max_date()
+                .unwrap()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_millis()
+        };
+        assert_eq!(horizon(ten_ms), horizon(Epoch::UNIX) * 10);
+
+        let policy = ValidationPolicy {
+            epoch,
+            ..Default::default()
+        };
+        assert!(
+            Nano64Generator::new()
+                .with_epoch(epoch)
+                .generate()
+                .unwrap()
+                .validate(&policy)
+                .is_ok()
+        );
+    }
+}
@@ -1,6 +1,8 @@
+use crate::{GcmAlgorithm, Nano64};
 use std::{
     error,
     fmt::{Display, Formatter, Result},
+    io,
 };
 
 #[derive(Debug)]
@@ -8,9 +10,89 @@ pub enum Nano64Error {
     Error(String),
     TimeStampRangeError,
     TimeStampExceedsBitRange(u64),
+    RandomExceedsBitRange(u32),
     RNGOutOfBounds(u32),
+    InvalidByteLength {
+        expected: usize,
+        got: usize,
+    },
+    InvalidEncodedLength {
+        encoding: &'static str,
+        expected: usize,
+        got: usize,
+    },
+    InvalidEncodedChar {
+        encoding: &'static str,
+        character: char,
+    },
+    EncodedValueOverflow(&'static str),
+    InvalidRadix(u32),
+    CheckDigitMismatch {
+        expected: char,
+        got: char,
+    },
     HexStringNotEvenCharacters,
     HexStringContainsNonHexChars,
+    Io(io::Error),
+    UnknownWord(String),
+    WordCountMismatch {
+        expected: usize,
+        got: usize,
+    },
+    WordChecksumMismatch,
+    InvalidWithSuggestions {
+        reason: String,
+        suggestions: Vec<Nano64>,
+    },
+    TimeStampInFuture {
+        timestamp: u64,
+        ahead_by_ms: u64,
+    },
+    TimeStampTooOld {
+        timestamp: u64,
+        age_ms: u64,
+    },
+    TimeStampBeforeEpoch {
+        unix_ms: u64,
+        epoch_ms: u64,
+    },
+    DefaultAlreadySet(&'static str),
+    RandomFieldExhausted {
+        timestamp: u64,
+    },
+    ClockMovedBackwards {
+        last_ms: u64,
+        now_ms: u64,
+    },
+    InvalidNodeBits(u32),
+    InvalidSignature,
+    UnknownKeyId(u8),
+    UnsupportedPayloadVersion(u8),
+    KeyUsageExceeded {
+        limit: u64,
+    },
+    TokenExpired {
+        expires_at: u64,
+    },
+    InvalidKeyLength {
+        algorithm: GcmAlgorithm,
+        expected: usize,
+        got: usize,
+    },
+    // A `Nano64Fpe` key, which is for FF1 rather than AES-GCM.
+    InvalidFpeKeyLength {
+        expected: usize,
+        got: usize,
+    },
+    NodeIdOutOfRange {
+        id: u32,
+        bits: u32,
+    },
+    QuotaExceeded {
+        limit: u64,
+        window_ms: u64,
+        retry_after_ms: u64,
+    },
 }
 
 impl Display for Nano64Error {
@@ -22,13 +104,108 @@ impl Display for Nano64Error {
             TimeStampExceedsBitRange(got) => {
                 write!(f, "Timestamp exceeds the 44-bit range. Got={got}")
             }
+            RandomExceedsBitRange(got) => {
+                write!(f, "Random value exceeds the 20-bit range. Got={got}")
+            }
             RNGOutOfBounds(got) => write!(f, "RNG bits must be between 1 and 32. Got {got}"),
+            InvalidByteLength { expected, got } => {
+                write!(f, "Expected {expected} bytes, got {got}")
+            }
+            InvalidEncodedLength {
+                encoding,
+                expected,
+                got,
+            } => write!(f, "Expected {expected} {encoding} characters, got {got}"),
+            InvalidEncodedChar {
+                encoding,
+                character,
+            } => write!(f, "'{character}' is not a valid {encoding} character"),
+            EncodedValueOverflow(encoding) => {
+                write!(f, "{encoding} value does not fit in 64 bits")
+            }
+            InvalidRadix(got) => write!(f, "Radix must be between 2 and 36. Got {got}"),
+            CheckDigitMismatch { expected, got } => {
+                write!(
+                    f,
+                    "Check symbol mismatch: expected '{expected}', got '{got}'"
+                )
+            }
             HexStringNotEvenCharacters => {
                 write!(f, "Hex string must contain an even amount of characters!")
             }
             HexStringContainsNonHexChars => write!(f, "Hex string contains non-hex characters!"),
+            Io(e) => write!(f, "I/O error: {e}"),
+            UnknownWord(word) => write!(f, "'{word}' is not in the word list"),
+            WordCountMismatch { expected, got } => {
+                write!(f, "Expected {expected} words, got {got}")
+            }
+            WordChecksumMismatch => write!(f, "Word checksum does not match!"),
+            InvalidWithSuggestions {
+                reason,
+                suggestions,
+            } => {
+                let hexes: Vec<String> = suggestions.iter().map(|id| id.to_hex()).collect();
+                write!(f, "{reason} (did you mean {}?)", hexes.join(" or "))
+            }
+            TimeStampInFuture {
+                timestamp,
+                ahead_by_ms,
+            } => write!(f, "Timestamp {timestamp} is {ahead_by_ms}ms in the future"),
+            TimeStampTooOld { timestamp, age_ms } => {
+                write!(f, "Timestamp {timestamp} is {age_ms}ms old")
+            }
+            TimeStampBeforeEpoch { unix_ms, epoch_ms } => {
+                write!(f, "Time {unix_ms} is before the epoch {epoch_ms}")
+            }
+            DefaultAlreadySet(what) => write!(f, "A default {what} has already been installed"),
+            RandomFieldExhausted { timestamp } => {
+                write!(f, "Random field exhausted for timestamp {timestamp}")
+            }
+            ClockMovedBackwards { last_ms, now_ms } => write!(
+                f,
+                "Clock moved backwards by {}ms (from {last_ms} to {now_ms})",
+                last_ms.saturating_sub(*now_ms)
+            ),
+            InvalidKeyLength {
+                algorithm,
+                expected,
+                got,
+            } => write!(
+                f,
+                "{} key must be {expected} bytes, got {got}",
+                algorithm.name()
+            ),
+            InvalidFpeKeyLength { expected, got } => {
+                write!(f, "FF1-AES-256 key must be {expected} bytes, got {got}")
+            }
+            InvalidNodeBits(bits) => write!(f, "Node ID width must be 1-16 bits, got {bits}"),
+            InvalidSignature => write!(f, "Signature does not match the ID"),
+            UnknownKeyId(id) => write!(f, "No key with ID {id} in the keyring"),
+            UnsupportedPayloadVersion(version) => {
+                write!(f, "Unsupported encrypted payload version {version}")
+            }
+            KeyUsageExceeded { limit } => write!(
+                f,
+                "Key has reached its limit of {limit} encryptions, rotate to a new key"
+            ),
+            TokenExpired { expires_at } => write!(f, "Token expired at {expires_at} (Unix ms)"),
+            NodeIdOutOfRange { id, bits } => write!(f, "Node ID {id} does not fit in {bits} bits"),
+            QuotaExceeded {
+                limit,
+                window_ms,
+                retry_after_ms,
+            } => write!(
+                f,
+                "Quota of {limit} IDs per {window_ms}ms exceeded, retry after {retry_after_ms}ms"
+            ),
         }
     }
 }
 
 impl error::Error for Nano64Error {}
+
+impl From<io::Error> for Nano64Error {
+    fn from(e: io::Error) -> Self {
+        Nano64Error::Io(e)
+    }
+}
@@ -0,0 +1,92 @@
+use crate::Nano64;
+use std::fmt;
+
+// Decoded view of an ID for logs and support tooling. See `Nano64::explain`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Explanation {
+    pub value: u64,
+    pub hex: String,
+    pub timestamp_ms: u64,
+    // UTC, formatted as `YYYY-MM-DDTHH:MM:SS.mmmZ`.
+    pub timestamp_utc: String,
+    pub random: u32,
+}
+
+impl Explanation {
+    pub(crate) fn new(id: &Nano64) -> Self {
+        Self {
+            value: id.u64_value(),
+            hex: id.to_hex(),
+            timestamp_ms: id.get_timestamp(),
+            timestamp_utc: format_utc(id.get_timestamp()),
+            random: id.get_random(),
+        }
+    }
+}
+
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "id:        {}", self.hex)?;
+        writeln!(f, "value:     {}", self.value)?;
+        writeln!(
+            f,
+            "timestamp: {} ({})",
+            self.timestamp_ms, self.timestamp_utc
+        )?;
+        write!(f, "random:    0x{:05X} ({})", self.random, self.random)
+    }
+}
+
+// Formats milliseconds since the UNIX epoch as an RFC 3339 UTC string.
+pub(crate) fn format_utc(ms: u64) -> String {
+    let days = ms / 86_400_000;
+    let ms_of_day = ms % 86_400_000;
+    let (year, month, day) = civil_from_days(days);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}.{:03}Z",
+        ms_of_day / 3_600_000,
+        ms_of_day / 60_000 % 60,
+        ms_of_day / 1000 % 60,
+        ms_of_day % 1000
+    )
+}
+
+// Days since 1970-01-01 to (year, month, day), after Howard Hinnant's `civil_from_days`.
+fn civil_from_days(days: u64) -> (u64, u64, u64) {
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let doe = z % 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + u64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::format_utc;
+    use crate::Nano64;
+
+    #[test]
+    fn test_format_utc() {
+        assert_eq!(format_utc(0), "1970-01-01T00:00:00.000Z");
+        assert_eq!(format_utc(951_782_400_000), "2000-02-29T00:00:00.000Z");
+        assert_eq!(format_utc(1_700_000_000_123), "2023-11-14T22:13:20.123Z");
+    }
+
+    #[test]
+    fn test_explain() {
+        let id = Nano64::from_parts(1_700_000_000_123, 0xBCDEF).unwrap();
+        let explained = id.explain();
+        assert_eq!(explained.value, id.u64_value());
+        assert_eq!(explained.hex, id.to_hex());
+        assert_eq!(explained.timestamp_ms, 1_700_000_000_123);
+        assert_eq!(explained.random, 0xBCDEF);
+        let text = explained.to_string();
+        assert!(text.contains("2023-11-14T22:13:20.123Z"));
+        assert!(text.contains("0xBCDEF"));
+    }
+}
@@ -0,0 +1,176 @@
+use crate::{Nano64, Nano64Error};
+use aes::{
+    Aes256,
+    cipher::{BlockEncrypt, KeyInit, generic_array::GenericArray},
+};
+
+// FF1 round count, fixed by NIST SP 800-38G.
+const FF1_ROUNDS: u8 = 10;
+
+// Format-preserving encryption of IDs: FF1 (NIST SP 800-38G) with AES-256 over the 64 bits of
+// the ID, so the ciphertext is another `u64` that fits the same BIGINT column and index. Unlike
+// `Nano64EncryptionFactory` there is no IV and no tag: the same ID under the same key and tweak
+// always encrypts to the same value, and any `u64` decrypts to some ID.
+#[derive(Clone)]
+pub struct Nano64Fpe {
+    cipher: Aes256,
+    tweak: Vec<u8>,
+}
+
+impl Nano64Fpe {
+    // Fails with `InvalidFpeKeyLength` unless `key` is 32 bytes.
+    pub fn new(key: &[u8]) -> Result<Self, Nano64Error> {
+        let cipher = Aes256::new_from_slice(key).map_err(|_| Nano64Error::InvalidFpeKeyLength {
+            expected: 32,
+            got: key.len(),
+        })?;
+        Ok(Self {
+            cipher,
+            tweak: Vec::new(),
+        })
+    }
+
+    // A public tweak mixed into every encryption, e.g. a table name, so the same ID encrypts
+    // differently in different contexts under one key. Empty by default.
+    pub fn with_tweak(mut self, tweak: &[u8]) -> Self {
+        self.tweak = tweak.to_vec();
+        self
+    }
+
+    pub fn encrypt(&self, id: Nano64) -> u64 {
+        ff1(&self.cipher, 2, 64, &self.tweak, id.value as u128, false) as u64
+    }
+
+    pub fn decrypt(&self, value: u64) -> Nano64 {
+        Nano64::new(ff1(&self.cipher, 2, 64, &self.tweak, value as u128, true) as u64)
+    }
+}
+
+// FF1 over the `n`-digit base-`radix` numeral string whose value is `x`. Numeral strings are
+// handled as integers throughout, which limits this to `radix^n < 2^128` and to round outputs of
+// a single AES block; 64 binary digits are well within both.
+fn ff1<C: BlockEncrypt>(
+    cipher: &C,
+    radix: u32,
+    n: u32,
+    tweak: &[u8],
+    x: u128,
+    decrypt: bool,
+) -> u128 {
+    let u = n / 2;
+    let v = n - u;
+    let radix_u = (radix as u128).pow(u);
+    let radix_v = (radix as u128).pow(v);
+    // Bytes needed for NUM(B), and bytes of PRF output per round.
+    let b = (128 - (radix_v - 1).leading_zeros()).div_ceil(8) as usize;
+    let d = 4 * b.div_ceil(4) + 4;
+    debug_assert!(d <= 16);
+
+    let mut p = [0u8; 16];
+    p[..3].copy_from_slice(&[1, 2, 1]);
+    p[3..6].copy_from_slice(&radix.to_be_bytes()[1..]);
+    p[6] = 10;
+    p[7] = u as u8;
+    p[8..12].copy_from_slice(&n.to_be_bytes());
+    p[12..16].copy_from_slice(&(tweak.len() as u32).to_be_bytes());
+
+    let round = |i: u8, num_b: u128, m: u32| -> u128 {
+        // PRF is CBC-MAC over P || T || 0-padding || [i] || NUM(B).
+        let pad = (16 - (tweak.len() + b + 1) % 16) % 16;
+        let mut q = Vec::with_capacity(tweak.len() + pad + 1 + b);
+        q.extend_from_slice(tweak);
+        q.resize(tweak.len() + pad, 0);
+        q.push(i);
+        q.extend_from_slice(&num_b.to_be_bytes()[16 - b..]);
+
+        let mut r = GenericArray::clone_from_slice(&p);
+        cipher.encrypt_block(&mut r);
+        for chunk in q.chunks(16) {
+            for (r, q) in r.iter_mut().zip(chunk) {
+                *r ^= q;
+            }
+            cipher.encrypt_block(&mut r);
+        }
+
+        let mut s = [0u8; 16];
+        s[16 - d..].copy_from_slice(&r[..d]);
+        u128::from_be_bytes(s) % (radix as u128).pow(m)
+    };
+
+    let (mut a, mut b_num) = (x / radix_v, x % radix_v);
+    if decrypt {
+        for i in (0..FF1_ROUNDS).rev() {
+            let m = if i % 2 == 0 { u } else { v };
+            let modulus = (radix as u128).pow(m);
+            let c = (b_num + modulus - round(i, a, m)) % modulus;
+            b_num = a;
+            a = c;
+        }
+    } else {
+        for i in 0..FF1_ROUNDS {
+            let m = if i % 2 == 0 { u } else { v };
+            let c = (a + round(i, b_num, m)) % (radix as u128).pow(m);
+            a = b_num;
+            b_num = c;
+        }
+    }
+    debug_assert!(a < radix_u);
+    a * radix_v + b_num
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ff1;
+    use crate::{Nano64, Nano64Error, Nano64Fpe};
+    use aes::{Aes128, cipher::KeyInit};
+
+    // NIST SP 800-38G FF1 samples (AES-128, radix 10).
+    #[test]
+    fn test_ff1_known_answers() {
+        let key = [
+            0x2B, 0x7E, 0x15, 0x16, 0x28, 0xAE, 0xD2, 0xA6, 0xAB, 0xF7, 0x15, 0x88, 0x09, 0xCF,
+            0x4F, 0x3C,
+        ];
+        let cipher = Aes128::new_from_slice(&key).unwrap();
+        assert_eq!(ff1(&cipher, 10, 10, &[], 123_456_789, false), 2_433_477_484);
+        assert_eq!(ff1(&cipher, 10, 10, &[], 2_433_477_484, true), 123_456_789);
+        let tweak = [0x39, 0x38, 0x37, 0x36, 0x35, 0x34, 0x33, 0x32, 0x31, 0x30];
+        assert_eq!(
+            ff1(&cipher, 10, 10, &tweak, 123_456_789, false),
+            6_124_200_773
+        );
+    }
+
+    #[test]
+    fn test_fpe_round_trip() {
+        let fpe = Nano64Fpe::new(&[7u8; 32]).unwrap();
+        for value in [0, 1, u64::MAX, 0x0123_4567_89AB_CDEF] {
+            let id = Nano64::new(value);
+            let encrypted = fpe.encrypt(id);
+            assert_ne!(encrypted, value);
+            assert_eq!(fpe.decrypt(encrypted), id);
+        }
+        let id = Nano64::generate_default().unwrap();
+        assert_eq!(fpe.encrypt(id), fpe.encrypt(id));
+
+        let tweaked = fpe.clone().with_tweak(b"orders");
+        assert_ne!(tweaked.encrypt(id), fpe.encrypt(id));
+        assert_eq!(tweaked.decrypt(tweaked.encrypt(id)), id);
+
+        let other = Nano64Fpe::new(&[8u8; 32]).unwrap();
+        assert_ne!(other.encrypt(id), fpe.encrypt(id));
+
+        let short = Nano64Fpe::new(&[0u8; 16]).err().unwrap();
+        assert!(matches!(
+            short,
+            Nano64Error::InvalidFpeKeyLength {
+                expected: 32,
+                got: 16
+            }
+        ));
+        assert_eq!(
+            short.to_string(),
+            "FF1-AES-256 key must be 32 bytes, got 16"
+        );
+    }
+}
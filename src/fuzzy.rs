@@ -0,0 +1,52 @@
+use crate::Nano64;
+
+// Builds "did you mean" candidates for a hand-entered ID that failed to parse.
+//
+// Corrections applied:
+// - surrounding whitespace is trimmed and stray separators (space, `_`, `.`) are dropped
+// - look-alike letters are mapped to digits (`O`/`o` -> `0`, `I`/`i`/`l`/`L` -> `1`)
+// - with exactly one character too many, a doubled keystroke is collapsed
+//
+// Dashes are already accepted anywhere by the strict parser, so a misplaced dash never needs a
+// suggestion.
+pub(crate) fn suggest(input: &str) -> Vec<Nano64> {
+    let normalized: String = input
+        .trim()
+        .chars()
+        .filter(|c| !matches!(c, '-' | ' ' | '_' | '.'))
+        .map(|c| match c {
+            'O' | 'o' => '0',
+            'I' | 'i' | 'l' | 'L' => '1',
+            _ => c,
+        })
+        .collect();
+
+    let normalized = normalized
+        .strip_prefix("0x")
+        .or_else(|| normalized.strip_prefix("0X"))
+        .unwrap_or(&normalized)
+        .to_string();
+
+    let mut candidates: Vec<Nano64> = Vec::new();
+    let mut push = |s: &str| {
+        if let Ok(id) = s.parse::<Nano64>()
+            && !candidates.iter().any(|c| c.equals(&id))
+        {
+            candidates.push(id);
+        }
+    };
+
+    if normalized.len() == 16 {
+        push(&normalized);
+    } else if normalized.len() == 17 {
+        let chars: Vec<char> = normalized.chars().collect();
+        for i in 1..chars.len() {
+            if chars[i] == chars[i - 1] {
+                let candidate: String = chars[..i].iter().chain(&chars[i + 1..]).collect();
+                push(&candidate);
+            }
+        }
+    }
+
+    candidates
+}
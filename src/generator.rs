@@ -0,0 +1,814 @@
+use crate::{
+    Clock, ClockAnomaly, ClockBackwardsPolicy, ClockImpl, Epoch, ExhaustionPolicy,
+    GeneratorMetrics, HybridCounter, MAX_TIMESTAMP, MonotonicBlock, MonotonicState, Nano64,
+    Nano64Error, NodeId, NoopMetrics, RandomSource, TimestampPrecision, default_rng,
+    hybrid::CounterState, time_now_since_epoch_ms,
+};
+use std::{
+    hint,
+    sync::{
+        Mutex, MutexGuard,
+        atomic::{AtomicU64, Ordering},
+    },
+    thread,
+    time::Duration,
+};
+
+// An ID generator that owns its clock, RNG and monotonic state.
+//
+// Unlike `Nano64::generate_monotonic_now` and friends, which share one process-wide state,
+// every `Nano64Generator` is independent: two generators can use different clocks and RNGs,
+// and each has its own monotonic sequence.
+pub struct Nano64Generator {
+    clock: Box<dyn Clock>,
+    rng: Mutex<Box<dyn RandomSource>>,
+    monotonic: MonotonicState,
+    exhaustion: ExhaustionPolicy,
+    clock_backwards: ClockBackwardsPolicy,
+    on_clock_anomaly: Option<Box<dyn Fn(ClockAnomaly) + Send + Sync>>,
+    // Latest clock reading used, to notice the clock going backwards.
+    latest_ms: AtomicU64,
+    metrics: Box<dyn GeneratorMetrics>,
+    epoch: Epoch,
+    node: NodeId,
+    counter: Option<HybridCounter>,
+    counter_state: CounterState,
+}
+
+impl Default for Nano64Generator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Nano64Generator {
+    // Uses the default clock and RNG: the ones installed with `set_default_clock` and
+    // `set_default_rng`, or else the system clock and a cryptographically-secure RNG.
+    pub fn new() -> Self {
+        Self {
+            clock: Box::new(time_now_since_epoch_ms as ClockImpl),
+            rng: Mutex::new(Box::new(default_rng)),
+            monotonic: MonotonicState::new(),
+            exhaustion: ExhaustionPolicy::default(),
+            clock_backwards: ClockBackwardsPolicy::default(),
+            on_clock_anomaly: None,
+            latest_ms: AtomicU64::new(0),
+            metrics: Box::new(NoopMetrics),
+            epoch: Epoch::UNIX,
+            node: NodeId::NONE,
+            counter: None,
+            counter_state: CounterState::default(),
+        }
+    }
+
+    // Accepts a `ClockImpl`, a closure, or any `Clock` implementation.
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Box::new(clock);
+        self
+    }
+
+    // Accepts a `RandomNumberGeneratorImpl`, a stateful closure, or `RngSource(your_rng)`.
+    pub fn with_rng(mut self, rng: impl RandomSource + 'static) -> Self {
+        self.rng = Mutex::new(Box::new(rng));
+        self
+    }
+
+    // Each generator starts with its own monotonic sequence. Pass a shared `MonotonicState` to
+    // have several generators issue one strictly increasing sequence between them.
+    pub fn with_monotonic_state(mut self, state: MonotonicState) -> Self {
+        self.monotonic = state;
+        self
+    }
+
+    // What monotonic generation does once a millisecond's random field is used up. Defaults to
+    // `ExhaustionPolicy::BorrowNextMs`.
+    pub fn with_exhaustion_policy(mut self, policy: ExhaustionPolicy) -> Self {
+        self.exhaustion = policy;
+        self
+    }
+
+    // What happens when the clock reads earlier than a reading already used. Defaults to
+    // `ClockBackwardsPolicy::ClampToLast`. Only clock-driven methods are affected; the `*_at`
+    // methods use the timestamp they're given, except as noted on `with_hybrid_counter`.
+    pub fn with_clock_backwards_policy(mut self, policy: ClockBackwardsPolicy) -> Self {
+        self.clock_backwards = policy;
+        self
+    }
+
+    // Called whenever the clock is seen going backwards, before the policy is applied, so
+    // operators can log or alert on clock anomalies.
+    pub fn with_clock_anomaly_callback(
+        mut self,
+        callback: impl Fn(ClockAnomaly) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_clock_anomaly = Some(Box::new(callback));
+        self
+    }
+
+    // Instrumentation hooks for generation, rollovers, clock anomalies and RNG failures.
+    pub fn with_metrics(mut self, metrics: impl GeneratorMetrics + 'static) -> Self {
+        self.metrics = Box::new(metrics);
+        self
+    }
+
+    // Timestamps count from `epoch` instead of the UNIX epoch. Decode the IDs with the same
+    // epoch, e.g. `Epoch::to_date` and `ValidationPolicy::epoch`.
+    pub fn with_epoch(mut self, epoch: Epoch) -> Self {
+        self.epoch = epoch;
+        self
+    }
+
+    pub fn epoch(&self) -> Epoch {
+        self.epoch
+    }
+
+    // Shorthand for `with_epoch(self.epoch().with_precision(precision))`.
+    pub fn with_precision(mut self, precision: TimestampPrecision) -> Self {
+        self.epoch = self.epoch.with_precision(precision);
+        self
+    }
+
+    // Stores `node` in the top of the random field of every ID, so generators with distinct
+    // node IDs never collide. Read it back with `Nano64::get_node(node.bits())`. Fails if a
+    // hybrid counter is set and the two don't fit in the random field together.
+    pub fn with_node(mut self, node: NodeId) -> Result<Self, Nano64Error> {
+        if let Some(counter) = self.counter {
+            hybrid_random_bits(node, counter)?;
+        }
+        self.node = node;
+        Ok(self)
+    }
+
+    pub fn node(&self) -> Option<NodeId> {
+        (self.node != NodeId::NONE).then_some(self.node)
+    }
+
+    // Makes `generate`, `fill` and friends number the IDs of each millisecond with a counter
+    // in the high random bits, so the generator itself never issues a duplicate. The
+    // exhaustion policy applies once the counter runs out. Monotonic generation is unaffected.
+    // IDs never go back past the latest millisecond issued: `generate_at` with an older
+    // timestamp issues at that millisecond instead, as reusing a finished millisecond's
+    // counter could repeat an ID. Fails if a node ID is set and the two don't fit in the
+    // random field together.
+    pub fn with_hybrid_counter(mut self, counter: HybridCounter) -> Result<Self, Nano64Error> {
+        hybrid_random_bits(self.node, counter)?;
+        self.counter = Some(counter);
+        Ok(self)
+    }
+
+    pub fn hybrid_counter(&self) -> Option<HybridCounter> {
+        self.counter
+    }
+
+    pub fn generate(&self) -> Result<Nano64, Nano64Error> {
+        let mut waiting = false;
+        loop {
+            match self.generate_unreported(self.now_ms()?) {
+                Err(Nano64Error::RandomFieldExhausted { timestamp })
+                    if self.spin_on_exhaustion() =>
+                {
+                    if !waiting {
+                        self.report_exhausted(timestamp);
+                        waiting = true;
+                    }
+                    hint::spin_loop()
+                }
+                result => return self.observe_exhausted(result),
+            }
+        }
+    }
+
+    // `timestamp` is the ID timestamp, i.e. relative to the generator's epoch. With a hybrid
+    // counter, a timestamp older than the latest ID's is raised to it (see
+    // `with_hybrid_counter`), and `ExhaustionPolicy::SpinWaitForNextMs` returns
+    // `RandomFieldExhausted` rather than waiting, since the caller chose the timestamp.
+    pub fn generate_at(&self, timestamp: u64) -> Result<Nano64, Nano64Error> {
+        let result = self.generate_unreported(timestamp);
+        self.observe_exhausted(result)
+    }
+
+    // `n` IDs sharing one clock reading and one RNG lock acquisition. See `fill`.
+    pub fn generate_batch(&self, n: usize) -> Result<Vec<Nano64>, Nano64Error> {
+        let mut ids = vec![Nano64::NIL; n];
+        self.fill(&mut ids)?;
+        Ok(ids)
+    }
+
+    // Fills `out` with IDs for the current millisecond. The clock is read and the RNG locked
+    // once for the whole buffer. If an error is returned, `out` may be partially written.
+    pub fn fill(&self, out: &mut [Nano64]) -> Result<(), Nano64Error> {
+        let mut timestamp = self.now_ms()?;
+        let mut guard = self.lock_rng()?;
+        let mut rng = self.observed(guard.as_mut());
+        // Reported once per exhausted millisecond, not once per spin.
+        let mut waiting = false;
+        for slot in out.iter_mut() {
+            *slot = loop {
+                match self.generate_with_source(timestamp, &mut rng) {
+                    Err(Nano64Error::RandomFieldExhausted {
+                        timestamp: exhausted,
+                    }) if self.spin_on_exhaustion() => {
+                        if !waiting {
+                            self.report_exhausted(exhausted);
+                            waiting = true;
+                        }
+                        hint::spin_loop();
+                        timestamp = self.now_ms()?;
+                    }
+                    result => {
+                        waiting = false;
+                        break self.observe_exhausted(result)?;
+                    }
+                }
+            };
+        }
+        self.metrics.on_generate(out.len() as u64, false);
+        Ok(())
+    }
+
+    // `generate`, in descending form; see `Nano64::to_descending`.
+    pub fn generate_descending(&self) -> Result<Nano64, Nano64Error> {
+        Ok(self.generate()?.to_descending())
+    }
+
+    pub fn generate_monotonic(&self) -> Result<Nano64, Nano64Error> {
+        let mut waiting = false;
+        loop {
+            let timestamp = self.now_ms()?;
+            match self.next_monotonic(timestamp) {
+                Err(Nano64Error::RandomFieldExhausted { timestamp })
+                    if self.exhaustion == ExhaustionPolicy::SpinWaitForNextMs =>
+                {
+                    if !waiting {
+                        self.report_exhausted(timestamp);
+                        waiting = true;
+                    }
+                    hint::spin_loop()
+                }
+                result => return self.observe_monotonic(timestamp, result),
+            }
+        }
+    }
+
+    // With `ExhaustionPolicy::SpinWaitForNextMs` this returns `RandomFieldExhausted` rather than
+    // waiting, since the caller chose the timestamp.
+    pub fn generate_monotonic_at(&self, timestamp: u64) -> Result<Nano64, Nano64Error> {
+        let result = self.next_monotonic(timestamp);
+        self.observe_monotonic(timestamp, result)
+    }
+
+    // Reserves `n` strictly increasing IDs from this generator's monotonic sequence in one step.
+    // Unless the exhaustion policy is `BorrowNextMs`, the whole block must fit in one
+    // millisecond, otherwise `RandomFieldExhausted` is returned without waiting.
+    pub fn generate_monotonic_block(&self, n: usize) -> Result<MonotonicBlock, Nano64Error> {
+        let timestamp = self.now_ms()?;
+        let mut rng = |bits: u32| self.random_bits(bits);
+        let block = self
+            .monotonic
+            .generate_block_dyn(timestamp, n, &mut rng, self.exhaustion, self.node)
+            .inspect_err(|e| {
+                if let Nano64Error::RandomFieldExhausted { timestamp } = e {
+                    self.report_exhausted(*timestamp);
+                }
+            })?;
+        if let (Some(first), Some(last)) = (block.clone().next(), block.clone().last())
+            && last.get_timestamp() > first.get_timestamp()
+        {
+            self.metrics.on_monotonic_rollover(first.get_timestamp());
+        }
+        self.metrics.on_generate(block.len() as u64, true);
+        Ok(block)
+    }
+
+    // Endless stream of IDs from `generate`. Items are `Result`s, so
+    // `generator.iter().take(1000).collect::<Result<Vec<_>, _>>()` stops at the first error.
+    pub fn iter(&self) -> Nano64Iter<'_> {
+        Nano64Iter {
+            generator: self,
+            monotonic: false,
+        }
+    }
+
+    // Like `iter`, but from `generate_monotonic`.
+    pub fn iter_monotonic(&self) -> Nano64Iter<'_> {
+        Nano64Iter {
+            generator: self,
+            monotonic: true,
+        }
+    }
+
+    // Handle to this generator's monotonic sequence; clone it into another generator with
+    // `with_monotonic_state` to make both feed one sequence.
+    pub fn monotonic_state(&self) -> MonotonicState {
+        self.monotonic.clone()
+    }
+
+    // The current ID timestamp, relative to the generator's epoch.
+    pub(crate) fn now_ms(&self) -> Result<u64, Nano64Error> {
+        self.epoch.to_timestamp(self.clock_ms()?)
+    }
+
+    // The current clock reading, with the clock-backwards policy applied.
+    fn clock_ms(&self) -> Result<u64, Nano64Error> {
+        let now_ms = self.clock.now_ms();
+        let last_ms = self.latest_ms.fetch_max(now_ms, Ordering::AcqRel);
+        if now_ms >= last_ms {
+            return Ok(now_ms);
+        }
+
+        let anomaly = ClockAnomaly { last_ms, now_ms };
+        #[cfg(feature = "tracing")]
+        tracing::warn!(
+            last_ms,
+            now_ms,
+            policy = ?self.clock_backwards,
+            "clock moved backwards"
+        );
+        self.metrics.on_clock_backwards(anomaly);
+        if let Some(callback) = &self.on_clock_anomaly {
+            callback(anomaly);
+        }
+        match self.clock_backwards {
+            ClockBackwardsPolicy::ClampToLast => Ok(last_ms),
+            ClockBackwardsPolicy::Error => {
+                Err(Nano64Error::ClockMovedBackwards { last_ms, now_ms })
+            }
+            ClockBackwardsPolicy::WaitUntilCaughtUp => loop {
+                thread::sleep(Duration::from_millis(1));
+                let now_ms = self.clock.now_ms();
+                if now_ms >= last_ms {
+                    self.latest_ms.fetch_max(now_ms, Ordering::AcqRel);
+                    return Ok(now_ms);
+                }
+            },
+        }
+    }
+
+    fn generate_with_source(
+        &self,
+        timestamp: u64,
+        rng: &mut impl RandomSource,
+    ) -> Result<Nano64, Nano64Error> {
+        if let Some(counter) = self.counter {
+            return self.generate_hybrid(timestamp, counter, rng);
+        }
+        if self.node == NodeId::NONE {
+            return Nano64::generate_with_source(timestamp, rng);
+        }
+        let random = rng.random_bits(self.node.counter_bits())? as u64 & self.node.counter_mask();
+        Nano64::from_parts(timestamp, (self.node.prefix() | random) as u32)
+    }
+
+    // Random field laid out as [node][counter][random].
+    fn generate_hybrid(
+        &self,
+        timestamp: u64,
+        counter: HybridCounter,
+        rng: &mut impl RandomSource,
+    ) -> Result<Nano64, Nano64Error> {
+        let random_bits = hybrid_random_bits(self.node, counter)?;
+        if timestamp > MAX_TIMESTAMP {
+            return Err(Nano64Error::TimeStampExceedsBitRange(timestamp));
+        }
+        let (issued_at, count) = self
+            .counter_state
+            .next(timestamp, counter, self.exhaustion)?;
+        if issued_at > timestamp && count == 0 {
+            self.metrics.on_monotonic_rollover(issued_at - 1);
+        }
+        let random = match random_bits {
+            0 => 0,
+            bits => rng.random_bits(bits)? as u64 & ((1 << bits) - 1),
+        };
+        let field = self.node.prefix() | (count << random_bits) | random;
+        Nano64::from_parts(issued_at, field as u32)
+    }
+
+    // `generate_at` without reporting `RandomFieldExhausted`, so a caller spinning on it can
+    // report once rather than on every attempt.
+    fn generate_unreported(&self, timestamp: u64) -> Result<Nano64, Nano64Error> {
+        let mut rng = self.lock_rng()?;
+        let id = self.generate_with_source(timestamp, &mut self.observed(rng.as_mut()))?;
+        self.metrics.on_generate(1, false);
+        Ok(id)
+    }
+
+    fn observe_exhausted(
+        &self,
+        result: Result<Nano64, Nano64Error>,
+    ) -> Result<Nano64, Nano64Error> {
+        if let Err(Nano64Error::RandomFieldExhausted { timestamp }) = &result {
+            self.report_exhausted(*timestamp);
+        }
+        result
+    }
+
+    fn spin_on_exhaustion(&self) -> bool {
+        self.exhaustion == ExhaustionPolicy::SpinWaitForNextMs
+    }
+
+    fn next_monotonic(&self, timestamp: u64) -> Result<Nano64, Nano64Error> {
+        // The RNG is only needed (and locked) when the sequence moves to a new millisecond.
+        let mut rng = |bits: u32| self.random_bits(bits);
+        self.monotonic
+            .generate_dyn(timestamp, &mut rng, self.exhaustion, self.node)
+    }
+
+    fn observe_monotonic(
+        &self,
+        timestamp: u64,
+        result: Result<Nano64, Nano64Error>,
+    ) -> Result<Nano64, Nano64Error> {
+        match &result {
+            // Borrowed the next millisecond: the increment carried out of the random field. The
+            // node bits are set even then, so only the counter below them is checked.
+            Ok(id)
+                if id.get_random() as u64 & self.node.counter_mask() == 0
+                    && id.get_timestamp() > timestamp =>
+            {
+                self.metrics.on_monotonic_rollover(id.get_timestamp() - 1);
+            }
+            Err(Nano64Error::RandomFieldExhausted { timestamp }) => {
+                self.report_exhausted(*timestamp);
+            }
+            _ => {}
+        }
+        if result.is_ok() {
+            self.metrics.on_generate(1, true);
+        }
+        result
+    }
+
+    // The random field ran out and the exhaustion policy refused to borrow the next millisecond.
+    fn report_exhausted(&self, timestamp: u64) {
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            timestamp,
+            policy = ?self.exhaustion,
+            "monotonic random field exhausted"
+        );
+        self.metrics.on_monotonic_rollover(timestamp);
+    }
+
+    // One draw from the RNG, reporting failures to the metrics.
+    fn random_bits(&self, bits: u32) -> Result<u32, Nano64Error> {
+        let mut rng = self.lock_rng()?;
+        self.observed(rng.as_mut()).random_bits(bits)
+    }
+
+    // Wraps an RNG so its failures are reported to the metrics.
+    fn observed<'a>(
+        &'a self,
+        rng: &'a mut dyn RandomSource,
+    ) -> impl FnMut(u32) -> Result<u32, Nano64Error> + Send + 'a {
+        move |bits| {
+            rng.random_bits(bits)
+                .inspect_err(|e| self.metrics.on_rng_error(e))
+        }
+    }
+
+    fn lock_rng(&self) -> Result<MutexGuard<'_, Box<dyn RandomSource>>, Nano64Error> {
+        self.rng
+            .lock()
+            .map_err(|_| Nano64Error::Error("Error unlocking rng".into()))
+    }
+}
+
+// Endless iterator over a generator's IDs, from `Nano64Generator::iter` or `iter_monotonic`.
+pub struct Nano64Iter<'a> {
+    generator: &'a Nano64Generator,
+    monotonic: bool,
+}
+
+impl Iterator for Nano64Iter<'_> {
+    type Item = Result<Nano64, Nano64Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(if self.monotonic {
+            self.generator.generate_monotonic()
+        } else {
+            self.generator.generate()
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (usize::MAX, None)
+    }
+}
+
+impl<'a> IntoIterator for &'a Nano64Generator {
+    type Item = Result<Nano64, Nano64Error>;
+    type IntoIter = Nano64Iter<'a>;
+
+    fn into_iter(self) -> Nano64Iter<'a> {
+        self.iter()
+    }
+}
+
+// Random bits left below `node` and `counter`, or an error if they overlap.
+fn hybrid_random_bits(node: NodeId, counter: HybridCounter) -> Result<u32, Nano64Error> {
+    node.counter_bits()
+        .checked_sub(counter.bits())
+        .ok_or_else(|| {
+            Nano64Error::Error(format!(
+                "{}-bit counter and {}-bit node ID don't fit in the random field",
+                counter.bits(),
+                node.bits()
+            ))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Nano64, Nano64Error, Nano64Generator, RngSource};
+    use rand::{SeedableRng, rngs::StdRng};
+
+    fn fixed_clock() -> u64 {
+        1_700_000_000_000
+    }
+
+    fn fixed_rng(_bits: u32) -> Result<u32, Nano64Error> {
+        Ok(0x12345)
+    }
+
+    #[test]
+    fn test_generator_uses_configured_clock_and_rng() {
+        let generator = Nano64Generator::new()
+            .with_clock(fixed_clock)
+            .with_rng(fixed_rng);
+        let id = generator.generate().unwrap();
+        assert_eq!(id.get_timestamp(), 1_700_000_000_000);
+        assert_eq!(id.get_random(), 0x12345);
+        assert_eq!(generator.generate_at(42).unwrap().get_timestamp(), 42);
+    }
+
+    #[test]
+    fn test_generators_have_independent_monotonic_state() {
+        let a = Nano64Generator::new()
+            .with_clock(fixed_clock)
+            .with_rng(fixed_rng);
+        let b = Nano64Generator::new()
+            .with_clock(fixed_clock)
+            .with_rng(fixed_rng);
+        let a1 = a.generate_monotonic().unwrap();
+        let a2 = a.generate_monotonic().unwrap();
+        let b1 = b.generate_monotonic().unwrap();
+        assert_eq!(a2.u64_value(), a1.u64_value() + 1);
+        // `b` never saw `a`'s IDs, so it starts fresh in the same millisecond.
+        assert_eq!(b1, a1);
+
+        // Going back in time is clamped per generator.
+        let a3 = a.generate_monotonic_at(1000).unwrap();
+        assert!(a3 > a2);
+        assert_eq!(
+            b.generate_monotonic_at(1000).unwrap().get_timestamp(),
+            fixed_clock()
+        );
+        assert!(a.generate_monotonic_at(u64::MAX).is_err());
+    }
+
+    #[test]
+    fn test_generator_with_seeded_rng() {
+        let a = Nano64Generator::new()
+            .with_clock(fixed_clock)
+            .with_rng(RngSource(StdRng::seed_from_u64(1)));
+        let b = Nano64Generator::new()
+            .with_clock(fixed_clock)
+            .with_rng(RngSource(StdRng::seed_from_u64(1)));
+        for _ in 0..10 {
+            assert_eq!(a.generate().unwrap(), b.generate().unwrap());
+        }
+    }
+
+    #[test]
+    fn test_generator_batch_reads_clock_once() {
+        use std::sync::atomic::{AtomicU64, Ordering};
+
+        static READS: AtomicU64 = AtomicU64::new(0);
+        let generator = Nano64Generator::new()
+            .with_clock(|| 1_000 + READS.fetch_add(1, Ordering::Relaxed))
+            .with_rng(fixed_rng);
+        let ids = generator.generate_batch(10).unwrap();
+        assert_eq!(READS.load(Ordering::Relaxed), 1);
+        assert!(ids.iter().all(|id| id.get_timestamp() == 1_000));
+        assert!(ids.iter().all(|id| id.get_random() == 0x12345));
+
+        let mut buf = [Nano64::NIL; 4];
+        generator.fill(&mut buf).unwrap();
+        assert!(buf.iter().all(|id| id.get_timestamp() == 1_001));
+    }
+
+    #[test]
+    fn test_generator_monotonic_block() {
+        let generator = Nano64Generator::new()
+            .with_clock(fixed_clock)
+            .with_rng(fixed_rng);
+        let first = generator.generate_monotonic().unwrap();
+        let block: Vec<Nano64> = generator.generate_monotonic_block(5).unwrap().collect();
+        assert_eq!(block.len(), 5);
+        assert!(block[0] > first);
+        assert!(block.windows(2).all(|w| w[0] < w[1]));
+        assert!(generator.generate_monotonic().unwrap() > block[4]);
+    }
+
+    #[test]
+    fn test_generator_exhaustion_policies() {
+        use crate::{
+            ExhaustionPolicy, MonotonicState, RANDOM_MASK,
+            testing::{MockClock, MockRng},
+        };
+        use std::{thread, time::Duration};
+
+        let state = MonotonicState::new();
+        state.set_last(1000, RANDOM_MASK);
+        let clock = MockClock::new(1000);
+        let generator = Nano64Generator::new()
+            .with_clock(clock.clone())
+            .with_rng(MockRng::constant(0))
+            .with_monotonic_state(state.clone())
+            .with_exhaustion_policy(ExhaustionPolicy::Error);
+        assert!(matches!(
+            generator.generate_monotonic(),
+            Err(Nano64Error::RandomFieldExhausted { timestamp: 1000 })
+        ));
+
+        let spinning = Nano64Generator::new()
+            .with_clock(clock.clone())
+            .with_rng(MockRng::constant(0))
+            .with_monotonic_state(state.clone())
+            .with_exhaustion_policy(ExhaustionPolicy::SpinWaitForNextMs);
+        let waiter = thread::spawn(move || spinning.generate_monotonic().unwrap());
+        thread::sleep(Duration::from_millis(20));
+        clock.advance(1);
+        let id = waiter.join().unwrap();
+        assert_eq!((id.get_timestamp(), id.get_random()), (1001, 0));
+
+        state.set_last(1001, RANDOM_MASK);
+        let borrowing = Nano64Generator::new()
+            .with_clock(fixed_clock)
+            .with_monotonic_state(state);
+        let id = borrowing.generate_monotonic_at(1001).unwrap();
+        assert_eq!((id.get_timestamp(), id.get_random()), (1002, 0));
+    }
+
+    #[test]
+    fn test_generator_clock_backwards_policies() {
+        use crate::{
+            ClockAnomaly, ClockBackwardsPolicy,
+            testing::{MockClock, MockRng},
+        };
+        use std::{
+            sync::{Arc, Mutex},
+            thread,
+            time::Duration,
+        };
+
+        let clock = MockClock::new(2000);
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let log = seen.clone();
+        let clamping = Nano64Generator::new()
+            .with_clock(clock.clone())
+            .with_rng(MockRng::constant(0))
+            .with_clock_anomaly_callback(move |anomaly| log.lock().unwrap().push(anomaly));
+        assert_eq!(clamping.generate().unwrap().get_timestamp(), 2000);
+        clock.set(1990);
+        assert_eq!(clamping.generate().unwrap().get_timestamp(), 2000);
+        let anomaly = ClockAnomaly {
+            last_ms: 2000,
+            now_ms: 1990,
+        };
+        assert_eq!(*seen.lock().unwrap(), vec![anomaly]);
+        assert_eq!(anomaly.behind_by_ms(), 10);
+
+        clock.set(2000);
+        let strict = Nano64Generator::new()
+            .with_clock(clock.clone())
+            .with_clock_backwards_policy(ClockBackwardsPolicy::Error);
+        strict.generate_monotonic().unwrap();
+        clock.set(1995);
+        assert!(matches!(
+            strict.generate_monotonic(),
+            Err(Nano64Error::ClockMovedBackwards {
+                last_ms: 2000,
+                now_ms: 1995
+            })
+        ));
+
+        clock.set(2000);
+        let waiting = Nano64Generator::new()
+            .with_clock(clock.clone())
+            .with_clock_backwards_policy(ClockBackwardsPolicy::WaitUntilCaughtUp);
+        waiting.generate().unwrap();
+        clock.set(1000);
+        let waiter = thread::spawn(move || waiting.generate().unwrap());
+        thread::sleep(Duration::from_millis(20));
+        clock.set(2001);
+        assert_eq!(waiter.join().unwrap().get_timestamp(), 2001);
+    }
+
+    #[test]
+    fn test_generator_iterators() {
+        let generator = Nano64Generator::new()
+            .with_clock(fixed_clock)
+            .with_rng(fixed_rng);
+        let ids: Vec<Nano64> = generator.iter().take(3).collect::<Result<_, _>>().unwrap();
+        assert_eq!(ids.len(), 3);
+        assert!(ids.iter().all(|id| id.get_random() == 0x12345));
+
+        let ordered: Vec<Nano64> = generator
+            .iter_monotonic()
+            .take(100)
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert!(ordered.windows(2).all(|w| w[0] < w[1]));
+
+        let mut count = 0;
+        for id in &generator {
+            id.unwrap();
+            count += 1;
+            if count == 5 {
+                break;
+            }
+        }
+        assert_eq!(count, 5);
+    }
+
+    #[test]
+    fn test_generator_metrics() {
+        use crate::{
+            ClockAnomaly, GeneratorMetrics, RANDOM_MASK,
+            testing::{MockClock, MockRng},
+        };
+        use std::sync::{
+            Arc,
+            atomic::{AtomicU64, Ordering},
+        };
+
+        #[derive(Default)]
+        struct Counters {
+            plain: AtomicU64,
+            monotonic: AtomicU64,
+            rollovers: AtomicU64,
+            backwards: AtomicU64,
+            rng_errors: AtomicU64,
+        }
+
+        impl GeneratorMetrics for Arc<Counters> {
+            fn on_generate(&self, count: u64, monotonic: bool) {
+                let counter = if monotonic {
+                    &self.monotonic
+                } else {
+                    &self.plain
+                };
+                counter.fetch_add(count, Ordering::Relaxed);
+            }
+            fn on_monotonic_rollover(&self, _timestamp: u64) {
+                self.rollovers.fetch_add(1, Ordering::Relaxed);
+            }
+            fn on_clock_backwards(&self, _anomaly: ClockAnomaly) {
+                self.backwards.fetch_add(1, Ordering::Relaxed);
+            }
+            fn on_rng_error(&self, _error: &Nano64Error) {
+                self.rng_errors.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        let counters = Arc::new(Counters::default());
+        let clock = MockClock::new(1000);
+        let generator = Nano64Generator::new()
+            .with_clock(clock.clone())
+            .with_rng(MockRng::constant(RANDOM_MASK as u32))
+            .with_metrics(counters.clone());
+        generator.generate().unwrap();
+        generator.generate_batch(4).unwrap();
+        generator.generate_monotonic().unwrap();
+        // The random field is already full, so this carries into the next millisecond.
+        generator.generate_monotonic().unwrap();
+        generator.generate_monotonic_block(3).unwrap();
+        clock.set(900);
+        generator.generate().unwrap();
+
+        assert_eq!(counters.plain.load(Ordering::Relaxed), 6);
+        assert_eq!(counters.monotonic.load(Ordering::Relaxed), 5);
+        assert_eq!(counters.rollovers.load(Ordering::Relaxed), 1);
+        assert_eq!(counters.backwards.load(Ordering::Relaxed), 1);
+
+        // With a node ID, a rollover leaves the node bits set.
+        let noded = Nano64Generator::new()
+            .with_clock(MockClock::new(1000))
+            .with_rng(MockRng::constant(RANDOM_MASK as u32))
+            .with_node(crate::NodeId::new(5, 4).unwrap())
+            .unwrap()
+            .with_metrics(counters.clone());
+        noded.generate_monotonic().unwrap();
+        assert_eq!(noded.generate_monotonic().unwrap().get_timestamp(), 1001);
+        assert_eq!(counters.rollovers.load(Ordering::Relaxed), 2);
+
+        let failing = Nano64Generator::new()
+            .with_rng(MockRng::failing())
+            .with_metrics(counters.clone());
+        assert!(failing.generate().is_err());
+        assert!(failing.generate_monotonic().is_err());
+        assert_eq!(counters.rng_errors.load(Ordering::Relaxed), 2);
+    }
+}
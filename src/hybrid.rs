@@ -0,0 +1,201 @@
+use crate::{
+    ExhaustionPolicy, MAX_TIMESTAMP, Nano64Error, RANDOM_BITS, RANDOM_MASK, TIMESTAMP_SHIFT,
+};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+// Splits the random field into a per-millisecond sequence counter (high bits) and residual
+// random bits (low bits). The counter restarts at zero every millisecond, so a generator never
+// repeats an ID until `2^bits` IDs have been issued in one millisecond; the random bits keep
+// consecutive IDs from being guessable. With a `NodeId`, the counter sits just below the node
+// bits and the two must fit in the 20-bit random field together.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct HybridCounter {
+    bits: u32,
+}
+
+impl HybridCounter {
+    // Fails unless `bits` is 1-20. 20 leaves no random bits at all.
+    pub fn new(bits: u32) -> Result<Self, Nano64Error> {
+        if bits == 0 || bits > RANDOM_BITS as u32 {
+            return Err(Nano64Error::Error(format!(
+                "counter width must be 1-{RANDOM_BITS} bits, got {bits}"
+            )));
+        }
+        Ok(Self { bits })
+    }
+
+    pub fn bits(&self) -> u32 {
+        self.bits
+    }
+}
+
+// The last (timestamp, counter) pair a generator issued, packed like an ID so one atomic
+// holds both.
+#[derive(Default)]
+pub(crate) struct CounterState {
+    last: AtomicU64,
+}
+
+impl CounterState {
+    // The (timestamp, counter) pair for the next ID at `timestamp`, or at the latest
+    // millisecond issued if `timestamp` is older. If the counter for the latest millisecond
+    // is used up, `policy` decides between borrowing the next millisecond and
+    // `RandomFieldExhausted`; `SpinWaitForNextMs` is left to the caller.
+    pub(crate) fn next(
+        &self,
+        timestamp: u64,
+        counter: HybridCounter,
+        policy: ExhaustionPolicy,
+    ) -> Result<(u64, u64), Nano64Error> {
+        let max_counter = (1 << counter.bits) - 1;
+        let mut issued = (0, 0);
+        self.last
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |last| {
+                let (last_ts, last_counter) = (last >> TIMESTAMP_SHIFT, last & RANDOM_MASK);
+                issued = if timestamp > last_ts {
+                    (timestamp, 0)
+                } else if last_counter < max_counter {
+                    (last_ts, last_counter + 1)
+                } else if policy == ExhaustionPolicy::BorrowNextMs && last_ts < MAX_TIMESTAMP {
+                    (last_ts + 1, 0)
+                } else {
+                    return None;
+                };
+                Some((issued.0 << TIMESTAMP_SHIFT) | issued.1)
+            })
+            .map_err(|last| Nano64Error::RandomFieldExhausted {
+                timestamp: last >> TIMESTAMP_SHIFT,
+            })?;
+        Ok(issued)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ExhaustionPolicy, HybridCounter, Nano64, Nano64Error, Nano64Generator, NodeId,
+        testing::{MockClock, MockRng},
+    };
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_hybrid_counter_validation() {
+        assert_eq!(HybridCounter::new(12).unwrap().bits(), 12);
+        assert!(HybridCounter::new(20).is_ok());
+        assert!(HybridCounter::new(0).is_err());
+        assert!(HybridCounter::new(21).is_err());
+    }
+
+    #[test]
+    fn test_hybrid_ids_never_collide_within_a_millisecond() {
+        let generator = Nano64Generator::new()
+            .with_clock(MockClock::new(1000))
+            // A constant RNG would collide on every ID without the counter.
+            .with_rng(MockRng::constant(0x7))
+            .with_hybrid_counter(HybridCounter::new(4).unwrap())
+            .unwrap();
+        let ids = generator.generate_batch(16).unwrap();
+        let unique: HashSet<Nano64> = ids.iter().copied().collect();
+        assert_eq!(unique.len(), 16);
+        assert!(ids.windows(2).all(|w| w[0] < w[1]));
+        assert!(ids.iter().all(|id| id.get_timestamp() == 1000));
+        assert_eq!(ids[3].get_random(), (3 << 16) | 0x7);
+
+        // The 17th ID borrows millisecond 1001.
+        let next = generator.generate().unwrap();
+        assert_eq!(next.get_timestamp(), 1001);
+        assert_eq!(next.get_random(), 0x7);
+    }
+
+    #[test]
+    fn test_hybrid_generate_at_never_goes_back() {
+        let generator = Nano64Generator::new()
+            .with_rng(MockRng::constant(0))
+            .with_hybrid_counter(HybridCounter::new(4).unwrap())
+            .unwrap();
+        let latest = generator.generate_at(2000).unwrap();
+        // Millisecond 1000's counter may already have been used; it isn't reused.
+        let older = generator.generate_at(1000).unwrap();
+        assert_eq!(older.get_timestamp(), 2000);
+        assert!(older > latest);
+    }
+
+    #[test]
+    fn test_hybrid_counter_exhaustion_and_node() {
+        let generator = Nano64Generator::new()
+            .with_clock(MockClock::new(1000))
+            .with_rng(MockRng::constant(u32::MAX))
+            .with_node(NodeId::new(0b11, 2).unwrap())
+            .unwrap()
+            .with_hybrid_counter(HybridCounter::new(1).unwrap())
+            .unwrap()
+            .with_exhaustion_policy(ExhaustionPolicy::Error);
+        let first = generator.generate().unwrap();
+        assert_eq!(first.get_node(2).unwrap(), 0b11);
+        assert_eq!(first.get_random(), (0b11 << 18) | ((1 << 17) - 1));
+        assert!(generator.generate().unwrap() > first);
+        assert!(matches!(
+            generator.generate(),
+            Err(Nano64Error::RandomFieldExhausted { timestamp: 1000 })
+        ));
+
+        // Checked when configuring, whichever is set first.
+        let node = NodeId::new(0, 16).unwrap();
+        let counter = HybridCounter::new(5).unwrap();
+        let noded = Nano64Generator::new().with_node(node).unwrap();
+        assert!(noded.with_hybrid_counter(counter).is_err());
+        let counted = Nano64Generator::new().with_hybrid_counter(counter).unwrap();
+        assert!(counted.with_node(node).is_err());
+        assert!(
+            Nano64Generator::new()
+                .with_hybrid_counter(HybridCounter::new(4).unwrap())
+                .unwrap()
+                .with_node(node)
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_hybrid_spin_reports_exhaustion_once() {
+        use crate::GeneratorMetrics;
+        use std::sync::{
+            Arc,
+            atomic::{AtomicU64, Ordering},
+        };
+        use std::{thread, time::Duration};
+
+        struct Rollovers(Arc<AtomicU64>);
+
+        impl GeneratorMetrics for Rollovers {
+            fn on_monotonic_rollover(&self, _timestamp: u64) {
+                self.0.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        let rollovers = Arc::new(AtomicU64::new(0));
+        let clock = MockClock::new(1000);
+        let generator = Nano64Generator::new()
+            .with_clock(clock.clone())
+            .with_rng(MockRng::constant(0))
+            .with_hybrid_counter(HybridCounter::new(1).unwrap())
+            .unwrap()
+            .with_exhaustion_policy(ExhaustionPolicy::SpinWaitForNextMs)
+            .with_metrics(Rollovers(rollovers.clone()));
+        generator.generate_batch(2).unwrap();
+
+        // Spins on a used-up millisecond until the clock moves on.
+        let spinner = thread::spawn(move || {
+            let id = generator.generate().unwrap();
+            let batch = generator.generate_batch(2).unwrap();
+            (id, batch)
+        });
+        thread::sleep(Duration::from_millis(20));
+        clock.advance(1);
+        thread::sleep(Duration::from_millis(20));
+        clock.advance(1);
+        let (id, batch) = spinner.join().unwrap();
+        assert_eq!(id.get_timestamp(), 1001);
+        assert_eq!(batch[1].get_timestamp(), 1002);
+        assert_eq!(rollovers.load(Ordering::Relaxed), 2);
+    }
+}
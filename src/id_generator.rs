@@ -0,0 +1,115 @@
+use crate::{AdaptiveSequencer, Nano64, Nano64Error, Nano64Generator, RateLimitedGenerator};
+#[cfg(feature = "encryption")]
+use crate::{AeadBackend, Nano64EncryptionFactory};
+use std::sync::Arc;
+
+// Object-safe source of IDs, for injecting `Arc<dyn IdGenerator>` and swapping in mocks.
+//
+// Any `Fn() -> Result<Nano64, Nano64Error>` is a generator, so a mock is just a closure and a
+// monotonic `Nano64Generator` is `move || generator.generate_monotonic()`.
+pub trait IdGenerator: Send + Sync {
+    fn next_id(&self) -> Result<Nano64, Nano64Error>;
+}
+
+impl<F> IdGenerator for F
+where
+    F: Fn() -> Result<Nano64, Nano64Error> + Send + Sync,
+{
+    fn next_id(&self) -> Result<Nano64, Nano64Error> {
+        self()
+    }
+}
+
+impl<G: IdGenerator + ?Sized> IdGenerator for Arc<G> {
+    fn next_id(&self) -> Result<Nano64, Nano64Error> {
+        (**self).next_id()
+    }
+}
+
+// `Nano64::generate_default`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DefaultIdGenerator;
+
+impl IdGenerator for DefaultIdGenerator {
+    fn next_id(&self) -> Result<Nano64, Nano64Error> {
+        Nano64::generate_default()
+    }
+}
+
+// `Nano64::generate_monotonic_default`, on the process-wide monotonic sequence.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MonotonicIdGenerator;
+
+impl IdGenerator for MonotonicIdGenerator {
+    fn next_id(&self) -> Result<Nano64, Nano64Error> {
+        Nano64::generate_monotonic_default()
+    }
+}
+
+impl IdGenerator for Nano64Generator {
+    fn next_id(&self) -> Result<Nano64, Nano64Error> {
+        self.generate()
+    }
+}
+
+impl IdGenerator for RateLimitedGenerator {
+    fn next_id(&self) -> Result<Nano64, Nano64Error> {
+        self.generate()
+    }
+}
+
+impl IdGenerator for AdaptiveSequencer {
+    fn next_id(&self) -> Result<Nano64, Nano64Error> {
+        self.generate_now()
+    }
+}
+
+// The plaintext ID of a freshly generated encrypted ID.
+#[cfg(feature = "encryption")]
+impl<B: AeadBackend> IdGenerator for Nano64EncryptionFactory<B> {
+    fn next_id(&self) -> Result<Nano64, Nano64Error> {
+        Ok(self.generate_encrypted_now()?.id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        DefaultIdGenerator, IdGenerator, MonotonicIdGenerator, Nano64, Nano64Error, Nano64Generator,
+    };
+    use std::sync::Arc;
+
+    fn collect(generator: &dyn IdGenerator, n: usize) -> Vec<Nano64> {
+        (0..n).map(|_| generator.next_id().unwrap()).collect()
+    }
+
+    #[test]
+    fn test_id_generators_are_injectable() {
+        #[allow(unused_mut)]
+        let mut generators: Vec<Arc<dyn IdGenerator>> = vec![
+            Arc::new(DefaultIdGenerator),
+            Arc::new(MonotonicIdGenerator),
+            Arc::new(Nano64Generator::new()),
+        ];
+        #[cfg(feature = "encryption")]
+        generators.push(Arc::new(
+            crate::Nano64EncryptionFactory::new(&[1u8; 32], None, None).unwrap(),
+        ));
+        for generator in &generators {
+            assert!(!collect(generator.as_ref(), 3)[0].is_nil());
+        }
+
+        let generator = Nano64Generator::new();
+        let monotonic: Box<dyn IdGenerator> = Box::new(move || generator.generate_monotonic());
+        let ids = collect(monotonic.as_ref(), 100);
+        assert!(ids.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn test_closure_mock_id_generator() {
+        let mock: Arc<dyn IdGenerator> = Arc::new(|| Ok(Nano64::new(42)));
+        assert_eq!(mock.next_id().unwrap().u64_value(), 42);
+        let failing = Arc::new(|| Err(Nano64Error::Error("unavailable".into())));
+        assert!(failing.next_id().is_err());
+    }
+}
@@ -0,0 +1,128 @@
+use crate::{KEY_LENGTH, Key, Nano64EncryptionFactory, Nano64Error};
+use argon2::{Algorithm, Argon2, Params, Version};
+
+// MIN_SALT_LENGTH is the shortest salt `Key::from_passphrase` accepts.
+pub const MIN_SALT_LENGTH: usize = 16;
+
+// Argon2id cost parameters. The defaults follow the OWASP recommendation of 19 MiB, 2 passes
+// and 1 lane; raise them as far as your startup time allows. Changing any parameter changes
+// the derived key, so store them alongside the salt.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct KdfParams {
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        Self {
+            memory_kib: 19 * 1024,
+            iterations: 2,
+            parallelism: 1,
+        }
+    }
+}
+
+impl Key {
+    // Derives a key from a passphrase with Argon2id. The salt need not be secret but should be
+    // random and unique per deployment; reuse the same salt and params to get the same key.
+    pub fn from_passphrase(
+        passphrase: &str,
+        salt: &[u8],
+        params: &KdfParams,
+    ) -> Result<Self, Nano64Error> {
+        if salt.len() < MIN_SALT_LENGTH {
+            return Err(Nano64Error::Error(format!(
+                "salt must be at least {MIN_SALT_LENGTH} bytes, got {}",
+                salt.len()
+            )));
+        }
+        let params = Params::new(
+            params.memory_kib,
+            params.iterations,
+            params.parallelism,
+            Some(KEY_LENGTH),
+        )
+        .map_err(|e| Nano64Error::Error(format!("invalid KDF params: {e}")))?;
+        let mut key = [0u8; KEY_LENGTH];
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+            .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+            .map_err(|e| Nano64Error::Error(format!("key derivation failed: {e}")))?;
+        let derived = Key::from_bytes(key);
+        #[cfg(feature = "zeroize")]
+        zeroize::Zeroize::zeroize(&mut key);
+        Ok(derived)
+    }
+}
+
+impl Nano64EncryptionFactory {
+    // An AES-256-GCM factory with the default clock and RNG, keyed by `Key::from_passphrase`.
+    pub fn from_passphrase(
+        passphrase: &str,
+        salt: &[u8],
+        params: &KdfParams,
+    ) -> Result<Self, Nano64Error> {
+        Self::new(
+            Key::from_passphrase(passphrase, salt, params)?.as_bytes(),
+            None,
+            None,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{KdfParams, Key, Nano64EncryptionFactory};
+
+    // Cheap parameters so the test stays fast; never use these for real keys.
+    const FAST: KdfParams = KdfParams {
+        memory_kib: 64,
+        iterations: 1,
+        parallelism: 1,
+    };
+
+    #[test]
+    fn test_passphrase_keys() {
+        let salt = b"nano64-test-salt";
+        let key = Key::from_passphrase("correct horse", salt, &FAST).unwrap();
+        assert_eq!(
+            Key::from_passphrase("correct horse", salt, &FAST).unwrap(),
+            key
+        );
+        assert_ne!(
+            Key::from_passphrase("battery staple", salt, &FAST).unwrap(),
+            key
+        );
+        assert_ne!(
+            Key::from_passphrase("correct horse", b"another-salt-16b", &FAST).unwrap(),
+            key
+        );
+        let slower = KdfParams {
+            iterations: 2,
+            ..FAST
+        };
+        assert_ne!(
+            Key::from_passphrase("correct horse", salt, &slower).unwrap(),
+            key
+        );
+
+        assert!(Key::from_passphrase("correct horse", b"short", &FAST).is_err());
+        let invalid = KdfParams {
+            memory_kib: 1,
+            ..FAST
+        };
+        assert!(Key::from_passphrase("correct horse", salt, &invalid).is_err());
+
+        let factory =
+            Nano64EncryptionFactory::from_passphrase("correct horse", salt, &FAST).unwrap();
+        let same = Nano64EncryptionFactory::new(key.as_bytes(), None, None).unwrap();
+        let encrypted = factory.generate_encrypted_now().unwrap();
+        assert_eq!(
+            same.from_encrypted_bytes(&encrypted.to_encrypted_bytes())
+                .unwrap()
+                .id,
+            encrypted.id
+        );
+    }
+}
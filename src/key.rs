@@ -0,0 +1,264 @@
+use crate::{Base64Url, Nano64EncryptionFactory, Nano64Error};
+use aes_gcm::aead::{OsRng, rand_core::RngCore};
+use std::fmt;
+
+// KEY_LENGTH is the length of a `Key` in bytes: an AES-256 key.
+pub const KEY_LENGTH: usize = 32;
+
+// A 32-byte encryption key. The parsers are strict, so a key pasted with a trailing newline, a
+// `0x` prefix or a character missing fails here with a clear error instead of later, at
+// encryption time. `Debug` never prints the key.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Key([u8; KEY_LENGTH]);
+
+impl Key {
+    // A fresh key from the OS RNG.
+    pub fn generate() -> Self {
+        let mut key = [0u8; KEY_LENGTH];
+        OsRng.fill_bytes(&mut key);
+        Self(key)
+    }
+
+    pub fn from_bytes(bytes: [u8; KEY_LENGTH]) -> Self {
+        Self(bytes)
+    }
+
+    pub fn from_slice(bytes: &[u8]) -> Result<Self, Nano64Error> {
+        let key = bytes
+            .try_into()
+            .map_err(|_| Nano64Error::InvalidByteLength {
+                expected: KEY_LENGTH,
+                got: bytes.len(),
+            })?;
+        Ok(Self(key))
+    }
+
+    // Exactly 64 hex digits, upper or lower case, without prefix or separators.
+    pub fn from_hex(s: &str) -> Result<Self, Nano64Error> {
+        reject_whitespace(s)?;
+        if s.len() != KEY_LENGTH * 2 {
+            return Err(Nano64Error::InvalidEncodedLength {
+                encoding: "hex key",
+                expected: KEY_LENGTH * 2,
+                got: s.len(),
+            });
+        }
+        if let Some(c) = s.chars().find(|c| !c.is_ascii_hexdigit()) {
+            return Err(Nano64Error::InvalidEncodedChar {
+                encoding: "hex key",
+                character: c,
+            });
+        }
+        let mut key = [0u8; KEY_LENGTH];
+        hex::decode_to_slice(s, &mut key).map_err(|_| Nano64Error::HexStringContainsNonHexChars)?;
+        Ok(Self(key))
+    }
+
+    // Standard padded base64 (RFC 4648 section 4), 44 chars, as printed by
+    // `openssl rand -base64 32`.
+    pub fn from_base64(s: &str) -> Result<Self, Nano64Error> {
+        reject_whitespace(s)?;
+        let expected = KEY_LENGTH.div_ceil(3) * 4;
+        if s.len() != expected {
+            return Err(Nano64Error::InvalidEncodedLength {
+                encoding: "base64 key",
+                expected,
+                got: s.len(),
+            });
+        }
+        let Some(unpadded) = s.strip_suffix('=').filter(|rest| !rest.ends_with('=')) else {
+            return Err(Nano64Error::Error(
+                "base64 key must end with exactly one '=' of padding".into(),
+            ));
+        };
+        // Map onto the URL-safe alphabet and let its strict decoder do the rest.
+        let mut url_safe = String::with_capacity(unpadded.len());
+        for c in unpadded.chars() {
+            url_safe.push(match c {
+                '+' => '-',
+                '/' => '_',
+                '-' | '_' => {
+                    return Err(Nano64Error::InvalidEncodedChar {
+                        encoding: "base64 key",
+                        character: c,
+                    });
+                }
+                c => c,
+            });
+        }
+        Self::from_slice(&Base64Url::decode(&url_safe)?)
+    }
+
+    // Either form, told apart by length: 64 chars of hex or 44 of base64.
+    pub fn parse(s: &str) -> Result<Self, Nano64Error> {
+        if s.len() == KEY_LENGTH * 2 {
+            Self::from_hex(s)
+        } else {
+            Self::from_base64(s)
+        }
+    }
+
+    // Reads a key in either form from the environment variable `name`. Errors name the
+    // variable but never include its value.
+    pub fn from_env(name: &str) -> Result<Self, Nano64Error> {
+        let value = std::env::var(name).map_err(|e| match e {
+            std::env::VarError::NotPresent => {
+                Nano64Error::Error(format!("environment variable {name} is not set"))
+            }
+            std::env::VarError::NotUnicode(_) => {
+                Nano64Error::Error(format!("environment variable {name} is not valid UTF-8"))
+            }
+        })?;
+        Self::parse(&value).map_err(|e| {
+            Nano64Error::Error(format!(
+                "environment variable {name} is not a 64-char hex or 44-char base64 key: {e}"
+            ))
+        })
+    }
+
+    pub fn as_bytes(&self) -> &[u8; KEY_LENGTH] {
+        &self.0
+    }
+
+    pub fn to_hex(&self) -> String {
+        hex::encode_upper(self.0)
+    }
+
+    pub fn to_base64(&self) -> String {
+        let url_safe = Base64Url::encode(&self.0);
+        let mut out: String = url_safe
+            .chars()
+            .map(|c| match c {
+                '-' => '+',
+                '_' => '/',
+                c => c,
+            })
+            .collect();
+        out.push('=');
+        out
+    }
+}
+
+impl fmt::Debug for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Key(..)")
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for Key {
+    fn drop(&mut self) {
+        zeroize::Zeroize::zeroize(&mut self.0);
+    }
+}
+
+impl Nano64EncryptionFactory {
+    // A fresh AES-256 key from the OS RNG. See `Key` for parsing keys from config.
+    pub fn generate_key() -> [u8; KEY_LENGTH] {
+        *Key::generate().as_bytes()
+    }
+
+    // An AES-256-GCM factory with the default clock and RNG, keyed from the environment
+    // variable `name`, e.g. `NANO64_KEY`. See `Key::from_env`.
+    pub fn from_env(name: &str) -> Result<Self, Nano64Error> {
+        Self::new(Key::from_env(name)?.as_bytes(), None, None)
+    }
+}
+
+fn reject_whitespace(s: &str) -> Result<(), Nano64Error> {
+    if s.chars().any(char::is_whitespace) {
+        return Err(Nano64Error::Error(
+            "key contains whitespace; trim it before parsing".into(),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{KEY_LENGTH, Key, Nano64EncryptionFactory, Nano64Error};
+
+    #[test]
+    fn test_key_round_trips() {
+        let key = Key::generate();
+        assert_eq!(Key::from_hex(&key.to_hex()).unwrap(), key);
+        assert_eq!(Key::from_hex(&key.to_hex().to_lowercase()).unwrap(), key);
+        assert_eq!(key.to_base64().len(), 44);
+        assert_eq!(Key::from_base64(&key.to_base64()).unwrap(), key);
+        assert_ne!(Key::generate(), key);
+        assert_ne!(
+            Nano64EncryptionFactory::generate_key(),
+            Nano64EncryptionFactory::generate_key()
+        );
+        assert_eq!(format!("{key:?}"), "Key(..)");
+
+        let factory = Nano64EncryptionFactory::new(key.as_bytes(), None, None).unwrap();
+        assert!(factory.generate_encrypted_now().is_ok());
+    }
+
+    #[test]
+    fn test_key_parsing_is_strict() {
+        let bytes: [u8; KEY_LENGTH] = std::array::from_fn(|i| i as u8 * 7);
+        let key = Key::from_bytes(bytes);
+        assert_eq!(
+            Key::from_base64("AAcOFRwjKjE4P0ZNVFtiaXB3foWMk5qhqK+2vcTL0tk=").unwrap(),
+            key
+        );
+
+        let hex = key.to_hex();
+        assert!(Key::from_hex(&format!("{hex}\n")).is_err());
+        assert!(Key::from_hex(&format!("0x{hex}")).is_err());
+        assert!(matches!(
+            Key::from_hex(&hex[..62]),
+            Err(Nano64Error::InvalidEncodedLength { got: 62, .. })
+        ));
+        assert!(matches!(
+            Key::from_hex(&format!("{}G", &hex[..63])),
+            Err(Nano64Error::InvalidEncodedChar { character: 'G', .. })
+        ));
+
+        let base64 = key.to_base64();
+        assert!(Key::from_base64(&base64[..43]).is_err());
+        assert!(Key::from_base64(&format!("{}==", &base64[..42])).is_err());
+        assert!(Key::from_base64(&base64.replace('+', "-")).is_err());
+        assert!(matches!(
+            Key::from_slice(&[0u8; 16]),
+            Err(Nano64Error::InvalidByteLength { got: 16, .. })
+        ));
+    }
+
+    #[test]
+    fn test_from_env() {
+        let key = Key::generate();
+        // Each test uses its own variable, so tests running in parallel don't race.
+        unsafe {
+            std::env::set_var("NANO64_TEST_KEY_HEX", key.to_hex());
+            std::env::set_var("NANO64_TEST_KEY_BASE64", key.to_base64());
+            std::env::set_var("NANO64_TEST_KEY_BAD", "not-a-key");
+        }
+        assert_eq!(Key::from_env("NANO64_TEST_KEY_HEX").unwrap(), key);
+        assert_eq!(Key::from_env("NANO64_TEST_KEY_BASE64").unwrap(), key);
+
+        let factory = Nano64EncryptionFactory::from_env("NANO64_TEST_KEY_HEX").unwrap();
+        let same = Nano64EncryptionFactory::new(key.as_bytes(), None, None).unwrap();
+        let encrypted = factory.generate_encrypted_now().unwrap();
+        assert_eq!(
+            same.from_encrypted_bytes(&encrypted.to_encrypted_bytes())
+                .unwrap()
+                .id,
+            encrypted.id
+        );
+
+        let missing = Key::from_env("NANO64_TEST_KEY_MISSING").unwrap_err();
+        assert!(
+            missing
+                .to_string()
+                .contains("NANO64_TEST_KEY_MISSING is not set")
+        );
+        let bad = Key::from_env("NANO64_TEST_KEY_BAD")
+            .unwrap_err()
+            .to_string();
+        assert!(bad.contains("NANO64_TEST_KEY_BAD"));
+        assert!(!bad.contains("not-a-key"));
+    }
+}
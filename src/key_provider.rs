@@ -0,0 +1,161 @@
+use crate::{
+    AesGcm, ClockImpl, GcmAlgorithm, Key, Keyring, Nano64EncryptionFactory, Nano64Error,
+    RandomNumberGeneratorImpl,
+};
+use std::collections::BTreeMap;
+
+// Supplies data keys by key ID from an external key-management system, so long-term keys
+// never have to be configured in the process. See `EnvelopeKeys` for data keys stored wrapped
+// under a KMS, Vault or HSM master key.
+pub trait KeyProvider: Send + Sync {
+    // The ID of the key new payloads are encrypted with.
+    fn current_key_id(&self) -> u8;
+
+    // Every ID that may appear in stored payloads, the current one included.
+    fn key_ids(&self) -> Vec<u8>;
+
+    fn data_key(&self, key_id: u8) -> Result<Key, Nano64Error>;
+}
+
+// Unwraps a data key with a master key held elsewhere, e.g. a KMS `Decrypt` call, a Vault
+// transit decrypt or an HSM unwrap.
+pub trait KeyUnwrapper: Send + Sync {
+    fn unwrap_key(&self, wrapped: &[u8]) -> Result<Key, Nano64Error>;
+}
+
+// Envelope encryption: data keys kept wrapped, e.g. in config, by key ID, and unwrapped through
+// `unwrapper` when the keyring is built.
+pub struct EnvelopeKeys<U: KeyUnwrapper> {
+    unwrapper: U,
+    current_id: u8,
+    wrapped: BTreeMap<u8, Vec<u8>>,
+}
+
+impl<U: KeyUnwrapper> EnvelopeKeys<U> {
+    pub fn new(unwrapper: U, key_id: u8, wrapped: impl Into<Vec<u8>>) -> Self {
+        Self {
+            unwrapper,
+            current_id: key_id,
+            wrapped: BTreeMap::from([(key_id, wrapped.into())]),
+        }
+    }
+
+    // Adds a wrapped decrypt-only key.
+    pub fn with_retired_key(mut self, key_id: u8, wrapped: impl Into<Vec<u8>>) -> Self {
+        self.wrapped.entry(key_id).or_insert_with(|| wrapped.into());
+        self
+    }
+}
+
+impl<U: KeyUnwrapper> KeyProvider for EnvelopeKeys<U> {
+    fn current_key_id(&self) -> u8 {
+        self.current_id
+    }
+
+    fn key_ids(&self) -> Vec<u8> {
+        self.wrapped.keys().copied().collect()
+    }
+
+    fn data_key(&self, key_id: u8) -> Result<Key, Nano64Error> {
+        let wrapped = self
+            .wrapped
+            .get(&key_id)
+            .ok_or(Nano64Error::UnknownKeyId(key_id))?;
+        self.unwrapper.unwrap_key(wrapped)
+    }
+}
+
+impl Keyring {
+    // Fetches every key from `provider` once, as AES-256-GCM keys. The keyring holds the
+    // unwrapped data keys from then on, so the KMS isn't called per payload.
+    pub fn from_provider(provider: &impl KeyProvider) -> Result<Self, Nano64Error> {
+        let aes = |key_id| AesGcm::new(GcmAlgorithm::Aes256, provider.data_key(key_id)?.as_bytes());
+        let current_id = provider.current_key_id();
+        let mut keys = Keyring::new(current_id, aes(current_id)?);
+        for key_id in provider.key_ids() {
+            if key_id != current_id {
+                keys = keys.with_retired_key(key_id, aes(key_id)?);
+            }
+        }
+        Ok(keys)
+    }
+}
+
+impl Nano64EncryptionFactory {
+    // A factory over `Keyring::from_provider`.
+    pub fn from_key_provider(
+        provider: &impl KeyProvider,
+        clock: Option<ClockImpl>,
+        rng: Option<RandomNumberGeneratorImpl>,
+    ) -> Result<Self, Nano64Error> {
+        Ok(Self::with_keyring(
+            Keyring::from_provider(provider)?,
+            clock,
+            rng,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        AesGcm, EnvelopeKeys, GcmAlgorithm, KEY_LENGTH, Key, KeyUnwrapper, Keyring,
+        Nano64EncryptionFactory, Nano64Error,
+    };
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    // Stands in for a KMS: "wraps" by XOR with a master key it never hands out.
+    struct XorKms {
+        master: [u8; KEY_LENGTH],
+        calls: AtomicUsize,
+    }
+
+    impl XorKms {
+        fn wrap(&self, key: &[u8; KEY_LENGTH]) -> Vec<u8> {
+            key.iter().zip(self.master).map(|(k, m)| k ^ m).collect()
+        }
+    }
+
+    impl KeyUnwrapper for &XorKms {
+        fn unwrap_key(&self, wrapped: &[u8]) -> Result<Key, Nano64Error> {
+            self.calls.fetch_add(1, Ordering::Relaxed);
+            let unwrapped: Vec<u8> = wrapped
+                .iter()
+                .zip(self.master)
+                .map(|(w, m)| w ^ m)
+                .collect();
+            Key::from_slice(&unwrapped)
+        }
+    }
+
+    #[test]
+    fn test_envelope_keys() {
+        let kms = XorKms {
+            master: [0x5A; KEY_LENGTH],
+            calls: AtomicUsize::new(0),
+        };
+        let (old_key, new_key) = ([1u8; KEY_LENGTH], [2u8; KEY_LENGTH]);
+        let provider =
+            EnvelopeKeys::new(&kms, 2, kms.wrap(&new_key)).with_retired_key(1, kms.wrap(&old_key));
+
+        let factory = Nano64EncryptionFactory::from_key_provider(&provider, None, None).unwrap();
+        assert_eq!(factory.keyring().current_key_id(), 2);
+        assert_eq!(kms.calls.load(Ordering::Relaxed), 2);
+
+        // Payloads under the retired key still decrypt, without calling the KMS again.
+        let old = Nano64EncryptionFactory::with_keyring(
+            Keyring::new(1, AesGcm::new(GcmAlgorithm::Aes256, &old_key).unwrap()),
+            None,
+            None,
+        );
+        let stored = old.generate_encrypted_now().unwrap();
+        assert_eq!(factory.decrypt(&stored.to_payload()).unwrap().id, stored.id);
+        let encrypted = factory.generate_encrypted_now().unwrap();
+        assert_eq!(encrypted.to_encrypted_bytes()[1], 2);
+        assert!(old.decrypt(&encrypted.to_payload()).is_err());
+        assert_eq!(kms.calls.load(Ordering::Relaxed), 2);
+
+        let bad = EnvelopeKeys::new(&kms, 3, vec![0u8; 16]);
+        assert!(Nano64EncryptionFactory::from_key_provider(&bad, None, None).is_err());
+    }
+}
@@ -0,0 +1,54 @@
+use crate::{AeadBackend, AesGcm};
+use std::collections::BTreeMap;
+
+// Encryption keys by one-byte key ID, for rotating keys without breaking stored payloads. The
+// current key encrypts new payloads and its ID is written in front of them; retired keys are
+// only used to decrypt payloads that carry their ID.
+pub struct Keyring<B: AeadBackend = AesGcm> {
+    current_id: u8,
+    current: B,
+    retired: BTreeMap<u8, B>,
+}
+
+impl<B: AeadBackend> Keyring<B> {
+    pub fn new(key_id: u8, current: B) -> Self {
+        Self {
+            current_id: key_id,
+            current,
+            retired: BTreeMap::new(),
+        }
+    }
+
+    // Adds a decrypt-only key. A retired key registered under the current key's ID is never
+    // used, so give every key its own ID.
+    pub fn with_retired_key(mut self, key_id: u8, backend: B) -> Self {
+        self.retired.insert(key_id, backend);
+        self
+    }
+
+    pub fn current_key_id(&self) -> u8 {
+        self.current_id
+    }
+
+    pub fn current(&self) -> &B {
+        &self.current
+    }
+
+    pub fn get(&self, key_id: u8) -> Option<&B> {
+        if key_id == self.current_id {
+            Some(&self.current)
+        } else {
+            self.retired.get(&key_id)
+        }
+    }
+
+    // Every key, current first.
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (u8, &B)> {
+        std::iter::once((self.current_id, &self.current)).chain(
+            self.retired
+                .iter()
+                .filter(|(id, _)| **id != self.current_id)
+                .map(|(id, backend)| (*id, backend)),
+        )
+    }
+}
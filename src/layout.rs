@@ -0,0 +1,56 @@
+use crate::{
+    MAX_TIMESTAMP, RANDOM_BITS, RANDOM_MASK, TIMESTAMP_BITS, TIMESTAMP_MASK, TIMESTAMP_SHIFT,
+};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// Describes how a Nano64 packs its fields, so tools that display or validate IDs don't have to
+// copy the masks. Available as `Nano64::LAYOUT`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Layout {
+    pub timestamp_bits: u64,
+    pub random_bits: u64,
+    // Bit position of the lowest timestamp bit.
+    pub timestamp_shift: u64,
+    // Mask for the timestamp after shifting it down by `timestamp_shift`.
+    pub timestamp_mask: u64,
+    pub random_mask: u64,
+    pub max_timestamp: u64,
+}
+
+impl Layout {
+    pub(crate) const NANO64: Layout = Layout {
+        timestamp_bits: TIMESTAMP_BITS,
+        random_bits: RANDOM_BITS,
+        timestamp_shift: TIMESTAMP_SHIFT,
+        timestamp_mask: TIMESTAMP_MASK,
+        random_mask: RANDOM_MASK,
+        max_timestamp: MAX_TIMESTAMP,
+    };
+
+    // Latest moment a timestamp can represent, with millisecond precision.
+    pub fn max_date(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_millis(self.max_timestamp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Layout, Nano64};
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn test_layout_matches_id() {
+        let layout = Nano64::LAYOUT;
+        assert_eq!(layout, Layout::NANO64);
+        assert_eq!(layout.timestamp_bits + layout.random_bits, 64);
+        let id = Nano64::MAX;
+        assert_eq!(
+            (id.u64_value() >> layout.timestamp_shift) & layout.timestamp_mask,
+            id.get_timestamp()
+        );
+        assert_eq!(id.u64_value() & layout.random_mask, id.get_random() as u64);
+        assert_eq!(layout.max_date(), Nano64::MAX.to_date());
+        // 2^44 ms is roughly 557 years past the epoch.
+        assert!(layout.max_date() > UNIX_EPOCH + Duration::from_secs(557 * 365 * 24 * 3600));
+    }
+}
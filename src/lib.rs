@@ -1,21 +1,163 @@
 //!
 //! [Please see our README for more info!](https://github.com/matthewoestreich/rs-nano64)
 //!
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::{
+    cmp::Ordering,
+    time::{SystemTime, UNIX_EPOCH},
+};
 
+mod adaptive;
+mod algorithm;
+#[cfg(feature = "tokio")]
+mod async_generator;
+#[cfg(feature = "encryption")]
+mod blind_index;
+#[cfg(feature = "encryption")]
+mod cipher;
+mod clock;
+#[cfg(feature = "tokio-util")]
+mod codec;
+pub mod convert;
+mod defaults;
+mod encoding;
+mod entropy_pool;
+mod epoch;
 mod errors;
-mod hex;
+mod explain;
+#[cfg(feature = "encryption")]
+mod fpe;
+mod fuzzy;
+mod generator;
+mod hybrid;
+mod id_generator;
+#[cfg(feature = "kdf")]
+mod kdf;
+#[cfg(feature = "encryption")]
+mod key;
+#[cfg(feature = "encryption")]
+mod key_provider;
+#[cfg(feature = "encryption")]
+mod keyring;
+mod layout;
+#[cfg(feature = "encryption")]
+mod mac;
+mod metrics;
 mod monotonic_refs;
+mod nano128;
+mod nano32;
 mod nano64;
+#[cfg(feature = "encryption")]
 mod nano64_encrypted;
+mod nano64_hex;
+mod nano64_micro;
+#[cfg(feature = "encryption")]
+mod nano64_roles;
+#[cfg(feature = "encryption")]
+mod nano64_signed;
+#[cfg(feature = "encryption")]
+mod nano64_token;
+mod node;
+mod obfuscate;
+#[cfg(feature = "rayon")]
+mod parallel;
+mod persistence;
+mod policy;
+mod quota;
+mod random;
+mod rate_limit;
+#[cfg(feature = "serde")]
+mod serde_impl;
+#[cfg(feature = "serde")]
+pub mod serde_string;
+mod service;
+mod smear;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+#[cfg(feature = "uuid")]
+mod uuid_v7;
+mod validation;
+mod wordlist;
+mod words;
 
+pub use adaptive::*;
+pub use algorithm::*;
+#[cfg(feature = "tokio")]
+pub use async_generator::*;
+#[cfg(feature = "encryption")]
+pub use blind_index::*;
+#[cfg(feature = "encryption")]
+pub use cipher::*;
+pub use clock::*;
+#[cfg(feature = "tokio-util")]
+pub use codec::*;
+pub use defaults::{set_default_clock, set_default_rng, set_default_rng_fn};
+pub use encoding::*;
+pub use entropy_pool::pooled_rng;
+pub use epoch::*;
 pub use errors::*;
-pub use hex::*;
+pub use explain::*;
+#[cfg(feature = "encryption")]
+pub use fpe::*;
+pub use generator::*;
+pub use hybrid::HybridCounter;
+pub use id_generator::*;
+#[cfg(feature = "kdf")]
+pub use kdf::*;
+#[cfg(feature = "encryption")]
+pub use key::*;
+#[cfg(feature = "encryption")]
+pub use key_provider::*;
+#[cfg(feature = "encryption")]
+pub use keyring::*;
+pub use layout::*;
+pub use metrics::*;
+pub use monotonic_refs::{MonotonicBlock, MonotonicState};
+pub use nano32::*;
 pub use nano64::*;
+#[cfg(feature = "encryption")]
 pub use nano64_encrypted::*;
+pub use nano64_hex::*;
+pub use nano64_micro::*;
+#[cfg(feature = "encryption")]
+pub use nano64_roles::*;
+#[cfg(feature = "encryption")]
+pub use nano64_signed::*;
+#[cfg(feature = "encryption")]
+pub use nano64_token::*;
+pub use nano128::*;
+pub use node::*;
+pub use obfuscate::*;
+pub use persistence::*;
+pub use policy::*;
+pub use quota::*;
+pub use random::*;
+pub use rate_limit::*;
+pub use service::*;
+pub use smear::*;
+pub use validation::*;
+pub use words::*;
 
 pub const IV_LENGTH: usize = 12;
-pub const PAYLOAD_LENGTH: usize = IV_LENGTH + 8 + 16;
+// Format version written as the first byte of every encrypted payload.
+pub const PAYLOAD_VERSION: u8 = 1;
+// Version and key ID bytes in front of the IV.
+pub const PAYLOAD_HEADER_LENGTH: usize = 2;
+pub const PAYLOAD_LENGTH: usize = PAYLOAD_HEADER_LENGTH + LEGACY_PAYLOAD_LENGTH;
+// Encrypted payload from before `PAYLOAD_VERSION`: IV, ciphertext and tag only.
+pub const LEGACY_PAYLOAD_LENGTH: usize = IV_LENGTH + 8 + 16;
+// Encrypted `Nano128` payload: header, IV, 16-byte ciphertext and tag.
+pub const NANO128_PAYLOAD_LENGTH: usize = PAYLOAD_HEADER_LENGTH + NANO128_LEGACY_PAYLOAD_LENGTH;
+pub const NANO128_LEGACY_PAYLOAD_LENGTH: usize = IV_LENGTH + 16 + 16;
+// Most caller-supplied bytes `encrypt_with_metadata` seals next to a `Nano64`. Each one makes
+// the payload a byte longer.
+pub const MAX_METADATA_LENGTH: usize = 4;
+// Format version of payloads written by factories built `with_truncated_tag`.
+pub const TRUNCATED_PAYLOAD_VERSION: u8 = 2;
+// Bytes of the GCM tag kept in a truncated-tag payload.
+pub const TRUNCATED_TAG_LENGTH: usize = 8;
+// Encrypted `Nano64` payload with a truncated tag: header, IV, ciphertext and 8 tag bytes.
+pub const TRUNCATED_PAYLOAD_LENGTH: usize =
+    PAYLOAD_HEADER_LENGTH + IV_LENGTH + 8 + TRUNCATED_TAG_LENGTH;
 // TIMESTAMP_BITS is the number of bits allocated to the millisecond timestamp (0..2^44-1).
 pub const TIMESTAMP_BITS: u64 = 44;
 // RANDOM_BITS is the number of bits allocated to the random field per millisecond (0..2^20-1).
@@ -32,12 +174,16 @@ pub(crate) const MAX_TIMESTAMP: u64 = TIMESTAMP_MASK;
 // Compare compares two IDs as unsigned 64-bit numbers.
 // Returns -1 if a < b, 0 if a == b, 1 if a > b.
 pub fn compare(a: &Nano64, b: &Nano64) -> i64 {
-    if a.value < b.value {
-        return -1;
-    } else if a.value > b.value {
-        return 1;
+    match compare_ordering(a, b) {
+        Ordering::Less => -1,
+        Ordering::Equal => 0,
+        Ordering::Greater => 1,
     }
-    return 0;
+}
+
+// Like `compare`, but returns a `std::cmp::Ordering` so it can be used with `sort_by` directly.
+pub fn compare_ordering(a: &Nano64, b: &Nano64) -> Ordering {
+    a.value.cmp(&b.value)
 }
 
 // A function that returns a random unsigned integer containing a specified number of random bits.
@@ -46,21 +192,34 @@ pub type RandomNumberGeneratorImpl = fn(bits: u32) -> Result<u32, Nano64Error>;
 
 pub type ClockImpl = fn() -> u64;
 
-// Gets time now since epoch in ms
+// Gets time now since epoch in ms, from the installed default clock if there is one
 fn time_now_since_epoch_ms() -> u64 {
-    return SystemTime::now()
+    match defaults::installed_clock() {
+        Some(clock) => clock.now_ms(),
+        None => system_time_ms(),
+    }
+}
+
+// Gets the operating system's time since epoch in ms
+fn system_time_ms() -> u64 {
+    SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .expect("Time went backwards")
-        .as_millis() as u64;
+        .as_millis() as u64
 }
 
 // Default cryptographically-secure RNG.
 // `bits` must be in the 1-32 range.
+// Uses the installed default RNG if there is one.
 fn default_rng(bits: u32) -> Result<u32, Nano64Error> {
     if bits == 0 || bits > 32 {
         return Err(Nano64Error::Error(format!("bits must be 1-32, got {bits}")));
     }
 
+    if let Some(rng) = defaults::installed_rng() {
+        return rng.random_bits(bits);
+    }
+
     // Generate 4 random bytes
     let mut buf = [0u8; 4];
     rand::fill(&mut buf);
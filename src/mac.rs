@@ -0,0 +1,79 @@
+use crate::Nano64Error;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use subtle::ConstantTimeEq;
+
+// HMAC-SHA256 keyed once, for signing and blind indexes. The keyed state is as good as the key,
+// so with the `zeroize` feature it is wiped on drop, along with the copies made per message.
+#[derive(Clone)]
+pub(crate) struct KeyedHmac(Hmac<Sha256>);
+
+impl KeyedHmac {
+    pub(crate) fn new(key: &[u8]) -> Result<Self, Nano64Error> {
+        Hmac::<Sha256>::new_from_slice(key)
+            .map(Self)
+            .map_err(|e| Nano64Error::Error(format!("invalid HMAC key: {e}")))
+    }
+
+    pub(crate) fn tag(&self, message: &[u8]) -> [u8; 32] {
+        let mut mac = self.clone();
+        mac.0.update(message);
+        mac.0.finalize_reset().into_bytes().into()
+    }
+
+    // Whether `tag` is a prefix of `message`'s tag, compared in constant time.
+    pub(crate) fn verify_truncated(&self, message: &[u8], tag: &[u8]) -> bool {
+        let expected = self.tag(message);
+        !tag.is_empty()
+            && tag.len() <= expected.len()
+            && bool::from(expected[..tag.len()].ct_eq(tag))
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for KeyedHmac {
+    fn drop(&mut self) {
+        // SAFETY: the HMAC state is fixed-size arrays and integers, with no pointers or `Drop`
+        // impls, and all zeroes is a valid (if useless) value for each.
+        unsafe { zeroize::zeroize_flat_type(&mut self.0) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::KeyedHmac;
+
+    #[test]
+    fn test_keyed_hmac() {
+        // RFC 4231, test case 2.
+        let mac = KeyedHmac::new(b"Jefe").unwrap();
+        let message = b"what do ya want for nothing?";
+        let expected =
+            hex::decode("5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843")
+                .unwrap();
+        assert_eq!(mac.tag(message).to_vec(), expected);
+        // Repeatable: tagging doesn't disturb the keyed state.
+        assert_eq!(mac.tag(message).to_vec(), expected);
+
+        assert!(mac.verify_truncated(message, &expected[..8]));
+        assert!(mac.verify_truncated(message, &expected));
+        assert!(!mac.verify_truncated(message, &[]));
+        assert!(!mac.verify_truncated(b"what do ya want for something?", &expected[..8]));
+        let mut forged = expected.clone();
+        forged[7] ^= 1;
+        assert!(!mac.verify_truncated(message, &forged[..8]));
+    }
+
+    #[cfg(feature = "zeroize")]
+    #[test]
+    fn test_keyed_hmac_zeroized_on_drop() {
+        use std::mem::{ManuallyDrop, size_of};
+
+        let mut mac = ManuallyDrop::new(KeyedHmac::new(b"0123456789abcdef").unwrap());
+        let state = &*mac as *const KeyedHmac as *const u8;
+        // SAFETY: `mac` is never used again, and its storage outlives the reads below.
+        unsafe { ManuallyDrop::drop(&mut mac) };
+        let bytes = unsafe { std::slice::from_raw_parts(state, size_of::<KeyedHmac>()) };
+        assert!(bytes.iter().all(|&b| b == 0));
+    }
+}
@@ -0,0 +1,25 @@
+use crate::{ClockAnomaly, Nano64Error};
+
+// Instrumentation hooks for `Nano64Generator`, e.g. to export Prometheus counters. Every method
+// defaults to doing nothing, so implement only what you need. Hooks run inline on the
+// generating thread and should be cheap (atomic increments, not I/O).
+pub trait GeneratorMetrics: Send + Sync {
+    // `count` IDs were issued; `monotonic` tells which mode issued them.
+    fn on_generate(&self, _count: u64, _monotonic: bool) {}
+
+    // The random field of `timestamp` ran out during monotonic generation, whatever the
+    // `ExhaustionPolicy` did about it.
+    fn on_monotonic_rollover(&self, _timestamp: u64) {}
+
+    // The clock read earlier than a reading already used.
+    fn on_clock_backwards(&self, _anomaly: ClockAnomaly) {}
+
+    // The RNG returned an error.
+    fn on_rng_error(&self, _error: &Nano64Error) {}
+}
+
+// The default metrics: none.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NoopMetrics;
+
+impl GeneratorMetrics for NoopMetrics {}
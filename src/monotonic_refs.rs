@@ -1,19 +1,547 @@
-use std::sync::{Arc, Mutex, OnceLock};
+use crate::{
+    ExhaustionPolicy, MAX_TIMESTAMP, Nano64, Nano64Error, NodeId, RANDOM_MASK, RandomSource,
+    TIMESTAMP_MASK, TIMESTAMP_SHIFT,
+};
+use std::{
+    cell::RefCell,
+    sync::{
+        Arc, Mutex, OnceLock,
+        atomic::{AtomicU64, Ordering as AtomicOrdering},
+    },
+};
 
+// Thread-local monotonic state; see `MonotonicState` for the shared, lock-free variant.
 pub(crate) struct MonotonicRefs {
     pub(crate) last_timestamp: u64,
     pub(crate) last_random: u64,
 }
 
-pub(crate) static MONOTONIC_REFS: OnceLock<Arc<Mutex<MonotonicRefs>>> = OnceLock::new();
+impl MonotonicRefs {
+    // Issues the next monotonic ID for `timestamp` and records it. `timestamp` must already be
+    // validated against `MAX_TIMESTAMP`.
+    pub(crate) fn next(
+        &mut self,
+        timestamp: u64,
+        rng: &mut dyn RandomSource,
+    ) -> Result<Nano64, Nano64Error> {
+        let last = (self.last_timestamp << TIMESTAMP_SHIFT) | self.last_random;
+        let value = successor(
+            last,
+            timestamp,
+            rng,
+            ExhaustionPolicy::BorrowNextMs,
+            NodeId::NONE,
+        )?;
+        self.last_timestamp = value >> TIMESTAMP_SHIFT;
+        self.last_random = value & RANDOM_MASK;
+        Ok(Nano64::new(value))
+    }
+}
+
+// The ID that follows `last` for a clock reading of `timestamp`.
+//
+// A newer millisecond gets a fresh random field. Otherwise (same millisecond, or the clock went
+// backwards) the previous ID is incremented, which carries into the timestamp when the random
+// field wraps, unless `policy` forbids borrowing the next millisecond. With a `node`, only the
+// bits below the node ID count up, and borrowing skips straight to the next millisecond.
+fn successor(
+    last: u64,
+    timestamp: u64,
+    rng: &mut dyn RandomSource,
+    policy: ExhaustionPolicy,
+    node: NodeId,
+) -> Result<u64, Nano64Error> {
+    let last_timestamp = last >> TIMESTAMP_SHIFT;
+    let counter_mask = node.counter_mask();
+    if timestamp > last_timestamp {
+        let random_value = rng.random_bits(node.counter_bits())?;
+        let ms = timestamp & TIMESTAMP_MASK;
+        return Ok((ms << TIMESTAMP_SHIFT) | node.prefix() | (random_value as u64 & counter_mask));
+    }
+    let exhausted = last & counter_mask == counter_mask;
+    if exhausted && policy != ExhaustionPolicy::BorrowNextMs {
+        return Err(Nano64Error::RandomFieldExhausted {
+            timestamp: last_timestamp,
+        });
+    }
+    #[cfg(feature = "tracing")]
+    if exhausted {
+        tracing::debug!(
+            timestamp = last_timestamp,
+            "monotonic random field exhausted, borrowing the next millisecond"
+        );
+    }
+    if exhausted && node != NodeId::NONE {
+        return (last_timestamp < MAX_TIMESTAMP)
+            .then(|| ((last_timestamp + 1) << TIMESTAMP_SHIFT) | node.prefix())
+            .ok_or_else(|| {
+                Nano64Error::Error(
+                    "timestamp overflow after incrementing for monotonic generation".into(),
+                )
+            });
+    }
+    if node != NodeId::NONE {
+        // Keep this node's ID in place even if `last` came from a generator with another one.
+        let counter = (last & counter_mask) + 1;
+        if counter > counter_mask {
+            return Err(Nano64Error::Error(
+                "monotonic counter overflowed into the node ID bits".into(),
+            ));
+        }
+        return Ok((last_timestamp << TIMESTAMP_SHIFT) | node.prefix() | counter);
+    }
+    // Only the very last ID (max timestamp, max random) has no successor.
+    last.checked_add(1).ok_or_else(|| {
+        Nano64Error::Error("timestamp overflow after incrementing for monotonic generation".into())
+    })
+}
+
+// CAS attempts before a thread queues on the fallback mutex instead of spinning.
+const CAS_ATTEMPTS_BEFORE_LOCK: u32 = 64;
+
+struct SharedRefs {
+    // Last issued ID; the timestamp and random field packed exactly as in the ID. `0` means
+    // nothing was issued yet (`successor` never returns 0).
+    last: AtomicU64,
+    // Only taken by threads that keep losing the CAS race, so they stop hammering the cache
+    // line and take turns instead.
+    contended: Mutex<()>,
+}
+
+// One monotonic ID space. IDs issued through the same state (or clones of it) are strictly
+// increasing; IDs from different states are unrelated. Cloning shares the state, so several
+// generators can deliberately feed one sequence.
+//
+// Issuing is lock-free in the common case: the last ID lives in one `AtomicU64` that is
+// advanced with compare-and-swap.
+#[derive(Clone)]
+pub struct MonotonicState {
+    shared: Arc<SharedRefs>,
+}
+
+impl Default for MonotonicState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MonotonicState {
+    pub fn new() -> Self {
+        Self {
+            shared: Arc::new(SharedRefs {
+                last: AtomicU64::new(0),
+                contended: Mutex::new(()),
+            }),
+        }
+    }
+
+    pub fn generate<R: RandomSource>(
+        &self,
+        timestamp: u64,
+        rng: &mut R,
+    ) -> Result<Nano64, Nano64Error> {
+        self.generate_dyn(timestamp, rng, ExhaustionPolicy::BorrowNextMs, NodeId::NONE)
+    }
+
+    pub(crate) fn generate_dyn(
+        &self,
+        timestamp: u64,
+        rng: &mut dyn RandomSource,
+        policy: ExhaustionPolicy,
+        node: NodeId,
+    ) -> Result<Nano64, Nano64Error> {
+        let first = self.reserve_dyn(timestamp, 1, rng, policy, node)?;
+        Ok(Nano64::new(first))
+    }
 
-pub(crate) fn get_monotonic_refs() -> Arc<Mutex<MonotonicRefs>> {
-    MONOTONIC_REFS
-        .get_or_init(|| {
-            Arc::new(Mutex::new(MonotonicRefs {
-                last_random: 0,
-                last_timestamp: 0,
-            }))
+    // Reserves `n` consecutive IDs with a single update of the shared state. The block starts
+    // where `generate` would and spills into later milliseconds if the random field runs out.
+    pub fn generate_block<R: RandomSource>(
+        &self,
+        timestamp: u64,
+        n: usize,
+        rng: &mut R,
+    ) -> Result<MonotonicBlock, Nano64Error> {
+        self.generate_block_dyn(
+            timestamp,
+            n,
+            rng,
+            ExhaustionPolicy::BorrowNextMs,
+            NodeId::NONE,
+        )
+    }
+
+    pub(crate) fn generate_block_dyn(
+        &self,
+        timestamp: u64,
+        n: usize,
+        rng: &mut dyn RandomSource,
+        policy: ExhaustionPolicy,
+        node: NodeId,
+    ) -> Result<MonotonicBlock, Nano64Error> {
+        if n == 0 {
+            return Ok(MonotonicBlock {
+                next: 0,
+                remaining: 0,
+            });
+        }
+        let first = self.reserve_dyn(timestamp, n as u64, rng, policy, node)?;
+        Ok(MonotonicBlock {
+            next: first,
+            remaining: n as u64,
         })
-        .clone()
+    }
+
+    // Advances the state by `n` (> 0) IDs and returns the first one. Unless `policy` allows
+    // borrowing (and there is no `node` to carry into), all `n` IDs must fit in the first one's
+    // millisecond.
+    fn reserve_dyn(
+        &self,
+        timestamp: u64,
+        n: u64,
+        rng: &mut dyn RandomSource,
+        policy: ExhaustionPolicy,
+        node: NodeId,
+    ) -> Result<u64, Nano64Error> {
+        if timestamp > MAX_TIMESTAMP {
+            return Err(Nano64Error::TimeStampExceedsBitRange(timestamp));
+        }
+
+        for _ in 0..CAS_ATTEMPTS_BEFORE_LOCK {
+            if let Some(first) = self.try_advance(timestamp, n, rng, policy, node)? {
+                return Ok(first);
+            }
+        }
+
+        let _guard = self
+            .shared
+            .contended
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        loop {
+            if let Some(first) = self.try_advance(timestamp, n, rng, policy, node)? {
+                return Ok(first);
+            }
+        }
+    }
+
+    // One CAS round. `None` means another thread got there first.
+    fn try_advance(
+        &self,
+        timestamp: u64,
+        n: u64,
+        rng: &mut dyn RandomSource,
+        policy: ExhaustionPolicy,
+        node: NodeId,
+    ) -> Result<Option<u64>, Nano64Error> {
+        let last = self.shared.last.load(AtomicOrdering::Acquire);
+        let first = successor(last, timestamp, rng, policy, node)?;
+        let end = first.checked_add(n - 1).ok_or_else(|| {
+            Nano64Error::Error(format!("timestamp overflow reserving {n} monotonic IDs"))
+        })?;
+        let spills = (first & node.counter_mask()) + (n - 1) > node.counter_mask();
+        if spills && (policy != ExhaustionPolicy::BorrowNextMs || node != NodeId::NONE) {
+            return Err(Nano64Error::RandomFieldExhausted {
+                timestamp: first >> TIMESTAMP_SHIFT,
+            });
+        }
+        #[cfg(feature = "tracing")]
+        if spills {
+            tracing::debug!(
+                timestamp = first >> TIMESTAMP_SHIFT,
+                n,
+                "monotonic block spills into the next millisecond"
+            );
+        }
+        Ok(self
+            .shared
+            .last
+            .compare_exchange_weak(last, end, AtomicOrdering::AcqRel, AtomicOrdering::Relaxed)
+            .ok()
+            .map(|_| first))
+    }
+
+    // The most recently issued ID, or `None` if this state hasn't issued one yet.
+    pub fn last(&self) -> Option<Nano64> {
+        match self.shared.last.load(AtomicOrdering::Acquire) {
+            0 => None,
+            value => Some(Nano64::new(value)),
+        }
+    }
+
+    // Snapshot of the sequence position for persisting; `Nano64::NIL` if nothing was issued.
+    pub fn export(&self) -> Nano64 {
+        Nano64::new(self.shared.last.load(AtomicOrdering::Acquire))
+    }
+
+    // Continues the sequence after `last`, e.g. a value saved with `export` before a restart.
+    // Never moves the sequence backwards, so importing an older snapshot is a no-op.
+    pub fn import(&self, last: Nano64) {
+        self.shared
+            .last
+            .fetch_max(last.u64_value(), AtomicOrdering::AcqRel);
+    }
+
+    #[cfg(any(test, feature = "testing"))]
+    pub(crate) fn set_last(&self, timestamp: u64, random: u64) {
+        self.shared.last.store(
+            (timestamp << TIMESTAMP_SHIFT) | random,
+            AtomicOrdering::Release,
+        );
+    }
+}
+
+// IDs reserved by `MonotonicState::generate_block`, yielded in increasing order. Collect it
+// into a `Vec` or iterate lazily; the IDs are already reserved either way.
+#[derive(Clone, Debug)]
+pub struct MonotonicBlock {
+    next: u64,
+    remaining: u64,
+}
+
+impl Iterator for MonotonicBlock {
+    type Item = Nano64;
+
+    fn next(&mut self) -> Option<Nano64> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let id = Nano64::new(self.next);
+        self.remaining -= 1;
+        // Never wraps: the last reserved value was checked to fit when the block was taken.
+        if self.remaining > 0 {
+            self.next += 1;
+        }
+        Some(id)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining as usize, Some(self.remaining as usize))
+    }
+}
+
+impl ExactSizeIterator for MonotonicBlock {}
+
+thread_local! {
+    // Per-thread state behind `Nano64::generate_monotonic_thread_local`.
+    pub(crate) static THREAD_MONOTONIC_REFS: RefCell<MonotonicRefs> =
+        const { RefCell::new(MonotonicRefs { last_timestamp: 0, last_random: 0 }) };
+}
+
+// Process-wide state behind `Nano64::generate_monotonic*`.
+pub(crate) static MONOTONIC_REFS: OnceLock<MonotonicState> = OnceLock::new();
+
+pub(crate) fn get_monotonic_state() -> &'static MonotonicState {
+    MONOTONIC_REFS.get_or_init(MonotonicState::new)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ExhaustionPolicy, MonotonicState, Nano64, Nano64Error, Nano64Generator, NodeId};
+
+    fn fixed_rng(_bits: u32) -> Result<u32, Nano64Error> {
+        Ok(100)
+    }
+
+    #[test]
+    fn test_monotonic_state_is_independent() {
+        let a = MonotonicState::new();
+        let b = MonotonicState::new();
+        assert!(a.last().is_none());
+        let a1 = a.generate(5000, &mut fixed_rng).unwrap();
+        let a2 = a.generate(5000, &mut fixed_rng).unwrap();
+        let b1 = b.generate(5000, &mut fixed_rng).unwrap();
+        assert_eq!(a2.u64_value(), a1.u64_value() + 1);
+        assert_eq!(b1, a1);
+        assert_eq!(a.last(), Some(a2));
+        assert!(a.generate(u64::MAX, &mut fixed_rng).is_err());
+    }
+
+    #[test]
+    fn test_generators_can_share_monotonic_state() {
+        let shared = MonotonicState::new();
+        let a = Nano64Generator::new()
+            .with_rng(fixed_rng)
+            .with_monotonic_state(shared.clone());
+        let b = Nano64Generator::new()
+            .with_rng(fixed_rng)
+            .with_monotonic_state(shared.clone());
+        let a1 = a.generate_monotonic_at(5000).unwrap();
+        let b1 = b.generate_monotonic_at(5000).unwrap();
+        assert!(b1 > a1);
+        assert_eq!(shared.last(), Some(b1));
+    }
+
+    #[test]
+    fn test_monotonic_state_concurrent_strictly_unique() {
+        use std::{collections::HashSet, thread};
+
+        let state = MonotonicState::new();
+        let handles: Vec<_> = (0..16)
+            .map(|_| {
+                let state = state.clone();
+                thread::spawn(move || {
+                    let mut ids = Vec::with_capacity(5_000);
+                    for _ in 0..5_000 {
+                        let id = state.generate(5000, &mut fixed_rng).unwrap();
+                        if let Some(prev) = ids.last() {
+                            assert!(id > *prev);
+                        }
+                        ids.push(id);
+                    }
+                    ids
+                })
+            })
+            .collect();
+        let mut all = HashSet::new();
+        for handle in handles {
+            for id in handle.join().unwrap() {
+                assert!(all.insert(id), "duplicate monotonic ID");
+            }
+        }
+        assert_eq!(all.len(), 16 * 5_000);
+        assert_eq!(state.last(), all.iter().max().copied());
+    }
+
+    #[test]
+    fn test_monotonic_state_overflow() {
+        let state = MonotonicState::new();
+        state.set_last(crate::MAX_TIMESTAMP, crate::RANDOM_MASK);
+        assert!(state.generate(0, &mut fixed_rng).is_err());
+    }
+
+    #[test]
+    fn test_monotonic_block_reserves_consecutive_ids() {
+        let state = MonotonicState::new();
+        let before = state.generate(5000, &mut fixed_rng).unwrap();
+        let block = state.generate_block(5000, 10, &mut fixed_rng).unwrap();
+        assert_eq!(block.len(), 10);
+        let ids: Vec<Nano64> = block.collect();
+        assert_eq!(ids[0].u64_value(), before.u64_value() + 1);
+        assert!(
+            ids.windows(2)
+                .all(|w| w[1].u64_value() == w[0].u64_value() + 1)
+        );
+        assert_eq!(state.last(), ids.last().copied());
+        let after = state.generate(5000, &mut fixed_rng).unwrap();
+        assert!(after > ids[9]);
+
+        assert_eq!(
+            state.generate_block(5000, 0, &mut fixed_rng).unwrap().len(),
+            0
+        );
+        assert_eq!(state.last(), Some(after));
+    }
+
+    #[test]
+    fn test_monotonic_block_spills_into_next_millisecond() {
+        let state = MonotonicState::new();
+        state.set_last(5000, crate::RANDOM_MASK - 1);
+        let ids: Vec<Nano64> = state
+            .generate_block(5000, 3, &mut fixed_rng)
+            .unwrap()
+            .collect();
+        assert_eq!(ids[0].get_timestamp(), 5000);
+        assert_eq!(ids[0].get_random(), crate::RANDOM_MASK as u32);
+        assert_eq!(ids[1].get_timestamp(), 5001);
+        assert_eq!(ids[1].get_random(), 0);
+        assert_eq!(ids[2].get_random(), 1);
+
+        state.set_last(crate::MAX_TIMESTAMP, crate::RANDOM_MASK - 2);
+        assert!(state.generate_block(0, 3, &mut fixed_rng).is_err());
+        assert_eq!(
+            state.generate_block(0, 2, &mut fixed_rng).unwrap().count(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_monotonic_state_exhaustion_policies() {
+        let state = MonotonicState::new();
+        state.set_last(5000, crate::RANDOM_MASK);
+        for policy in [ExhaustionPolicy::Error, ExhaustionPolicy::SpinWaitForNextMs] {
+            assert!(matches!(
+                state.generate_dyn(5000, &mut fixed_rng, policy, NodeId::NONE),
+                Err(Nano64Error::RandomFieldExhausted { timestamp: 5000 })
+            ));
+        }
+        // A new millisecond is always fine.
+        assert_eq!(
+            state
+                .generate_dyn(5001, &mut fixed_rng, ExhaustionPolicy::Error, NodeId::NONE)
+                .unwrap()
+                .get_timestamp(),
+            5001
+        );
+
+        state.set_last(5000, crate::RANDOM_MASK - 1);
+        assert!(
+            state
+                .generate_block_dyn(
+                    5000,
+                    2,
+                    &mut fixed_rng,
+                    ExhaustionPolicy::Error,
+                    NodeId::NONE
+                )
+                .is_err()
+        );
+        assert_eq!(
+            state
+                .generate_block_dyn(
+                    5000,
+                    1,
+                    &mut fixed_rng,
+                    ExhaustionPolicy::Error,
+                    NodeId::NONE
+                )
+                .unwrap()
+                .count(),
+            1
+        );
+        let borrowed = state
+            .generate_dyn(
+                5000,
+                &mut fixed_rng,
+                ExhaustionPolicy::BorrowNextMs,
+                NodeId::NONE,
+            )
+            .unwrap();
+        assert_eq!(borrowed.get_timestamp(), 5001);
+    }
+
+    #[test]
+    fn test_monotonic_state_keeps_node_bits() {
+        let node = NodeId::new(3, 4).unwrap();
+        let state = MonotonicState::new();
+        // Last issued without a node, so its node bits are zero.
+        state.set_last(5000, 7);
+        let id = state
+            .generate_dyn(5000, &mut fixed_rng, ExhaustionPolicy::Error, node)
+            .unwrap();
+        assert_eq!(id.get_timestamp(), 5000);
+        assert_eq!(id.get_random(), (3 << 16) | 8);
+        let next = state
+            .generate_dyn(5000, &mut fixed_rng, ExhaustionPolicy::Error, node)
+            .unwrap();
+        assert_eq!(next.get_random(), (3 << 16) | 9);
+    }
+
+    #[test]
+    fn test_monotonic_state_export_import() {
+        let state = MonotonicState::new();
+        assert!(state.export().is_nil());
+        let last = state.generate(5000, &mut fixed_rng).unwrap();
+        assert_eq!(state.export(), last);
+
+        // A restarted process whose clock is behind continues after the imported ID.
+        let restarted = MonotonicState::new();
+        restarted.import(state.export());
+        assert_eq!(
+            restarted.generate(4000, &mut fixed_rng).unwrap(),
+            Nano64::new(last.u64_value() + 1)
+        );
+        restarted.import(Nano64::new(1));
+        assert!(restarted.export() > last);
+    }
 }
@@ -0,0 +1,268 @@
+use crate::{
+    Hex, MAX_TIMESTAMP, Nano64, Nano64Error, RandomNumberGeneratorImpl, RandomSource,
+    TIMESTAMP_BITS, default_rng, time_now_since_epoch_ms,
+};
+use std::{
+    fmt, str,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+// NANO128_RANDOM_BITS is the number of random bits in a Nano128 (0..2^84-1).
+pub const NANO128_RANDOM_BITS: u32 = 84;
+// NANO128_HEX_LENGTH is the length of the canonical `XXXXXXXXXXX-XXXXXXXXXXXXXXXXXXXXX` form.
+pub const NANO128_HEX_LENGTH: usize = 33;
+const NANO128_RANDOM_MASK: u128 = (1 << NANO128_RANDOM_BITS) - 1;
+
+// A 128-bit sibling of `Nano64`: the same 44-bit millisecond timestamp followed by 84 random
+// bits, for write rates where 20 random bits per millisecond collide too often. Sorts by
+// timestamp like `Nano64`, and `Nano128::from(Nano64)` widens without losing anything.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct Nano128 {
+    pub(crate) value: u128,
+}
+
+// Widening keeps the timestamp and puts the 20 random bits at the top of the random field, so
+// widened IDs keep their relative order.
+impl From<Nano64> for Nano128 {
+    fn from(id: Nano64) -> Self {
+        Self {
+            value: (id.value as u128) << 64,
+        }
+    }
+}
+
+// Narrowing only succeeds for IDs that were widened from a `Nano64`.
+impl TryFrom<Nano128> for Nano64 {
+    type Error = Nano64Error;
+
+    fn try_from(id: Nano128) -> Result<Self, Self::Error> {
+        if id.value as u64 != 0 {
+            return Err(Nano64Error::Error(
+                "Nano128 has random bits that don't fit in a Nano64".into(),
+            ));
+        }
+        Ok(Nano64::new((id.value >> 64) as u64))
+    }
+}
+
+impl From<Nano128> for u128 {
+    fn from(id: Nano128) -> Self {
+        id.value
+    }
+}
+
+impl From<u128> for Nano128 {
+    fn from(value: u128) -> Self {
+        Self { value }
+    }
+}
+
+// From big-endian bytes
+impl From<[u8; 16]> for Nano128 {
+    fn from(bytes: [u8; 16]) -> Self {
+        Self {
+            value: u128::from_be_bytes(bytes),
+        }
+    }
+}
+
+// From a big-endian byte slice, which must be exactly 16 bytes long
+impl TryFrom<&[u8]> for Nano128 {
+    type Error = Nano64Error;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        let bytes: [u8; 16] = bytes
+            .try_into()
+            .map_err(|_| Nano64Error::InvalidByteLength {
+                expected: 16,
+                got: bytes.len(),
+            })?;
+        Ok(Self::from(bytes))
+    }
+}
+
+// From hex string, with or without the dash and an `0x` prefix
+impl str::FromStr for Nano128 {
+    type Err = Nano64Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let clean = value.replace("-", "");
+        let clean = clean
+            .strip_prefix("0x")
+            .or_else(|| clean.strip_prefix("0X"))
+            .unwrap_or(&clean);
+        if clean.len() != 32 {
+            return Err(Nano64Error::Error(format!(
+                "hex must be 32 chars after removing dash, got {}",
+                clean.len()
+            )));
+        }
+        let bytes: [u8; 16] = Hex::to_bytes(clean)?
+            .try_into()
+            .map_err(|_| Nano64Error::Error("hex must decode to exactly 16 bytes".into()))?;
+        Ok(Self::from(bytes))
+    }
+}
+
+// `{}` prints the canonical hex ID (same as `to_hex`). `{:#}` prints the verbose
+// `Nano128{value=..., timestamp=..., random=...}` form.
+impl fmt::Display for Nano128 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            return write!(
+                f,
+                "Nano128{{value={}, timestamp={}, random={}}}",
+                self.value,
+                self.get_timestamp(),
+                self.get_random()
+            );
+        }
+        f.pad(&self.to_hex())
+    }
+}
+
+impl Nano128 {
+    pub const MIN: Nano128 = Nano128 { value: 0 };
+    pub const MAX: Nano128 = Nano128 { value: u128::MAX };
+    pub const NIL: Nano128 = Nano128 { value: 0 };
+
+    pub const fn new(value: u128) -> Self {
+        Self { value }
+    }
+
+    pub fn from_parts(timestamp_ms: u64, random: u128) -> Result<Self, Nano64Error> {
+        if timestamp_ms > MAX_TIMESTAMP {
+            return Err(Nano64Error::TimeStampExceedsBitRange(timestamp_ms));
+        }
+        if random > NANO128_RANDOM_MASK {
+            return Err(Nano64Error::Error(format!(
+                "random must fit in {NANO128_RANDOM_BITS} bits, got {random}"
+            )));
+        }
+        Ok(Self {
+            value: ((timestamp_ms as u128) << NANO128_RANDOM_BITS) | random,
+        })
+    }
+
+    pub fn generate_now(rng: Option<RandomNumberGeneratorImpl>) -> Result<Self, Nano64Error> {
+        Self::generate(time_now_since_epoch_ms(), rng)
+    }
+
+    pub fn generate(
+        timestamp: u64,
+        rng: Option<RandomNumberGeneratorImpl>,
+    ) -> Result<Self, Nano64Error> {
+        let mut rng = if let Some(_rng) = rng {
+            _rng
+        } else {
+            default_rng
+        };
+        Self::generate_with_source(timestamp, &mut rng)
+    }
+
+    // Draws the 84 random bits from any `RandomSource`, in three calls (32, 32 and 20 bits).
+    pub fn generate_with_source<R: RandomSource + ?Sized>(
+        timestamp: u64,
+        rng: &mut R,
+    ) -> Result<Self, Nano64Error> {
+        if timestamp > MAX_TIMESTAMP {
+            return Err(Nano64Error::TimeStampExceedsBitRange(timestamp));
+        }
+        let mut random = 0u128;
+        for bits in [32, 32, NANO128_RANDOM_BITS - 64] {
+            let part = rng.random_bits(bits)? as u128 & ((1 << bits) - 1);
+            random = (random << bits) | part;
+        }
+        Self::from_parts(timestamp, random)
+    }
+
+    pub fn get_timestamp(&self) -> u64 {
+        (self.value >> NANO128_RANDOM_BITS) as u64 & MAX_TIMESTAMP
+    }
+
+    pub fn get_random(&self) -> u128 {
+        self.value & NANO128_RANDOM_MASK
+    }
+
+    pub fn u128_value(&self) -> u128 {
+        self.value
+    }
+
+    pub fn to_date(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_millis(self.get_timestamp())
+    }
+
+    // Big-endian bytes
+    pub fn to_bytes(&self) -> [u8; 16] {
+        self.value.to_be_bytes()
+    }
+
+    // Canonical `XXXXXXXXXXX-XXXXXXXXXXXXXXXXXXXXX` form: timestamp, dash, random field.
+    pub fn to_hex(&self) -> String {
+        const TIMESTAMP_HEX: usize = TIMESTAMP_BITS as usize / 4;
+        let hex = format!("{:032X}", self.value);
+        format!("{}-{}", &hex[..TIMESTAMP_HEX], &hex[TIMESTAMP_HEX..])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{NANO128_HEX_LENGTH, Nano64, Nano128, testing::MockRng};
+
+    #[test]
+    fn test_nano128_layout() {
+        let id = Nano128::from_parts(1_700_000_000_000, 0x000A_BCDE_1234_5678_9ABC_DEF0).unwrap();
+        assert_eq!(id.get_timestamp(), 1_700_000_000_000);
+        assert_eq!(id.get_random(), 0x000A_BCDE_1234_5678_9ABC_DEF0);
+        assert!(Nano128::from_parts(1 << 44, 0).is_err());
+        assert!(Nano128::from_parts(0, 1 << 84).is_err());
+        assert_eq!(Nano128::MAX.get_timestamp(), Nano64::MAX.get_timestamp());
+    }
+
+    #[test]
+    fn test_nano128_generate_with_source() {
+        let rng = MockRng::new([0xFFFF_FFFF, 0x0000_0001, 0xFFFF_FFFF]);
+        let id = Nano128::generate_with_source(1000, &mut rng.clone()).unwrap();
+        assert_eq!(id.get_timestamp(), 1000);
+        assert_eq!(id.get_random(), (0xFFFF_FFFF << 52) | (1 << 20) | 0xFFFFF);
+        assert_eq!(rng.calls(), 3);
+        assert!(Nano128::generate_with_source(1000, &mut MockRng::failing()).is_err());
+
+        let a = Nano128::generate_now(None).unwrap();
+        let b = Nano128::generate_now(None).unwrap();
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn test_nano128_hex_and_bytes() {
+        let id = Nano128::new(0x0123456789ABCDEF_FEDCBA9876543210);
+        let hex = id.to_hex();
+        assert_eq!(hex, "0123456789A-BCDEFFEDCBA9876543210");
+        assert_eq!(hex.len(), NANO128_HEX_LENGTH);
+        assert_eq!(hex.parse::<Nano128>().unwrap(), id);
+        assert_eq!(
+            "0x0123456789ABCDEFFEDCBA9876543210"
+                .parse::<Nano128>()
+                .unwrap(),
+            id
+        );
+        assert!("0123456789A-BCDEF".parse::<Nano128>().is_err());
+        assert_eq!(format!("{id}"), hex);
+        assert_eq!(Nano128::from(id.to_bytes()), id);
+        assert_eq!(Nano128::try_from(&id.to_bytes()[..]).unwrap(), id);
+        assert!(Nano128::try_from(&[0u8; 8][..]).is_err());
+    }
+
+    #[test]
+    fn test_nano128_widening_is_lossless() {
+        let small = Nano64::from_parts(1_700_000_000_000, 0x12345).unwrap();
+        let larger = Nano64::from_parts(1_700_000_000_000, 0x12346).unwrap();
+        let wide = Nano128::from(small);
+        assert_eq!(wide.get_timestamp(), small.get_timestamp());
+        assert_eq!(wide.get_random() >> 64, 0x12345);
+        assert_eq!(Nano64::try_from(wide).unwrap(), small);
+        assert!(Nano128::from(small) < Nano128::from(larger));
+        assert!(Nano64::try_from(Nano128::new(1)).is_err());
+    }
+}
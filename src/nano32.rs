@@ -0,0 +1,154 @@
+use crate::{
+    Hex, Nano64Error, RandomNumberGeneratorImpl, RandomSource, default_rng, time_now_since_epoch_ms,
+};
+use std::{fmt, str, time::Duration};
+
+// NANO32_TIMESTAMP_BITS is the width of the rolling millisecond timestamp (~70 minute window).
+pub const NANO32_TIMESTAMP_BITS: u32 = 22;
+// NANO32_RANDOM_BITS is the number of random bits (0..1023).
+pub const NANO32_RANDOM_BITS: u32 = 10;
+// NANO32_WINDOW_MS is how long the timestamp takes to wrap around.
+pub const NANO32_WINDOW_MS: u64 = 1 << NANO32_TIMESTAMP_BITS;
+const NANO32_RANDOM_MASK: u32 = (1 << NANO32_RANDOM_BITS) - 1;
+
+// A 4-byte ID for short-lived, in-memory things like request tags: the low 22 bits of the
+// millisecond clock followed by 10 random bits. The timestamp wraps every ~70 minutes, so
+// only compare ages of IDs younger than that, and expect collisions: 1,024 random values per
+// millisecond is plenty for tagging log lines, not for primary keys. Not `Ord`, since the
+// order breaks at every wrap.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+pub struct Nano32 {
+    pub(crate) value: u32,
+}
+
+impl From<Nano32> for u32 {
+    fn from(id: Nano32) -> Self {
+        id.value
+    }
+}
+
+impl From<u32> for Nano32 {
+    fn from(value: u32) -> Self {
+        Self { value }
+    }
+}
+
+// From big-endian bytes
+impl From<[u8; 4]> for Nano32 {
+    fn from(bytes: [u8; 4]) -> Self {
+        Self {
+            value: u32::from_be_bytes(bytes),
+        }
+    }
+}
+
+// From an 8-char hex string
+impl str::FromStr for Nano32 {
+    type Err = Nano64Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let bytes: [u8; 4] = Hex::to_bytes(value)?
+            .try_into()
+            .map_err(|_| Nano64Error::Error("hex must decode to exactly 4 bytes".into()))?;
+        Ok(Self::from(bytes))
+    }
+}
+
+impl fmt::Display for Nano32 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(&self.to_hex())
+    }
+}
+
+impl Nano32 {
+    pub const fn new(value: u32) -> Self {
+        Self { value }
+    }
+
+    // Uses the installed default clock and RNG, like `Nano64::generate_default`.
+    pub fn generate_now(rng: Option<RandomNumberGeneratorImpl>) -> Result<Self, Nano64Error> {
+        let mut rng = if let Some(_rng) = rng {
+            _rng
+        } else {
+            default_rng
+        };
+        Self::generate_with_source(time_now_since_epoch_ms(), &mut rng)
+    }
+
+    // Only the low 22 bits of `timestamp_ms` are kept.
+    pub fn generate_with_source<R: RandomSource + ?Sized>(
+        timestamp_ms: u64,
+        rng: &mut R,
+    ) -> Result<Self, Nano64Error> {
+        let random = rng.random_bits(NANO32_RANDOM_BITS)? & NANO32_RANDOM_MASK;
+        let timestamp = (timestamp_ms % NANO32_WINDOW_MS) as u32;
+        Ok(Self {
+            value: (timestamp << NANO32_RANDOM_BITS) | random,
+        })
+    }
+
+    // The rolling timestamp: milliseconds since the UNIX epoch modulo `NANO32_WINDOW_MS`.
+    pub fn get_timestamp(&self) -> u32 {
+        self.value >> NANO32_RANDOM_BITS
+    }
+
+    pub fn get_random(&self) -> u32 {
+        self.value & NANO32_RANDOM_MASK
+    }
+
+    pub fn u32_value(&self) -> u32 {
+        self.value
+    }
+
+    // Time between the ID's timestamp and `now_ms`, assuming the ID is less than one window
+    // old.
+    pub fn age_at(&self, now_ms: u64) -> Duration {
+        let now = now_ms % NANO32_WINDOW_MS;
+        let age = (now + NANO32_WINDOW_MS - self.get_timestamp() as u64) % NANO32_WINDOW_MS;
+        Duration::from_millis(age)
+    }
+
+    pub fn age(&self) -> Duration {
+        self.age_at(time_now_since_epoch_ms())
+    }
+
+    // Big-endian bytes
+    pub fn to_bytes(&self) -> [u8; 4] {
+        self.value.to_be_bytes()
+    }
+
+    pub fn to_hex(&self) -> String {
+        Hex::from_bytes(&self.to_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{NANO32_WINDOW_MS, Nano32, testing::MockRng};
+    use std::time::Duration;
+
+    #[test]
+    fn test_nano32_layout_and_hex() {
+        let mut rng = MockRng::constant(0x3FF);
+        let id = Nano32::generate_with_source(NANO32_WINDOW_MS + 5, &mut rng).unwrap();
+        assert_eq!(id.get_timestamp(), 5);
+        assert_eq!(id.get_random(), 0x3FF);
+        assert_eq!(id.u32_value(), (5 << 10) | 0x3FF);
+        assert_eq!(id.to_hex(), "000017FF");
+        assert_eq!("000017FF".parse::<Nano32>().unwrap(), id);
+        assert!("17FF".parse::<Nano32>().is_err());
+        assert_eq!(Nano32::from(id.to_bytes()), id);
+        assert_eq!(format!("{id}"), "000017FF");
+        assert!(Nano32::generate_now(None).is_ok());
+    }
+
+    #[test]
+    fn test_nano32_age_wraps() {
+        let mut rng = MockRng::constant(0);
+        let id = Nano32::generate_with_source(NANO32_WINDOW_MS - 10, &mut rng).unwrap();
+        assert_eq!(id.age_at(NANO32_WINDOW_MS - 10), Duration::ZERO);
+        // 20ms later the clock has wrapped past the end of the window.
+        assert_eq!(id.age_at(NANO32_WINDOW_MS + 10), Duration::from_millis(20));
+    }
+}
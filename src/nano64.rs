@@ -1,18 +1,34 @@
 use crate::{
-    ClockImpl, Hex, MAX_TIMESTAMP, Nano64EncryptionFactory, Nano64Error, RANDOM_BITS, RANDOM_MASK,
-    RandomNumberGeneratorImpl, TIMESTAMP_MASK, TIMESTAMP_SHIFT, compare, default_rng,
-    monotonic_refs::*, time_now_since_epoch_ms,
+    Base32Hex, Base58, Base58Fixed, Base62, Base64Url, CANONICAL_HEX_LENGTH, CheckedHex,
+    Explanation, Hex, HexFormat, Layout, MAX_TIMESTAMP, Nano64Error, RANDOM_BITS, RANDOM_MASK,
+    Radix, RandomNumberGeneratorImpl, RandomSource, SmearOptions, TIMESTAMP_MASK, TIMESTAMP_SHIFT,
+    Ulid, ValidationPolicy, Varint, Words, compare, default_rng, defaults::installed_rng,
+    encoding::HEX_UPPER, fuzzy, monotonic_refs::*, pooled_rng, smear::get_smear_refs,
+    time_now_since_epoch_ms,
 };
 use std::{
-    fmt, str,
+    cmp::Ordering,
+    fmt,
+    mem::MaybeUninit,
+    str,
     time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
-#[derive(Clone, Debug)]
+// `Nano64` is guaranteed to have the same layout as a `u64`, and compares, orders and hashes by it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
 pub struct Nano64 {
     pub(crate) value: u64,
 }
 
+// SAFETY: `Nano64` is `repr(transparent)` over a `u64`, so every bit pattern is valid and the
+// all-zero pattern is `Nano64::new(0)`.
+#[cfg(feature = "bytemuck")]
+unsafe impl bytemuck::Zeroable for Nano64 {}
+
+#[cfg(feature = "bytemuck")]
+unsafe impl bytemuck::Pod for Nano64 {}
+
 impl Default for Nano64 {
     fn default() -> Self {
         Self {
@@ -47,6 +63,18 @@ impl From<[u8; 8]> for Nano64 {
     }
 }
 
+// IDs filled per `rand::fill` call when batch-generating with the default RNG.
+const BATCH_RNG_CHUNK: usize = 256;
+
+// From a big-endian byte slice, which must be exactly 8 bytes long
+impl TryFrom<&[u8]> for Nano64 {
+    type Error = Nano64Error;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Self::from_be_slice(bytes)
+    }
+}
+
 // From hex string
 impl str::FromStr for Nano64 {
     type Err = Nano64Error;
@@ -102,27 +130,66 @@ impl TryFrom<String> for Nano64 {
     }
 }
 
+// `{}` prints the canonical hex ID (same as `to_hex`). `{:#}` prints the verbose
+// `Nano64{value=..., timestamp=..., random=...}` form.
 impl fmt::Display for Nano64 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "Nano64{{value={}, timestamp={}, random={}}}",
-            self.value,
-            self.get_timestamp(),
-            self.get_random()
-        )
+        if f.alternate() {
+            return write!(
+                f,
+                "Nano64{{value={}, timestamp={}, random={}}}",
+                self.value,
+                self.get_timestamp(),
+                self.get_random()
+            );
+        }
+        let mut buf = [0u8; CANONICAL_HEX_LENGTH];
+        f.pad(self.to_hex_buf(&mut buf))
     }
 }
 
 impl Nano64 {
-    pub fn new(value: u64) -> Self {
+    // Smallest possible ID (timestamp 0, random 0). Useful as an inclusive lower bound in range scans.
+    pub const MIN: Nano64 = Nano64 { value: 0 };
+    // Largest possible ID (max timestamp, max random). Useful as an inclusive upper bound.
+    pub const MAX: Nano64 = Nano64 { value: u64::MAX };
+    // Sentinel for "no ID", e.g. placeholder rows. Same bits as `MIN`.
+    pub const NIL: Nano64 = Nano64 { value: 0 };
+    // Bit layout of the ID fields.
+    pub const LAYOUT: Layout = Layout::NANO64;
+
+    pub const fn new(value: u64) -> Self {
         Self { value }
     }
 
+    pub const fn is_nil(&self) -> bool {
+        self.value == Self::NIL.value
+    }
+
+    // Builds an ID from a millisecond timestamp and a random field, rejecting values that don't
+    // fit in 44 and 20 bits respectively.
+    pub fn from_parts(timestamp_ms: u64, random: u32) -> Result<Self, Nano64Error> {
+        if timestamp_ms > MAX_TIMESTAMP {
+            return Err(Nano64Error::TimeStampExceedsBitRange(timestamp_ms));
+        }
+        if random as u64 > RANDOM_MASK {
+            return Err(Nano64Error::RandomExceedsBitRange(random));
+        }
+        Ok(Self {
+            value: (timestamp_ms << TIMESTAMP_SHIFT) | random as u64,
+        })
+    }
+
     pub fn generate_default() -> Result<Self, Nano64Error> {
         Self::generate_now(Some(default_rng))
     }
 
+    // Current-time ID from `pooled_rng`: a ChaCha CSPRNG per thread, seeded from the OS, so
+    // concurrent callers never contend on a shared entropy source.
+    pub fn generate_fast() -> Result<Self, Nano64Error> {
+        Self::generate_now(Some(pooled_rng))
+    }
+
     pub fn generate_now(rng: Option<RandomNumberGeneratorImpl>) -> Result<Self, Nano64Error> {
         Self::generate(time_now_since_epoch_ms(), rng)
     }
@@ -133,16 +200,120 @@ impl Nano64 {
         Self::generate_monotonic(time_now_since_epoch_ms(), rng)
     }
 
+    // Monotonic generation with state kept per thread: IDs are strictly increasing within the
+    // calling thread, with no lock shared between threads. IDs from different threads are not
+    // ordered relative to each other and, unlike `generate_monotonic_now`, may collide as
+    // often as plain `generate_now` IDs.
+    pub fn generate_monotonic_thread_local(
+        rng: Option<RandomNumberGeneratorImpl>,
+    ) -> Result<Self, Nano64Error> {
+        let timestamp = time_now_since_epoch_ms();
+        if timestamp > MAX_TIMESTAMP {
+            return Err(Nano64Error::TimeStampExceedsBitRange(timestamp));
+        }
+
+        let mut rng = if let Some(_rng) = rng {
+            _rng
+        } else {
+            default_rng
+        };
+
+        THREAD_MONOTONIC_REFS.with_borrow_mut(|refs| refs.next(timestamp, &mut rng))
+    }
+
+    // Reserves `n` strictly increasing IDs from the process-wide monotonic sequence in one
+    // step, spilling into later milliseconds if one millisecond's random space runs out.
+    pub fn generate_monotonic_block(
+        n: usize,
+        rng: Option<RandomNumberGeneratorImpl>,
+    ) -> Result<MonotonicBlock, Nano64Error> {
+        let mut rng = if let Some(_rng) = rng {
+            _rng
+        } else {
+            default_rng
+        };
+
+        get_monotonic_state().generate_block(time_now_since_epoch_ms(), n, &mut rng)
+    }
+
     pub fn generate_monotonic_default() -> Result<Self, Nano64Error> {
         Self::generate_monotonic_now(Some(default_rng))
     }
 
+    // `n` IDs for the current millisecond. See `generate_into`.
+    pub fn generate_batch(
+        n: usize,
+        rng: Option<RandomNumberGeneratorImpl>,
+    ) -> Result<Vec<Nano64>, Nano64Error> {
+        let mut ids = vec![Self::NIL; n];
+        Self::generate_into(&mut ids, rng)?;
+        Ok(ids)
+    }
+
+    // Fills `out` with IDs for the current millisecond without allocating.
+    // The clock is read once for the whole buffer, and with the default RNG (`None`) random
+    // bytes are drawn in chunks rather than once per ID.
+    pub fn generate_into(
+        out: &mut [Nano64],
+        rng: Option<RandomNumberGeneratorImpl>,
+    ) -> Result<(), Nano64Error> {
+        let timestamp = time_now_since_epoch_ms();
+        if timestamp > MAX_TIMESTAMP {
+            return Err(Nano64Error::TimeStampExceedsBitRange(timestamp));
+        }
+
+        let ms = (timestamp & TIMESTAMP_MASK) << TIMESTAMP_SHIFT;
+        match rng {
+            Some(rng) => {
+                for slot in out.iter_mut() {
+                    let random = rng(RANDOM_BITS as u32)? as u64 & RANDOM_MASK;
+                    *slot = Self::new(ms | random);
+                }
+            }
+            // Custom default RNGs are called per ID like any other.
+            None if installed_rng().is_some() => {
+                for slot in out.iter_mut() {
+                    let random = default_rng(RANDOM_BITS as u32)? as u64 & RANDOM_MASK;
+                    *slot = Self::new(ms | random);
+                }
+            }
+            None => {
+                let mut buf = [0u8; 4 * BATCH_RNG_CHUNK];
+                for chunk in out.chunks_mut(BATCH_RNG_CHUNK) {
+                    let bytes = &mut buf[..chunk.len() * 4];
+                    rand::fill(bytes);
+                    for (slot, word) in chunk.iter_mut().zip(bytes.chunks_exact(4)) {
+                        let random = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+                        *slot = Self::new(ms | (random as u64 & RANDOM_MASK));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Like `generate_into`, but for uninitialized buffers (e.g. reused arenas).
+    // Returns the now-initialized slice. If an error is returned, `out` may be partially written.
+    pub fn generate_into_uninit(
+        out: &mut [MaybeUninit<Nano64>],
+        rng: Option<RandomNumberGeneratorImpl>,
+    ) -> Result<&mut [Nano64], Nano64Error> {
+        let timestamp = time_now_since_epoch_ms();
+        for slot in out.iter_mut() {
+            slot.write(Self::generate(timestamp, rng)?);
+        }
+        // SAFETY: every element was written above, and `MaybeUninit<Nano64>` has the same
+        // layout as `Nano64`.
+        Ok(unsafe { &mut *(out as *mut [MaybeUninit<Nano64>] as *mut [Nano64]) })
+    }
+
+    #[cfg(feature = "encryption")]
     pub fn encrypted_factory(
         key: &[u8],
-        clock: Option<ClockImpl>,
+        clock: Option<crate::ClockImpl>,
         rng: Option<RandomNumberGeneratorImpl>,
-    ) -> Result<Nano64EncryptionFactory, Nano64Error> {
-        return Nano64EncryptionFactory::new(key, clock, rng);
+    ) -> Result<crate::Nano64EncryptionFactory, Nano64Error> {
+        crate::Nano64EncryptionFactory::new(key, clock, rng)
     }
 
     pub fn get_timestamp(&self) -> u64 {
@@ -153,20 +324,293 @@ impl Nano64 {
         (self.value & RANDOM_MASK) as u32
     }
 
+    // Reads an ID from exactly 8 big-endian bytes, e.g. a slice read off the wire.
+    pub fn from_be_slice(bytes: &[u8]) -> Result<Self, Nano64Error> {
+        Ok(Self {
+            value: u64::from_be_bytes(Self::exact_8(bytes)?),
+        })
+    }
+
+    // Reads an ID from exactly 8 little-endian bytes.
+    pub fn from_le_slice(bytes: &[u8]) -> Result<Self, Nano64Error> {
+        Ok(Self::from_le_bytes(Self::exact_8(bytes)?))
+    }
+
+    fn exact_8(bytes: &[u8]) -> Result<[u8; 8], Nano64Error> {
+        bytes
+            .try_into()
+            .map_err(|_| Nano64Error::InvalidByteLength {
+                expected: 8,
+                got: bytes.len(),
+            })
+    }
+
     pub fn to_bytes(&self) -> [u8; 8] {
         self.value.to_be_bytes()
     }
 
+    // Little-endian counterpart of `to_bytes`, for fixed64 fields in protobuf and similar formats.
+    pub fn to_le_bytes(&self) -> [u8; 8] {
+        self.value.to_le_bytes()
+    }
+
+    pub fn from_le_bytes(bytes: [u8; 8]) -> Self {
+        Self {
+            value: u64::from_le_bytes(bytes),
+        }
+    }
+
     pub fn to_hex(&self) -> String {
-        let full = format!("{:016X}", self.value);
+        let mut buf = [0u8; CANONICAL_HEX_LENGTH];
+        self.to_hex_buf(&mut buf).to_string()
+    }
+
+    // Hex with caller-chosen case, separator and prefix. Forms using a `-` separator and/or a
+    // `0x` prefix parse back with `from_str`.
+    pub fn to_hex_with(&self, format: &HexFormat) -> String {
+        format.format(self.value)
+    }
+
+    // Encodes the canonical `XXXXXXXXXXX-XXXXX` form into `buf` without allocating.
+    pub fn to_hex_buf<'a>(&self, buf: &'a mut [u8; CANONICAL_HEX_LENGTH]) -> &'a str {
         const SPLIT: usize = 11;
-        format!("{}-{}", &full[..SPLIT], &full[SPLIT..])
+        let mut pos = 0;
+        for i in 0..16 {
+            if i == SPLIT {
+                buf[pos] = b'-';
+                pos += 1;
+            }
+            let nibble = (self.value >> (60 - i * 4)) & 0xF;
+            buf[pos] = HEX_UPPER[nibble as usize];
+            pos += 1;
+        }
+        // Only ever ASCII hex digits and a dash.
+        str::from_utf8(buf).expect("canonical hex is always ASCII")
+    }
+
+    // Writes the canonical hex form to any `fmt::Write` sink without allocating.
+    pub fn write_hex<W: fmt::Write>(&self, w: &mut W) -> fmt::Result {
+        let mut buf = [0u8; CANONICAL_HEX_LENGTH];
+        w.write_str(self.to_hex_buf(&mut buf))
+    }
+
+    // Decimal u64 form, as found in CSV exports and older APIs.
+    pub fn to_decimal_string(&self) -> String {
+        self.value.to_string()
+    }
+
+    // Parses a decimal u64 string. Signs, whitespace and separators are rejected.
+    pub fn from_decimal_str(value: &str) -> Result<Self, Nano64Error> {
+        if value.is_empty() || !value.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(Nano64Error::Error(format!(
+                "'{value}' is not a decimal u64"
+            )));
+        }
+        let value = value
+            .parse::<u64>()
+            .map_err(|e| Nano64Error::Error(format!("invalid decimal ID: {e}")))?;
+        Ok(Self { value })
+    }
+
+    // Accepts either hex (as `from_str`) or decimal. An all-digit string of exactly 16 chars is
+    // ambiguous and is always read as hex. `from_str` stays hex-only for callers that need a
+    // strict format.
+    pub fn parse_any(value: &str) -> Result<Self, Nano64Error> {
+        let looks_decimal = !value.is_empty() && value.bytes().all(|b| b.is_ascii_digit());
+        if looks_decimal && value.len() != 16 {
+            Self::from_decimal_str(value)
+        } else {
+            value.parse::<Nano64>()
+        }
+    }
+
+    // Parses like `from_str`, but on failure tries common hand-entry corrections (look-alike
+    // characters, stray separators, doubled keystrokes). If any correction yields a valid ID the
+    // error is `Nano64Error::InvalidWithSuggestions`; the input is never silently accepted.
+    pub fn parse_with_suggestions(value: &str) -> Result<Self, Nano64Error> {
+        match value.parse::<Nano64>() {
+            Ok(id) => Ok(id),
+            Err(e) => {
+                let suggestions = fuzzy::suggest(value);
+                if suggestions.is_empty() {
+                    return Err(e);
+                }
+                Err(Nano64Error::InvalidWithSuggestions {
+                    reason: e.to_string(),
+                    suggestions,
+                })
+            }
+        }
+    }
+
+    // 11-character alphanumeric token. Fixed width, so tokens sort like the IDs.
+    pub fn to_base62(&self) -> String {
+        Base62::encode(self.value)
+    }
+
+    pub fn from_base62(s: &str) -> Result<Self, Nano64Error> {
+        Ok(Self {
+            value: Base62::decode(s)?,
+        })
+    }
+
+    // Standard Base58 (Bitcoin alphabet) of the ID's 8 big-endian bytes, as other Base58
+    // tooling expects. Up to 11 characters; does not sort like the ID.
+    pub fn to_base58(&self) -> String {
+        Base58::encode(self.value)
+    }
+
+    pub fn from_base58(s: &str) -> Result<Self, Nano64Error> {
+        Ok(Self {
+            value: Base58::decode(s)?,
+        })
+    }
+
+    // 11-character Base58-alphabet token. Fixed width, so tokens sort like the IDs.
+    pub fn to_base58_fixed(&self) -> String {
+        Base58Fixed::encode(self.value)
+    }
+
+    pub fn from_base58_fixed(s: &str) -> Result<Self, Nano64Error> {
+        Ok(Self {
+            value: Base58Fixed::decode(s)?,
+        })
+    }
+
+    // 11-character unpadded base64url; the shortest URL-safe form. Does not sort like the ID.
+    pub fn to_slug(&self) -> String {
+        Base64Url::encode(&self.to_bytes())
+    }
+
+    pub fn from_slug(s: &str) -> Result<Self, Nano64Error> {
+        Self::from_be_slice(&Base64Url::decode(s)?)
+    }
+
+    // The raw value in any radix from 2 to 36 (lowercase, unpadded).
+    pub fn to_string_radix(&self, radix: u32) -> Result<String, Nano64Error> {
+        Radix::encode(self.value, radix)
+    }
+
+    pub fn from_string_radix(s: &str, radix: u32) -> Result<Self, Nano64Error> {
+        Ok(Self {
+            value: Radix::decode(s, radix)?,
+        })
+    }
+
+    // Canonical hex plus a mod-37 check symbol, for IDs that get copied between tools by hand.
+    pub fn to_checked_hex(&self) -> String {
+        CheckedHex::encode(self.value)
+    }
+
+    pub fn from_checked_hex(s: &str) -> Result<Self, Nano64Error> {
+        Ok(Self {
+            value: CheckedHex::decode(s)?,
+        })
+    }
+
+    // 13-character base32hex whose byte-wise string order always equals the ID order.
+    pub fn to_sortable_string(&self) -> String {
+        Base32Hex::encode(self.value)
+    }
+
+    pub fn from_sortable_string(s: &str) -> Result<Self, Nano64Error> {
+        Ok(Self {
+            value: Base32Hex::decode(s)?,
+        })
+    }
+
+    // 26-character ULID string with the same timestamp; the random field fills the top 20 of
+    // the ULID's 80 random bits.
+    pub fn to_ulid(&self) -> String {
+        Ulid::encode(self.value)
+    }
+
+    pub fn from_ulid(s: &str) -> Result<Self, Nano64Error> {
+        Ok(Self {
+            value: Ulid::decode(s)?,
+        })
+    }
+
+    // Unsigned LEB128 (protobuf varint) bytes.
+    pub fn to_varint(&self) -> Vec<u8> {
+        Varint::encode(self.value)
+    }
+
+    // Reads a varint from the start of `bytes`; also returns how many bytes it used.
+    pub fn from_varint(bytes: &[u8]) -> Result<(Self, usize), Nano64Error> {
+        let (value, used) = Varint::decode(bytes)?;
+        Ok((Self { value }, used))
+    }
+
+    // Seven space-separated words (six data words and a checksum word), for reading IDs aloud.
+    pub fn to_words(&self) -> String {
+        Words::encode(self.value)
+    }
+
+    pub fn from_words(words: &str) -> Result<Self, Nano64Error> {
+        Ok(Self {
+            value: Words::decode(words)?,
+        })
     }
 
     pub fn to_date(&self) -> SystemTime {
         UNIX_EPOCH + Duration::from_millis(self.get_timestamp())
     }
 
+    // Time since the embedded timestamp. Zero if the ID is from the future.
+    pub fn age(&self) -> Duration {
+        Duration::from_millis(time_now_since_epoch_ms().saturating_sub(self.get_timestamp()))
+    }
+
+    // Sanity check for client-supplied IDs: rejects timestamps too far in the future or older
+    // than the policy allows.
+    pub fn validate(&self, policy: &ValidationPolicy) -> Result<(), Nano64Error> {
+        policy.check(self, time_now_since_epoch_ms())
+    }
+
+    // Signed milliseconds from `other` to `self`: positive if `self` is newer.
+    pub fn elapsed_since(&self, other: &Nano64) -> i64 {
+        self.get_timestamp() as i64 - other.get_timestamp() as i64
+    }
+
+    // Flips the top bit so signed comparison of the result matches unsigned comparison of the
+    // ID. Use for signed BIGINT columns.
+    pub const fn to_sortable_i64(&self) -> i64 {
+        (self.value ^ (1 << 63)) as i64
+    }
+
+    pub const fn from_sortable_i64(value: i64) -> Self {
+        Self {
+            value: (value as u64) ^ (1 << 63),
+        }
+    }
+
+    // Inverts the timestamp bits so newer IDs sort first, bytewise and as hex, for stores that
+    // only scan forward (Bigtable/HBase-style "newest first" row keys). The random field is
+    // left alone. Applying it twice gives back the original ID. `get_timestamp` on the result
+    // is meaningless; convert back with `from_descending` first.
+    pub const fn to_descending(&self) -> Self {
+        Self {
+            value: self.value ^ (TIMESTAMP_MASK << TIMESTAMP_SHIFT),
+        }
+    }
+
+    pub const fn from_descending(&self) -> Self {
+        self.to_descending()
+    }
+
+    // Like `generate_now`, but returns the ID in descending form. See `to_descending`.
+    pub fn generate_descending(
+        rng: Option<RandomNumberGeneratorImpl>,
+    ) -> Result<Self, Nano64Error> {
+        Ok(Self::generate_now(rng)?.to_descending())
+    }
+
+    // Decodes the ID into its parts for debugging; `Display` on the result is human-friendly.
+    pub fn explain(&self) -> Explanation {
+        Explanation::new(self)
+    }
+
     pub fn u64_value(&self) -> u64 {
         self.value
     }
@@ -175,7 +619,45 @@ impl Nano64 {
         compare(self, other) == 0
     }
 
-    pub(crate) fn generate(
+    // Returns a copy of this ID with the timestamp replaced; the random field is kept.
+    pub fn with_timestamp(&self, timestamp_ms: u64) -> Result<Self, Nano64Error> {
+        Self::from_parts(timestamp_ms, self.get_random())
+    }
+
+    // Returns a copy of this ID with the random field replaced; the timestamp is kept.
+    pub fn with_random(&self, random: u32) -> Result<Self, Nano64Error> {
+        Self::from_parts(self.get_timestamp(), random)
+    }
+
+    // Orders by the embedded timestamp only, ignoring the random field.
+    pub fn cmp_by_timestamp(&self, other: &Nano64) -> Ordering {
+        self.get_timestamp().cmp(&other.get_timestamp())
+    }
+
+    // Like `generate`, but takes a `SystemTime`. Times before the UNIX epoch are rejected.
+    pub fn generate_at(
+        time: SystemTime,
+        rng: Option<RandomNumberGeneratorImpl>,
+    ) -> Result<Self, Nano64Error> {
+        let since_epoch = time
+            .duration_since(UNIX_EPOCH)
+            .map_err(|_| Nano64Error::Error("time is before the UNIX epoch".into()))?;
+        Self::generate_at_duration(since_epoch, rng)
+    }
+
+    // Like `generate`, but takes the time since the UNIX epoch as a `Duration`.
+    // Sub-millisecond precision is truncated.
+    pub fn generate_at_duration(
+        since_epoch: Duration,
+        rng: Option<RandomNumberGeneratorImpl>,
+    ) -> Result<Self, Nano64Error> {
+        let ms = u64::try_from(since_epoch.as_millis()).unwrap_or(u64::MAX);
+        Self::generate(ms, rng)
+    }
+
+    // Generates an ID for an explicit millisecond, e.g. when backfilling historical events.
+    // Fails with `TimeStampExceedsBitRange` if `timestamp` doesn't fit in 44 bits.
+    pub fn generate(
         timestamp: u64,
         rng: Option<RandomNumberGeneratorImpl>,
     ) -> Result<Self, Nano64Error> {
@@ -183,13 +665,26 @@ impl Nano64 {
             return Err(Nano64Error::TimeStampExceedsBitRange(timestamp));
         }
 
-        let rng = if let Some(_rng) = rng {
+        let mut rng = if let Some(_rng) = rng {
             _rng
         } else {
             default_rng
         };
 
-        let random_value = rng(RANDOM_BITS as u32)?;
+        Self::generate_with_source(timestamp, &mut rng)
+    }
+
+    // Like `generate`, but draws random bits from any `RandomSource`, e.g. a seeded RNG or a
+    // stateful closure.
+    pub fn generate_with_source<R: RandomSource + ?Sized>(
+        timestamp: u64,
+        rng: &mut R,
+    ) -> Result<Self, Nano64Error> {
+        if timestamp > MAX_TIMESTAMP {
+            return Err(Nano64Error::TimeStampExceedsBitRange(timestamp));
+        }
+
+        let random_value = rng.random_bits(RANDOM_BITS as u32)?;
         let ms = timestamp & TIMESTAMP_MASK;
         let random = (random_value as u64) & RANDOM_MASK;
         let value = (ms << TIMESTAMP_SHIFT) | random;
@@ -197,7 +692,9 @@ impl Nano64 {
         Ok(Self { value })
     }
 
-    pub(crate) fn generate_monotonic(
+    // Monotonic variant of `generate`. Shares state with `generate_monotonic_now`, so a
+    // timestamp older than the last one issued is clamped forward to keep IDs increasing.
+    pub fn generate_monotonic(
         timestamp: u64,
         rng: Option<RandomNumberGeneratorImpl>,
     ) -> Result<Self, Nano64Error> {
@@ -205,88 +702,90 @@ impl Nano64 {
             return Err(Nano64Error::TimeStampExceedsBitRange(timestamp));
         }
 
+        let mut rng = if let Some(_rng) = rng {
+            _rng
+        } else {
+            default_rng
+        };
+
+        Self::generate_monotonic_with_source(timestamp, &mut rng)
+    }
+
+    // Like `generate_monotonic`, but draws random bits from any `RandomSource`. Shares the same
+    // process-wide monotonic state.
+    pub fn generate_monotonic_with_source<R: RandomSource>(
+        timestamp: u64,
+        rng: &mut R,
+    ) -> Result<Self, Nano64Error> {
+        if timestamp > MAX_TIMESTAMP {
+            return Err(Nano64Error::TimeStampExceedsBitRange(timestamp));
+        }
+
+        get_monotonic_state().generate(timestamp, rng)
+    }
+
+    pub fn generate_smeared_now(
+        options: SmearOptions,
+        rng: Option<RandomNumberGeneratorImpl>,
+    ) -> Result<Self, Nano64Error> {
+        Self::generate_smeared(time_now_since_epoch_ms(), options, rng)
+    }
+
+    // Like `generate`, but once a millisecond has seen more than `options.burst_threshold` IDs,
+    // the timestamp of each further ID is pushed forward by a random 1..=`options.window_ms` ms.
+    pub(crate) fn generate_smeared(
+        timestamp: u64,
+        options: SmearOptions,
+        rng: Option<RandomNumberGeneratorImpl>,
+    ) -> Result<Self, Nano64Error> {
+        if timestamp > MAX_TIMESTAMP {
+            return Err(Nano64Error::TimeStampExceedsBitRange(timestamp));
+        }
+
         let rng = if let Some(_rng) = rng {
             _rng
         } else {
             default_rng
         };
 
-        let monotonic_refs = get_monotonic_refs();
-        let mut refs = monotonic_refs
-            .lock()
-            .map_err(|_| Nano64Error::Error("Error unlocking refs".into()))?;
+        let count = {
+            let smear_refs = get_smear_refs();
+            let mut refs = smear_refs
+                .lock()
+                .map_err(|_| Nano64Error::Error("Error unlocking refs".into()))?;
+            if refs.timestamp != timestamp {
+                refs.timestamp = timestamp;
+                refs.count = 0;
+            }
+            refs.count += 1;
+            refs.count
+        };
 
-        // Enforce nondecreasing time
         let mut ts = timestamp;
-        if ts < refs.last_timestamp {
-            ts = refs.last_timestamp;
-        }
-
-        let random: u64;
-        if ts == refs.last_timestamp {
-            // Same ms → increment
-            random = (refs.last_random + 1) & RANDOM_MASK;
-            if random == 0 {
-                ts += 1;
-                if ts > MAX_TIMESTAMP {
-                    return Err(Nano64Error::Error(
-                        "timestamp overflow after incrementing for monotonic generation".into(),
-                    ));
-                }
-                refs.last_timestamp = ts;
-                refs.last_random = 0;
-                let ms = ts & TIMESTAMP_MASK;
-                let value = ms << TIMESTAMP_SHIFT;
-                return Ok(Self { value });
-            }
-        } else {
-            let random_value = rng(RANDOM_BITS as u32)?;
-            random = (random_value as u64) & RANDOM_MASK;
+        if options.window_ms > 0 && count > options.burst_threshold {
+            let offset = (rng(32)? as u64 % options.window_ms) + 1;
+            ts = (ts + offset).min(MAX_TIMESTAMP);
         }
 
-        refs.last_timestamp = ts;
-        refs.last_random = random;
-        let ms = ts & TIMESTAMP_MASK;
-        let value = (ms << TIMESTAMP_SHIFT) | random;
-        return Ok(Self { value });
+        Self::generate(ts, Some(rng))
     }
 }
 
 #[cfg(test)]
 mod tests {
 
-    use std::{
-        collections::HashSet,
-        sync::{Mutex, OnceLock},
-        thread,
-        time::UNIX_EPOCH,
-    };
+    use std::{collections::HashSet, thread, time::UNIX_EPOCH};
 
     use rand::Rng;
 
     use crate::{
-        Nano64, Nano64Error, RANDOM_BITS, TIMESTAMP_BITS, compare, default_rng,
-        monotonic_refs::get_monotonic_refs,
+        HexFormat, Nano64, Nano64Error, RANDOM_BITS, SmearOptions, TIMESTAMP_BITS, compare,
+        default_rng,
         nano64::{MAX_TIMESTAMP, RANDOM_MASK},
+        testing::lock_monotonic_state,
         time_now_since_epoch_ms,
     };
 
-    // Rust tests run concurrently by default. Some tests reset or manipulate the global
-    // monotonic refs to produce predictable results. Without coordination, these tests
-    // can interfere with each other, causing failures that would not occur in normal usage.
-    // This lock ensures only one test at a time can access or modify the global monotonic refs.
-    static MONOTONIC_LOCK_FOR_TESTS: OnceLock<Mutex<()>> = OnceLock::new();
-    fn get_monotonic_lock_for_tests() -> &'static Mutex<()> {
-        MONOTONIC_LOCK_FOR_TESTS.get_or_init(|| Mutex::new(()))
-    }
-
-    fn set_monotonic_refs_to(last_random: u64, last_timestamp: u64) {
-        let monotonic_refs = get_monotonic_refs();
-        let mut refs = monotonic_refs.lock().unwrap();
-        refs.last_random = last_random;
-        refs.last_timestamp = last_timestamp;
-    }
-
     #[test]
     fn test_nano64_new() {
         let _zero = 0;
@@ -312,6 +811,21 @@ mod tests {
         assert_eq!(id.get_random(), expected_random);
     }
 
+    #[test]
+    fn test_nano64_generate_rejects_out_of_range_timestamp() {
+        assert!(matches!(
+            Nano64::generate(MAX_TIMESTAMP + 1, None),
+            Err(Nano64Error::TimeStampExceedsBitRange(_))
+        ));
+        assert!(matches!(
+            Nano64::generate_monotonic(MAX_TIMESTAMP + 1, None),
+            Err(Nano64Error::TimeStampExceedsBitRange(_))
+        ));
+        // A historical millisecond is fine.
+        let id = Nano64::generate(946_684_800_000, None).unwrap();
+        assert_eq!(id.get_timestamp(), 946_684_800_000);
+    }
+
     #[test]
     fn test_nano64_generate_default() {
         let id = Nano64::generate_default().unwrap();
@@ -325,7 +839,7 @@ mod tests {
 
     #[test]
     fn test_nano64_generate_monotonic() {
-        let _guard = get_monotonic_lock_for_tests().lock().unwrap();
+        let _guard = lock_monotonic_state();
         let timestamp: u64 = 1234567890123;
         fn _rng(_bits: u32) -> Result<u32, Nano64Error> {
             Ok(0x12345)
@@ -607,8 +1121,13 @@ mod tests {
     fn test_nano64_string() {
         let id = Nano64::new(0x123456789ABCD);
         let str = id.to_string();
-        assert_ne!(str, "");
-        assert!(str.contains("Nano64"));
+        assert_eq!(str, id.to_hex());
+        assert_eq!(String::from(id), id.to_hex());
+        assert_eq!(str.parse::<Nano64>().unwrap(), id);
+        assert_eq!(format!("{id:>20}"), format!("   {}", id.to_hex()));
+        let verbose = format!("{id:#}");
+        assert!(verbose.contains("Nano64"));
+        assert!(verbose.contains(&format!("value={}", id.u64_value())));
     }
 
     #[test]
@@ -642,8 +1161,7 @@ mod tests {
 
     #[test]
     fn test_nano64_monotonic_now() {
-        let _guard = get_monotonic_lock_for_tests().lock().unwrap();
-        set_monotonic_refs_to(0, 0);
+        let _guard = lock_monotonic_state();
         let id_1: Nano64 = match Nano64::generate_monotonic_now(None) {
             Ok(got) => got,
             Err(e) => panic!("[id_1] did not expect error {e}"),
@@ -657,7 +1175,7 @@ mod tests {
 
     #[test]
     fn test_monotonic_race() {
-        let _guard = get_monotonic_lock_for_tests().lock().unwrap();
+        let _guard = lock_monotonic_state();
         let min_threads = 5;
         let max_threads = 17;
         let num_ids_to_create = 100_000;
@@ -722,8 +1240,7 @@ mod tests {
 
     #[test]
     fn test_nano64_monotonic_default() {
-        let _guard = get_monotonic_lock_for_tests().lock().unwrap();
-        set_monotonic_refs_to(0, 0);
+        let _guard = lock_monotonic_state();
         let id = match Nano64::generate_monotonic_default() {
             Ok(got) => got,
             Err(e) => panic!("unexpected error {e}"),
@@ -733,9 +1250,9 @@ mod tests {
 
     #[test]
     fn test_nano64_monotonic_overflow() {
-        let _guard = get_monotonic_lock_for_tests().lock().unwrap();
+        let guard = lock_monotonic_state();
         // Set refs to maximums, simulate exhaustion.
-        set_monotonic_refs_to(RANDOM_MASK, MAX_TIMESTAMP);
+        guard.set_last(MAX_TIMESTAMP, RANDOM_MASK);
         if let Ok(got) = Nano64::generate_monotonic(MAX_TIMESTAMP, None) {
             panic!(
                 "`generate_monotonic` called with max timestamp and exhausted random should error but got {got:?}"
@@ -745,8 +1262,8 @@ mod tests {
 
     #[test]
     fn test_nano64_monotonic_backwards_time() {
-        let _guard = get_monotonic_lock_for_tests().lock().unwrap();
-        set_monotonic_refs_to(100, 1000000);
+        let guard = lock_monotonic_state();
+        guard.set_last(1000000, 100);
         // Try to generate with an earlier timestamp
         let id = Nano64::generate_monotonic(500000, None).unwrap();
         // Should use the last timestamp, not provided one
@@ -788,8 +1305,8 @@ mod tests {
 
     #[test]
     fn test_nano64_monotonic_failing_rng() {
-        let _guard = get_monotonic_lock_for_tests().lock().unwrap();
-        set_monotonic_refs_to(0, 1000);
+        let guard = lock_monotonic_state();
+        guard.set_last(1000, 0);
         fn rng(_bits: u32) -> Result<u32, Nano64Error> {
             Err(Nano64Error::Error("Simulated rng failure".into()))
         }
@@ -800,8 +1317,8 @@ mod tests {
 
     #[test]
     fn test_nano64_monotonic_same_timestamp_increment() {
-        let _guard = get_monotonic_lock_for_tests().lock().unwrap();
-        set_monotonic_refs_to(50, 1000);
+        let guard = lock_monotonic_state();
+        guard.set_last(1000, 50);
         let id_1 = Nano64::generate_monotonic(1000, None).unwrap();
         let id_2 = Nano64::generate_monotonic(1000, None).unwrap();
         if id_2.get_random() <= id_1.get_random() {
@@ -826,8 +1343,7 @@ mod tests {
 
     #[test]
     fn test_nano64_monotonic_generate_with_none_rng() {
-        let _guard = get_monotonic_lock_for_tests().lock().unwrap();
-        set_monotonic_refs_to(0, 0);
+        let _guard = lock_monotonic_state();
         let timestamp = 12345;
         let id = if let Ok(got) = Nano64::generate_monotonic(timestamp, None) {
             got
@@ -856,4 +1372,503 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_nano64_generate_smeared_within_window() {
+        let timestamp: u64 = 1234567890123;
+        let options = SmearOptions {
+            window_ms: 3,
+            burst_threshold: 10,
+        };
+        let mut smeared = 0;
+        for _ in 0..1000 {
+            let id = Nano64::generate_smeared(timestamp, options, None).unwrap();
+            let ts = id.get_timestamp();
+            assert!(
+                ts >= timestamp && ts <= timestamp + options.window_ms,
+                "smeared timestamp {ts} outside of window"
+            );
+            if ts != timestamp {
+                smeared += 1;
+            }
+        }
+        assert!(smeared > 0, "expected burst to be smeared forward");
+    }
+
+    #[test]
+    fn test_nano64_generate_smeared_zero_window() {
+        let timestamp: u64 = 1234567890999;
+        let options = SmearOptions {
+            window_ms: 0,
+            burst_threshold: 0,
+        };
+        for _ in 0..100 {
+            let id = Nano64::generate_smeared(timestamp, options, None).unwrap();
+            assert_eq!(id.get_timestamp(), timestamp);
+        }
+    }
+
+    #[test]
+    fn test_nano64_generate_smeared_clamps_to_max_timestamp() {
+        let options = SmearOptions {
+            window_ms: 5,
+            burst_threshold: 0,
+        };
+        for _ in 0..100 {
+            let id = Nano64::generate_smeared(MAX_TIMESTAMP, options, None).unwrap();
+            assert_eq!(id.get_timestamp(), MAX_TIMESTAMP);
+        }
+    }
+
+    #[test]
+    fn test_nano64_layout_matches_u64() {
+        assert_eq!(std::mem::size_of::<Nano64>(), std::mem::size_of::<u64>());
+        assert_eq!(std::mem::align_of::<Nano64>(), std::mem::align_of::<u64>());
+    }
+
+    #[cfg(feature = "bytemuck")]
+    #[test]
+    fn test_nano64_bytemuck_cast_slice() {
+        let raw: Vec<u64> = vec![0, 1, 0x123456789ABCDEF0, u64::MAX];
+        let ids: &[Nano64] = bytemuck::cast_slice(&raw);
+        assert_eq!(ids.len(), raw.len());
+        for (id, value) in ids.iter().zip(raw.iter()) {
+            assert_eq!(id.u64_value(), *value);
+        }
+        let back: &[u64] = bytemuck::cast_slice(ids);
+        assert_eq!(back, raw.as_slice());
+        let zeroed: Nano64 = bytemuck::Zeroable::zeroed();
+        assert_eq!(zeroed.u64_value(), 0);
+    }
+
+    #[test]
+    fn test_nano64_words_roundtrip() {
+        let id = Nano64::generate_default().unwrap();
+        let words = id.to_words();
+        assert!(Nano64::from_words(&words).unwrap().equals(&id));
+        assert!(Nano64::from_words("abandon").is_err());
+    }
+
+    #[test]
+    fn test_nano64_generate_into() {
+        let mut buf = [Nano64::new(0); 64];
+        Nano64::generate_into(&mut buf, None).unwrap();
+        let timestamp = buf[0].get_timestamp();
+        assert_ne!(timestamp, 0);
+        assert!(buf.iter().all(|id| id.get_timestamp() == timestamp));
+
+        fn rng(_bits: u32) -> Result<u32, Nano64Error> {
+            Err(Nano64Error::Error("Simulated rng failure".into()))
+        }
+        assert!(Nano64::generate_into(&mut buf, Some(rng)).is_err());
+    }
+
+    #[test]
+    fn test_nano64_generate_fast_across_threads() {
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                thread::spawn(|| {
+                    (0..100)
+                        .map(|_| Nano64::generate_fast().unwrap())
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        let ids: Vec<Nano64> = handles
+            .into_iter()
+            .flat_map(|h| h.join().unwrap())
+            .collect();
+        assert_eq!(ids.len(), 800);
+        assert!(ids.iter().all(|id| id.get_timestamp() > 0));
+        let random: HashSet<u32> = ids.iter().map(|id| id.get_random()).collect();
+        assert!(random.len() > 700);
+    }
+
+    #[test]
+    fn test_nano64_generate_batch() {
+        // Spans several RNG chunks, including a partial one.
+        let ids = Nano64::generate_batch(1000, None).unwrap();
+        assert_eq!(ids.len(), 1000);
+        let timestamp = ids[0].get_timestamp();
+        assert!(ids.iter().all(|id| id.get_timestamp() == timestamp));
+        let distinct: std::collections::HashSet<u32> =
+            ids.iter().map(|id| id.get_random()).collect();
+        assert!(distinct.len() > 900);
+
+        fn rng(_bits: u32) -> Result<u32, Nano64Error> {
+            Ok(u32::MAX)
+        }
+        let ids = Nano64::generate_batch(3, Some(rng)).unwrap();
+        assert!(ids.iter().all(|id| id.get_random() == RANDOM_MASK as u32));
+        assert!(Nano64::generate_batch(0, None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_nano64_generate_into_uninit() {
+        let mut buf = [std::mem::MaybeUninit::<Nano64>::uninit(); 32];
+        let ids = Nano64::generate_into_uninit(&mut buf, None).unwrap();
+        assert_eq!(ids.len(), 32);
+        assert!(ids.iter().all(|id| id.get_timestamp() != 0));
+        let empty = Nano64::generate_into_uninit(&mut [], None).unwrap();
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn test_nano64_parse_with_suggestions() {
+        struct TestCase {
+            name: String,
+            input: String,
+            suggestion: Option<u64>,
+        }
+
+        let test_cases: Vec<TestCase> = vec![
+            TestCase {
+                name: "letter O".into(),
+                input: "123456789AB-CDEFO".into(),
+                suggestion: Some(0x123456789ABCDEF0),
+            },
+            TestCase {
+                name: "letter l and I".into(),
+                input: "l23456789AB-CDEFI".into(),
+                suggestion: Some(0x123456789ABCDEF1),
+            },
+            TestCase {
+                name: "space separator".into(),
+                input: " 123456789AB CDEF0 ".into(),
+                suggestion: Some(0x123456789ABCDEF0),
+            },
+            TestCase {
+                name: "doubled keystroke".into(),
+                input: "1233456789AB-CDEF0".into(),
+                suggestion: Some(0x123456789ABCDEF0),
+            },
+            TestCase {
+                name: "hopeless".into(),
+                input: "nope".into(),
+                suggestion: None,
+            },
+        ];
+
+        for tc in test_cases {
+            match Nano64::parse_with_suggestions(&tc.input) {
+                Ok(got) => panic!("[{}] expected error, got {got:?}", tc.name),
+                Err(Nano64Error::InvalidWithSuggestions { suggestions, .. }) => {
+                    let want = tc
+                        .suggestion
+                        .unwrap_or_else(|| panic!("[{}] did not expect suggestions", tc.name));
+                    assert!(
+                        suggestions.iter().any(|id| id.u64_value() == want),
+                        "[{}] expected {want:X} in {suggestions:?}",
+                        tc.name
+                    );
+                }
+                Err(e) => assert!(
+                    tc.suggestion.is_none(),
+                    "[{}] unexpected error {e}",
+                    tc.name
+                ),
+            }
+        }
+
+        let valid = Nano64::parse_with_suggestions("123456789AB-CDEF0").unwrap();
+        assert_eq!(valid.u64_value(), 0x123456789ABCDEF0);
+    }
+
+    #[test]
+    fn test_nano64_std_traits() {
+        use std::collections::{BTreeMap, HashMap};
+
+        let mut ids = vec![Nano64::new(300), Nano64::new(100), Nano64::new(200)];
+        ids.sort();
+        assert_eq!(
+            ids,
+            vec![Nano64::new(100), Nano64::new(200), Nano64::new(300)]
+        );
+        assert!(Nano64::new(1) < Nano64::new(u64::MAX));
+        assert_eq!(ids.iter().max(), Some(&Nano64::new(300)));
+
+        let mut by_hash = HashMap::new();
+        by_hash.insert(Nano64::new(42), "answer");
+        assert_eq!(by_hash.get(&Nano64::new(42)), Some(&"answer"));
+
+        let mut by_order = BTreeMap::new();
+        for id in &ids {
+            by_order.insert(*id, id.u64_value());
+        }
+        let keys: Vec<Nano64> = by_order.keys().copied().collect();
+        assert_eq!(keys, ids);
+    }
+
+    #[test]
+    fn test_nano64_compare_ordering() {
+        use std::cmp::Ordering;
+
+        let id_1 = Nano64::new(100);
+        let id_2 = Nano64::new(200);
+        assert_eq!(crate::compare_ordering(&id_1, &id_2), Ordering::Less);
+        assert_eq!(crate::compare_ordering(&id_2, &id_1), Ordering::Greater);
+        assert_eq!(crate::compare_ordering(&id_1, &id_1), Ordering::Equal);
+
+        let mut ids = vec![id_2, id_1];
+        ids.sort_by(crate::compare_ordering);
+        assert_eq!(ids, vec![id_1, id_2]);
+    }
+
+    #[test]
+    fn test_nano64_cmp_by_timestamp() {
+        use std::cmp::Ordering;
+
+        fn rng_high(_bits: u32) -> Result<u32, Nano64Error> {
+            Ok(0xFFFFF)
+        }
+        fn rng_low(_bits: u32) -> Result<u32, Nano64Error> {
+            Ok(0)
+        }
+        let a = Nano64::generate(1000, Some(rng_high)).unwrap();
+        let b = Nano64::generate(1000, Some(rng_low)).unwrap();
+        let c = Nano64::generate(1001, Some(rng_low)).unwrap();
+        assert_eq!(a.cmp_by_timestamp(&b), Ordering::Equal);
+        assert_ne!(compare(&a, &b), 0);
+        assert_eq!(b.cmp_by_timestamp(&c), Ordering::Less);
+        assert_eq!(c.cmp_by_timestamp(&a), Ordering::Greater);
+    }
+
+    #[test]
+    fn test_nano64_boundary_constants() {
+        assert!(Nano64::NIL.is_nil());
+        assert!(Nano64::MIN.is_nil());
+        assert!(!Nano64::MAX.is_nil());
+        assert_eq!(Nano64::MIN.get_timestamp(), 0);
+        assert_eq!(Nano64::MAX.get_timestamp(), MAX_TIMESTAMP);
+        assert_eq!(Nano64::MAX.get_random(), RANDOM_MASK as u32);
+        let id = Nano64::generate_default().unwrap();
+        assert!(Nano64::MIN < id && id < Nano64::MAX);
+        assert!(!id.is_nil());
+    }
+
+    #[test]
+    fn test_nano64_from_parts() {
+        let id = Nano64::from_parts(1_700_000_000_000, 0xABCDE).unwrap();
+        assert_eq!(id.get_timestamp(), 1_700_000_000_000);
+        assert_eq!(id.get_random(), 0xABCDE);
+        assert_eq!(
+            Nano64::from_parts(MAX_TIMESTAMP, RANDOM_MASK as u32).unwrap(),
+            Nano64::MAX
+        );
+        assert!(matches!(
+            Nano64::from_parts(MAX_TIMESTAMP + 1, 0),
+            Err(Nano64Error::TimeStampExceedsBitRange(_))
+        ));
+        assert!(matches!(
+            Nano64::from_parts(0, 1 << RANDOM_BITS),
+            Err(Nano64Error::RandomExceedsBitRange(_))
+        ));
+    }
+
+    #[test]
+    fn test_nano64_with_timestamp_and_random() {
+        let id = Nano64::from_parts(5000, 42).unwrap();
+        let moved = id.with_timestamp(9000).unwrap();
+        assert_eq!(moved.get_timestamp(), 9000);
+        assert_eq!(moved.get_random(), 42);
+        let rerolled = id.with_random(7).unwrap();
+        assert_eq!(rerolled.get_timestamp(), 5000);
+        assert_eq!(rerolled.get_random(), 7);
+
+        // Range bounds for every ID in one millisecond.
+        let lo = id.with_random(0).unwrap();
+        let hi = id.with_random(RANDOM_MASK as u32).unwrap();
+        assert!(lo <= id && id <= hi);
+
+        assert!(id.with_timestamp(MAX_TIMESTAMP + 1).is_err());
+        assert!(id.with_random(1 << RANDOM_BITS).is_err());
+    }
+
+    #[test]
+    fn test_nano64_generate_at() {
+        use std::time::Duration;
+
+        let time = UNIX_EPOCH + Duration::from_millis(1_700_000_000_123);
+        let id = Nano64::generate_at(time, None).unwrap();
+        assert_eq!(id.get_timestamp(), 1_700_000_000_123);
+        assert_eq!(id.to_date(), time);
+
+        let id = Nano64::generate_at_duration(Duration::from_micros(5_999), None).unwrap();
+        assert_eq!(id.get_timestamp(), 5);
+
+        assert!(Nano64::generate_at(UNIX_EPOCH - Duration::from_millis(1), None).is_err());
+        assert!(matches!(
+            Nano64::generate_at_duration(Duration::from_secs(u64::MAX), None),
+            Err(Nano64Error::TimeStampExceedsBitRange(_))
+        ));
+    }
+
+    #[test]
+    fn test_nano64_age_and_elapsed_since() {
+        let now = time_now_since_epoch_ms();
+        let old = Nano64::generate(now - 5_000, None).unwrap();
+        assert!(old.age().as_millis() >= 5_000);
+        let future = Nano64::generate(now + 60_000, None).unwrap();
+        assert_eq!(future.age().as_millis(), 0);
+
+        let a = Nano64::from_parts(1000, 0).unwrap();
+        let b = Nano64::from_parts(1250, 0).unwrap();
+        assert_eq!(b.elapsed_since(&a), 250);
+        assert_eq!(a.elapsed_since(&b), -250);
+        assert_eq!(
+            Nano64::MAX.elapsed_since(&Nano64::MIN),
+            MAX_TIMESTAMP as i64
+        );
+    }
+
+    #[test]
+    fn test_nano64_to_hex_buf_and_write_hex() {
+        let id = Nano64::new(0x0123456789ABCDEF);
+        let mut buf = [0u8; 17];
+        assert_eq!(id.to_hex_buf(&mut buf), "0123456789A-BCDEF");
+        assert_eq!(id.to_hex(), "0123456789A-BCDEF");
+
+        let mut out = String::new();
+        id.write_hex(&mut out).unwrap();
+        Nano64::MAX.write_hex(&mut out).unwrap();
+        assert_eq!(out, "0123456789A-BCDEFFFFFFFFFFFF-FFFFF");
+
+        for _ in 0..100 {
+            let id = Nano64::generate_default().unwrap();
+            let mut expected = format!("{:016X}", id.u64_value());
+            expected.insert(11, '-');
+            assert_eq!(id.to_hex_buf(&mut buf), expected);
+        }
+    }
+
+    #[test]
+    fn test_nano64_to_hex_with_round_trips() {
+        let id = Nano64::generate_default().unwrap();
+        assert_eq!(id.to_hex_with(&HexFormat::default()), id.to_hex());
+        let lower = HexFormat {
+            uppercase: false,
+            prefix: true,
+            ..HexFormat::plain()
+        };
+        assert_eq!(id.to_hex_with(&lower).parse::<Nano64>().unwrap(), id);
+    }
+
+    #[test]
+    fn test_nano64_decimal_strings() {
+        let id = Nano64::new(18_446_744_073_709_551_615);
+        assert_eq!(id.to_decimal_string(), "18446744073709551615");
+        assert_eq!(
+            Nano64::from_decimal_str("18446744073709551615").unwrap(),
+            id
+        );
+        assert_eq!(Nano64::from_decimal_str("0").unwrap(), Nano64::NIL);
+        for bad in ["", "-1", "+1", " 1", "1_000", "18446744073709551616", "ABC"] {
+            assert!(Nano64::from_decimal_str(bad).is_err(), "{bad} should fail");
+        }
+    }
+
+    #[test]
+    fn test_nano64_parse_any() {
+        let id = Nano64::new(0x0123456789ABCDEF);
+        assert_eq!(Nano64::parse_any("0123456789A-BCDEF").unwrap(), id);
+        assert_eq!(Nano64::parse_any(&id.to_decimal_string()).unwrap(), id);
+        // 16 digits is read as hex.
+        assert_eq!(
+            Nano64::parse_any("0000000000000010").unwrap().u64_value(),
+            0x10
+        );
+        assert_eq!(Nano64::parse_any("10").unwrap().u64_value(), 10);
+        // FromStr stays hex-only.
+        assert!("10".parse::<Nano64>().is_err());
+    }
+
+    #[test]
+    fn test_nano64_from_slices() {
+        let bytes = [0x01, 0x23, 0x45, 0x67, 0x89, 0xAB, 0xCD, 0xEF];
+        let id = Nano64::try_from(&bytes[..]).unwrap();
+        assert_eq!(id.u64_value(), 0x0123456789ABCDEF);
+        assert_eq!(Nano64::from_be_slice(&bytes).unwrap(), id);
+        assert_eq!(
+            Nano64::from_le_slice(&bytes).unwrap().u64_value(),
+            0xEFCDAB8967452301
+        );
+
+        let err = Nano64::try_from(&bytes[..5]).unwrap_err();
+        assert!(matches!(
+            err,
+            Nano64Error::InvalidByteLength {
+                expected: 8,
+                got: 5
+            }
+        ));
+        assert_eq!(err.to_string(), "Expected 8 bytes, got 5");
+        assert!(Nano64::from_le_slice(&[0u8; 9]).is_err());
+    }
+
+    #[test]
+    fn test_nano64_le_bytes() {
+        let id = Nano64::new(0x0123456789ABCDEF);
+        let le = id.to_le_bytes();
+        assert_eq!(le, [0xEF, 0xCD, 0xAB, 0x89, 0x67, 0x45, 0x23, 0x01]);
+        assert_eq!(Nano64::from_le_bytes(le), id);
+        let mut be = id.to_bytes();
+        be.reverse();
+        assert_eq!(be, le);
+    }
+
+    #[test]
+    fn test_nano64_sortable_i64() {
+        assert_eq!(Nano64::MIN.to_sortable_i64(), i64::MIN);
+        assert_eq!(Nano64::MAX.to_sortable_i64(), i64::MAX);
+
+        let mut ids: Vec<Nano64> = [0, 1, 1 << 63, (1 << 63) - 1, u64::MAX - 1]
+            .into_iter()
+            .map(Nano64::new)
+            .collect();
+        ids.push(Nano64::generate_default().unwrap());
+        let mut by_i64 = ids.clone();
+        ids.sort();
+        by_i64.sort_by_key(|id| id.to_sortable_i64());
+        assert_eq!(ids, by_i64);
+
+        for id in ids {
+            assert_eq!(Nano64::from_sortable_i64(id.to_sortable_i64()), id);
+        }
+    }
+
+    #[test]
+    fn test_nano64_descending() {
+        let older = Nano64::from_parts(1_000, 0x00001).unwrap();
+        let newer = Nano64::from_parts(2_000, 0x00002).unwrap();
+        let (older_desc, newer_desc) = (older.to_descending(), newer.to_descending());
+        assert!(newer_desc < older_desc);
+        assert!(newer_desc.to_bytes() < older_desc.to_bytes());
+        assert!(newer_desc.to_hex() < older_desc.to_hex());
+        assert_eq!(older_desc.get_random(), older.get_random());
+        assert_eq!(older_desc.from_descending(), older);
+        assert_eq!(Nano64::MIN.to_descending().get_timestamp(), MAX_TIMESTAMP);
+
+        let id = Nano64::generate_descending(None).unwrap();
+        let age_ms = time_now_since_epoch_ms() - id.from_descending().get_timestamp();
+        assert!(age_ms < 60_000);
+    }
+
+    #[test]
+    fn test_nano64_generate_monotonic_thread_local() {
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                thread::spawn(|| {
+                    let mut last = Nano64::MIN;
+                    for _ in 0..10_000 {
+                        let id = Nano64::generate_monotonic_thread_local(None).unwrap();
+                        assert!(id > last, "not strictly increasing within the thread");
+                        last = id;
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+    }
 }
@@ -1,126 +1,481 @@
 use crate::{
-    ClockImpl, Hex, IV_LENGTH, Nano64, Nano64Error, PAYLOAD_LENGTH, RandomNumberGeneratorImpl,
-    default_rng, time_now_since_epoch_ms,
+    AeadBackend, AesGcm, Base64Url, Clock, ClockImpl, GcmAlgorithm, Hex, IV_LENGTH, Keyring,
+    LEGACY_PAYLOAD_LENGTH, MAX_METADATA_LENGTH, Nano64, Nano64Error, Nano128,
+    PAYLOAD_HEADER_LENGTH, PAYLOAD_LENGTH, PAYLOAD_VERSION, RandomNumberGeneratorImpl,
+    RandomSource, TAG_LENGTH, TRUNCATED_PAYLOAD_LENGTH, TRUNCATED_PAYLOAD_VERSION,
+    TRUNCATED_TAG_LENGTH, default_rng, mac::KeyedHmac, time_now_since_epoch_ms,
 };
-use aes_gcm::{
-    Aes256Gcm, Key,
-    aead::{Aead, KeyInit, OsRng, generic_array::GenericArray, rand_core::RngCore},
+use aes_gcm::aead::{OsRng, rand_core::RngCore};
+use std::{
+    fmt,
+    hash::{Hash, Hasher},
+    ops::RangeInclusive,
+    str::FromStr,
+    sync::{
+        Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
 };
+use subtle::ConstantTimeEq;
 
 #[derive(Clone)]
 pub struct Nano64Encrypted {
     pub id: Nano64,
-    pub(crate) payload: [u8; PAYLOAD_LENGTH],
-    #[allow(dead_code)]
-    pub(crate) gcm: Aes256Gcm,
+    pub(crate) metadata: Vec<u8>,
+    pub(crate) payload: Vec<u8>,
 }
 
 impl Nano64Encrypted {
+    // The bytes sealed with `encrypt_with_metadata`, recovered and authenticated on decryption.
+    // Empty for payloads encrypted without metadata.
+    pub fn metadata(&self) -> &[u8] {
+        &self.metadata
+    }
+
     pub fn to_encrypted_hex(&self) -> String {
         Hex::from_bytes(&self.payload)
     }
 
-    pub fn to_encrypted_bytes(&self) -> [u8; PAYLOAD_LENGTH] {
-        self.payload
+    // Payload as unpadded base64url: 51 URL-safe chars instead of 76 hex chars.
+    pub fn to_encrypted_slug(&self) -> String {
+        Base64Url::encode(&self.payload)
+    }
+
+    // A `Vec` rather than the fixed-size array of earlier releases: metadata and truncated tags
+    // change the payload length. `as_encrypted_bytes` avoids the copy.
+    pub fn to_encrypted_bytes(&self) -> Vec<u8> {
+        self.payload.clone()
+    }
+
+    // The payload without a copy, e.g. for writing to a buffer.
+    pub fn as_encrypted_bytes(&self) -> &[u8] {
+        &self.payload
+    }
+
+    pub fn to_payload(&self) -> Nano64EncryptedPayload {
+        Nano64EncryptedPayload(self.payload.clone())
+    }
+
+    // Compares payloads in constant time, so matching a submitted token against a stored one
+    // doesn't leak how many leading bytes agree. Only the lengths are compared in variable time.
+    pub fn ct_eq(&self, other: &Self) -> bool {
+        self.payload.ct_eq(&other.payload).into()
+    }
+}
+
+// Equality and hashing go by the payload bytes, which already determine the ID.
+impl PartialEq for Nano64Encrypted {
+    fn eq(&self, other: &Self) -> bool {
+        self.ct_eq(other)
+    }
+}
+
+impl Eq for Nano64Encrypted {}
+
+impl Hash for Nano64Encrypted {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.payload.hash(state);
+    }
+}
+
+// Writes the payload as hex, the same as `to_encrypted_hex`, so the plaintext ID never ends up
+// in logs by accident.
+impl fmt::Display for Nano64Encrypted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_encrypted_hex())
+    }
+}
+
+// An encrypted `Nano64` payload that hasn't been decrypted, e.g. a token read from a request
+// before a factory is at hand. Parsing only checks the hex and the length; authenticity is
+// checked by `Nano64EncryptionFactory::decrypt`. Compares in constant time, like
+// `Nano64Encrypted`.
+#[derive(Clone, Debug)]
+pub struct Nano64EncryptedPayload(Vec<u8>);
+
+impl PartialEq for Nano64EncryptedPayload {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.ct_eq(&other.0).into()
+    }
+}
+
+impl Eq for Nano64EncryptedPayload {}
+
+impl Hash for Nano64EncryptedPayload {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state);
+    }
+}
+
+impl Nano64EncryptedPayload {
+    // Accepts `PAYLOAD_LENGTH` bytes, `TRUNCATED_PAYLOAD_LENGTH` for truncated-tag payloads,
+    // either plus up to `MAX_METADATA_LENGTH` bytes of metadata, or `LEGACY_PAYLOAD_LENGTH` for
+    // payloads from before the version header.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Nano64Error> {
+        let length = bytes.len();
+        if !(PAYLOAD_LENGTH..=PAYLOAD_LENGTH + MAX_METADATA_LENGTH).contains(&length)
+            && !(TRUNCATED_PAYLOAD_LENGTH..=TRUNCATED_PAYLOAD_LENGTH + MAX_METADATA_LENGTH)
+                .contains(&length)
+            && length != LEGACY_PAYLOAD_LENGTH
+        {
+            return Err(Nano64Error::InvalidByteLength {
+                expected: PAYLOAD_LENGTH,
+                got: bytes.len(),
+            });
+        }
+        Ok(Self(bytes.to_vec()))
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    pub fn to_encrypted_hex(&self) -> String {
+        Hex::from_bytes(&self.0)
+    }
+}
+
+impl FromStr for Nano64EncryptedPayload {
+    type Err = Nano64Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Self::from_bytes(&Hex::to_bytes_lenient(value)?)
+    }
+}
+
+impl fmt::Display for Nano64EncryptedPayload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_encrypted_hex())
     }
 }
 
-pub struct Nano64EncryptionFactory {
-    pub(crate) gcm: Aes256Gcm,
-    pub(crate) clock: ClockImpl,
-    pub(crate) rng: RandomNumberGeneratorImpl,
+// An encrypted `Nano128`, the 128-bit counterpart of `Nano64Encrypted`.
+#[derive(Clone)]
+pub struct Nano128Encrypted {
+    pub id: Nano128,
+    pub(crate) payload: Vec<u8>,
+}
+
+impl Nano128Encrypted {
+    pub fn to_encrypted_hex(&self) -> String {
+        Hex::from_bytes(&self.payload)
+    }
+
+    pub fn to_encrypted_slug(&self) -> String {
+        Base64Url::encode(&self.payload)
+    }
+
+    pub fn to_encrypted_bytes(&self) -> Vec<u8> {
+        self.payload.clone()
+    }
+
+    pub fn as_encrypted_bytes(&self) -> &[u8] {
+        &self.payload
+    }
+
+    pub fn ct_eq(&self, other: &Self) -> bool {
+        self.payload.ct_eq(&other.payload).into()
+    }
+}
+
+impl PartialEq for Nano128Encrypted {
+    fn eq(&self, other: &Self) -> bool {
+        self.ct_eq(other)
+    }
+}
+
+impl Eq for Nano128Encrypted {}
+
+impl Hash for Nano128Encrypted {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.payload.hash(state);
+    }
+}
+
+impl fmt::Display for Nano128Encrypted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_encrypted_hex())
+    }
+}
+
+// NIST SP 800-38D limit on GCM encryptions under one key with random IVs.
+pub const GCM_RANDOM_IV_LIMIT: u64 = 1 << 32;
+
+// How a factory picks the 12-byte IV for each encryption.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum NonceStrategy {
+    // 12 bytes from the OS RNG. Collisions become a concern only after billions of encryptions
+    // under one key (~2^32 for a 2^-32 chance).
+    #[default]
+    Random,
+    // A random 96-bit starting point, drawn when the strategy is set, counted up by one per
+    // encryption: a 4-byte prefix and an 8-byte counter starting at a random offset. IVs never
+    // repeat within the factory, for up to 2^64 encryptions. Factories sharing a key, across
+    // restarts and replicas, collide only if their ranges overlap, which is as unlikely as with
+    // random IVs: about n^2 / 2^96 for n encryptions in total.
+    Counter,
+}
+
+type UsageCallback = Box<dyn Fn(u64) + Send + Sync>;
+
+// Encrypts IDs into `[version][key id][iv][ciphertext][tag]` payloads. The cipher is pluggable
+// through `AeadBackend`; by default it is AES-GCM.
+//
+// Payloads from before the header was introduced, `[iv][ciphertext][tag]` only, are still
+// accepted: they are recognised by their length (`LEGACY_PAYLOAD_LENGTH`), which no versioned
+// payload shares, and tried against every key.
+pub struct Nano64EncryptionFactory<B: AeadBackend = AesGcm> {
+    pub(crate) keys: Keyring<B>,
+    // Keys tried, in order, when the keyring key for a payload's key ID fails to decrypt it, or
+    // when the keyring has no key with that ID. Legacy payloads try them after every keyring key.
+    pub(crate) fallbacks: Vec<B>,
+    pub(crate) nonce: NonceStrategy,
+    pub(crate) nonce_prefix: [u8; 4],
+    pub(crate) nonce_offset: u64,
+    // Counter IVs used so far; the IV's low 8 bytes are `nonce_offset` plus this.
+    pub(crate) nonce_counter: AtomicU64,
+    // Encryptions so far under the current key, and the limit set with `with_usage_limit`.
+    pub(crate) usage: AtomicU64,
+    pub(crate) usage_limit: Option<u64>,
+    pub(crate) usage_warning: Option<(u64, UsageCallback)>,
+    // Set by `with_truncated_tag`.
+    pub(crate) truncated_tag: bool,
+    // Set by `with_blind_index_key`.
+    pub(crate) index_mac: Option<KeyedHmac>,
+    pub(crate) clock: Box<dyn Clock>,
+    pub(crate) rng: Mutex<Box<dyn RandomSource>>,
 }
 
 impl Nano64EncryptionFactory {
+    // An AES-256-GCM factory; `aes_key` must be 32 bytes.
     pub fn new(
         aes_key: &[u8],
         clock: Option<ClockImpl>,
         rng: Option<RandomNumberGeneratorImpl>,
     ) -> Result<Self, Nano64Error> {
-        if aes_key.len() != 32 {
-            return Err(Nano64Error::Error("AES-256 key must be 32 bytes!".into()));
-        }
+        Self::new_with_algorithm(GcmAlgorithm::Aes256, aes_key, clock, rng)
+    }
+
+    // Fails with `InvalidKeyLength` unless `aes_key` is `algorithm.key_length()` bytes.
+    pub fn new_with_algorithm(
+        algorithm: GcmAlgorithm,
+        aes_key: &[u8],
+        clock: Option<ClockImpl>,
+        rng: Option<RandomNumberGeneratorImpl>,
+    ) -> Result<Self, Nano64Error> {
+        Ok(Self::with_backend(
+            AesGcm::new(algorithm, aes_key)?,
+            clock,
+            rng,
+        ))
+    }
+
+    pub fn algorithm(&self) -> GcmAlgorithm {
+        self.backend().algorithm()
+    }
 
+    // Adds AES-256 keys that decryption falls back to, in order, when the primary key fails.
+    // Meant for emergency rotation: encrypt with the new key and keep the old one here until
+    // the tokens it issued have expired.
+    pub fn with_fallback_keys(self, keys: &[[u8; 32]]) -> Self {
+        keys.iter().fold(self, |factory, key| {
+            factory.with_fallback_backend(
+                AesGcm::new(GcmAlgorithm::Aes256, key).expect("32 bytes is an AES-256 key"),
+            )
+        })
+    }
+
+    // Writes compact payloads that keep only `TRUNCATED_TAG_LENGTH` bytes of the tag, 30 bytes
+    // instead of 38 for a `Nano64`, for QR codes, SMS links and NFC tags. The tradeoff is
+    // authenticity: a forged payload gets through with probability 2^-64 per attempt instead of
+    // 2^-128, so rate-limit decryption wherever attackers can submit payloads.
+    //
+    // Only the AES-GCM backend can verify a truncated tag, so other backends can't opt in.
+    pub fn with_truncated_tag(mut self) -> Self {
+        self.truncated_tag = true;
+        self
+    }
+}
+
+impl<B: AeadBackend> Nano64EncryptionFactory<B> {
+    // A factory encrypting through any `AeadBackend`, e.g. a wrapper around an HSM.
+    pub fn with_backend(
+        backend: B,
+        clock: Option<ClockImpl>,
+        rng: Option<RandomNumberGeneratorImpl>,
+    ) -> Self {
+        Self::with_keyring(Keyring::new(0, backend), clock, rng)
+    }
+
+    // A factory that encrypts with the keyring's current key. Payloads record the key's ID, so
+    // they still decrypt after the key is retired. Single-key factories use key ID 0.
+    pub fn with_keyring(
+        keys: Keyring<B>,
+        clock: Option<ClockImpl>,
+        rng: Option<RandomNumberGeneratorImpl>,
+    ) -> Self {
         let rng = if let Some(_rng) = rng {
             _rng
         } else {
             default_rng
         };
 
-        let clock = if let Some(_clock) = clock {
-            _clock
+        let clock: Box<dyn Clock> = if let Some(_clock) = clock {
+            Box::new(_clock)
         } else {
-            time_now_since_epoch_ms
+            Box::new(time_now_since_epoch_ms as ClockImpl)
         };
 
-        let key = Key::<Aes256Gcm>::from_slice(aes_key);
-        let gcm = Aes256Gcm::new(key);
+        Self {
+            keys,
+            fallbacks: Vec::new(),
+            nonce: NonceStrategy::Random,
+            nonce_prefix: [0; 4],
+            nonce_offset: 0,
+            nonce_counter: AtomicU64::new(0),
+            usage: AtomicU64::new(0),
+            usage_limit: None,
+            usage_warning: None,
+            truncated_tag: false,
+            index_mac: None,
+            clock,
+            rng: Mutex::new(Box::new(rng)),
+        }
+    }
+
+    // Like `with_fallback_keys`, for any backend.
+    pub fn with_fallback_backend(mut self, backend: B) -> Self {
+        self.fallbacks.push(backend);
+        self
+    }
+
+    // The backend new payloads are encrypted with.
+    pub fn backend(&self) -> &B {
+        self.keys.current()
+    }
+
+    pub fn keyring(&self) -> &Keyring<B> {
+        &self.keys
+    }
+
+    // Switches how IVs are chosen. Setting `Counter` draws a fresh prefix and restarts the
+    // counter.
+    pub fn with_nonce_strategy(mut self, strategy: NonceStrategy) -> Self {
+        self.nonce = strategy;
+        OsRng.fill_bytes(&mut self.nonce_prefix);
+        self.nonce_offset = OsRng.next_u64();
+        self.nonce_counter = AtomicU64::new(0);
+        self
+    }
+
+    pub fn nonce_strategy(&self) -> NonceStrategy {
+        self.nonce
+    }
+
+    // Fails encryptions with `KeyUsageExceeded` once the current key has encrypted `limit`
+    // payloads. Defaults to `GCM_RANDOM_IV_LIMIT` with random IVs and no limit beyond the
+    // counter's with `NonceStrategy::Counter`. Counts are per factory: factories sharing a key
+    // should split the budget between them.
+    pub fn with_usage_limit(mut self, limit: u64) -> Self {
+        self.usage_limit = Some(limit);
+        self
+    }
+
+    // Calls `callback` with the count, once, when the current key reaches `threshold`
+    // encryptions, e.g. to schedule a key rotation before the limit is hit.
+    pub fn with_rotation_callback(
+        mut self,
+        threshold: u64,
+        callback: impl Fn(u64) + Send + Sync + 'static,
+    ) -> Self {
+        self.usage_warning = Some((threshold, Box::new(callback)));
+        self
+    }
+
+    // Encryptions performed with the current key.
+    pub fn encryptions(&self) -> u64 {
+        self.usage.load(Ordering::Relaxed)
+    }
+
+    pub fn usage_limit(&self) -> u64 {
+        self.usage_limit.unwrap_or(match self.nonce {
+            NonceStrategy::Random => GCM_RANDOM_IV_LIMIT,
+            NonceStrategy::Counter => u64::MAX,
+        })
+    }
 
-        Ok(Self { gcm, clock, rng })
+    // Bytes of tag in the payloads this factory writes.
+    pub fn tag_length(&self) -> usize {
+        if self.truncated_tag {
+            TRUNCATED_TAG_LENGTH
+        } else {
+            TAG_LENGTH
+        }
+    }
+
+    // Replaces the clock used by `generate_encrypted_now` with any `Clock`.
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Box::new(clock);
+        self
+    }
+
+    // Replaces the RNG used for new IDs with any `RandomSource`, e.g. `RngSource(your_rng)`.
+    pub fn with_random_source(mut self, rng: impl RandomSource + 'static) -> Self {
+        self.rng = Mutex::new(Box::new(rng));
+        self
     }
 
     pub fn encrypt(&self, id: Nano64) -> Result<Nano64Encrypted, Nano64Error> {
-        let iv = self.generate_iv();
-        let nonce = GenericArray::clone_from_slice(&iv);
-        let plaintext = id.value.to_be_bytes();
-        let ciphertext = self
-            .gcm
-            .encrypt(&nonce, plaintext.as_ref())
-            .map_err(|e| Nano64Error::Error(format!("Error during encryption! {e}")))?;
-
-        if ciphertext.len() != 8 + 16 {
+        self.encrypt_with_metadata(id, &[])
+    }
+
+    // Seals up to `MAX_METADATA_LENGTH` caller-supplied bytes, e.g. a record type or tenant
+    // shard, next to the ID. They are encrypted and authenticated with it and come back from
+    // `Nano64Encrypted::metadata` on decryption.
+    pub fn encrypt_with_metadata(
+        &self,
+        id: Nano64,
+        metadata: &[u8],
+    ) -> Result<Nano64Encrypted, Nano64Error> {
+        if metadata.len() > MAX_METADATA_LENGTH {
             return Err(Nano64Error::Error(format!(
-                "unexpected AES-GCM output length: {}",
-                ciphertext.len()
+                "metadata must be at most {MAX_METADATA_LENGTH} bytes, got {}",
+                metadata.len()
             )));
         }
-
-        let mut payload = [0u8; PAYLOAD_LENGTH];
-        payload[..IV_LENGTH].copy_from_slice(&iv);
-        payload[IV_LENGTH..].copy_from_slice(&ciphertext);
-
+        let mut plaintext = [0u8; 8 + MAX_METADATA_LENGTH];
+        plaintext[..8].copy_from_slice(&id.value.to_be_bytes());
+        plaintext[8..8 + metadata.len()].copy_from_slice(metadata);
         Ok(Nano64Encrypted {
             id,
-            payload,
-            gcm: self.gcm.clone(),
+            metadata: metadata.to_vec(),
+            payload: self.seal(&plaintext[..8 + metadata.len()], &[])?,
         })
     }
 
     pub fn generate_encrypted(&self, timestamp: u64) -> Result<Nano64Encrypted, Nano64Error> {
         let mut ts = timestamp;
         if ts == 0 {
-            ts = (self.clock)();
+            ts = self.clock.now_ms();
         }
-        let id = Nano64::generate(ts, Some(self.rng))?;
+        let id = {
+            let mut rng = self
+                .rng
+                .lock()
+                .map_err(|_| Nano64Error::Error("Error unlocking rng".into()))?;
+            Nano64::generate_with_source(ts, rng.as_mut())?
+        };
         self.encrypt(id)
     }
 
     pub fn generate_encrypted_now(&self) -> Result<Nano64Encrypted, Nano64Error> {
-        self.generate_encrypted((self.clock)())
+        self.generate_encrypted(self.clock.now_ms())
     }
 
     #[allow(clippy::wrong_self_convention)]
     pub fn from_encrypted_bytes(&self, bytes: &[u8]) -> Result<Nano64Encrypted, Nano64Error> {
-        if bytes.len() != PAYLOAD_LENGTH {
-            return Err(Nano64Error::Error(format!(
-                "encrypted payload must be {} bytes, got {}",
-                PAYLOAD_LENGTH,
-                bytes.len()
-            )));
-        }
-
-        // Split into IV and ciphertext
-        let iv = &bytes[..IV_LENGTH];
-        let ciphertext = &bytes[IV_LENGTH..];
-
-        // Decrypt
-        let nonce = GenericArray::from_slice(iv);
-        let plaintext = self
-            .gcm
-            .decrypt(nonce, ciphertext)
-            .map_err(|_| Nano64Error::Error("decryption failed".into()))?;
-
-        if plaintext.len() != 8 {
+        let plaintext = self.open(bytes, 8..=8 + MAX_METADATA_LENGTH, &[])?;
+        if plaintext.len() < 8 {
             return Err(Nano64Error::Error(format!(
                 "decryption yielded invalid length: {}",
                 plaintext.len()
@@ -128,43 +483,311 @@ impl Nano64EncryptionFactory {
         }
 
         let mut arr = [0u8; 8];
-        arr.copy_from_slice(&plaintext);
+        arr.copy_from_slice(&plaintext[..8]);
         let value = u64::from_be_bytes(arr);
-
-        let mut payload = [0u8; PAYLOAD_LENGTH];
-        payload.copy_from_slice(bytes);
+        let metadata = plaintext[8..].to_vec();
 
         Ok(Nano64Encrypted {
             id: Nano64 { value },
-            payload,
-            gcm: self.gcm.clone(),
+            metadata,
+            payload: bytes.to_vec(),
         })
     }
 
+    pub fn decrypt(
+        &self,
+        payload: &Nano64EncryptedPayload,
+    ) -> Result<Nano64Encrypted, Nano64Error> {
+        self.from_encrypted_bytes(payload.as_bytes())
+    }
+
+    // Accepts either case, surrounding whitespace and a `0x` prefix.
     #[allow(clippy::wrong_self_convention)]
     pub fn from_encrypted_hex(&self, hex: String) -> Result<Nano64Encrypted, Nano64Error> {
-        let bytes = Hex::to_bytes(hex.as_str())?;
-        if bytes.len() != PAYLOAD_LENGTH {
+        self.from_encrypted_bytes(&Hex::to_bytes_lenient(hex.as_str())?)
+    }
+
+    // Accepts only the exact form `to_encrypted_hex` writes.
+    #[allow(clippy::wrong_self_convention)]
+    pub fn from_encrypted_hex_strict(&self, hex: &str) -> Result<Nano64Encrypted, Nano64Error> {
+        self.from_encrypted_bytes(&Hex::to_bytes_strict(hex)?)
+    }
+
+    #[allow(clippy::wrong_self_convention)]
+    pub fn from_encrypted_slug(&self, slug: &str) -> Result<Nano64Encrypted, Nano64Error> {
+        self.from_encrypted_bytes(&Base64Url::decode(slug)?)
+    }
+
+    // Decrypts `payload` with this factory's keys and encrypts the ID and its metadata again
+    // with `target`'s current key, e.g. when migrating stored payloads to a new key.
+    pub fn re_encrypt<C: AeadBackend>(
+        &self,
+        payload: &[u8],
+        target: &Nano64EncryptionFactory<C>,
+    ) -> Result<Nano64Encrypted, Nano64Error> {
+        let decrypted = self.from_encrypted_bytes(payload)?;
+        target.encrypt_with_metadata(decrypted.id, decrypted.metadata())
+    }
+
+    // `re_encrypt` for many payloads. Each payload gets its own result, so one corrupt row
+    // doesn't stop a migration.
+    pub fn re_encrypt_batch<C: AeadBackend, P: AsRef<[u8]>>(
+        &self,
+        payloads: impl IntoIterator<Item = P>,
+        target: &Nano64EncryptionFactory<C>,
+    ) -> Vec<Result<Nano64Encrypted, Nano64Error>> {
+        payloads
+            .into_iter()
+            .map(|payload| self.re_encrypt(payload.as_ref(), target))
+            .collect()
+    }
+
+    pub fn encrypt_128(&self, id: Nano128) -> Result<Nano128Encrypted, Nano64Error> {
+        let payload = self.seal(&id.to_bytes(), &[])?;
+        Ok(Nano128Encrypted { id, payload })
+    }
+
+    pub fn generate_encrypted_128_now(&self) -> Result<Nano128Encrypted, Nano64Error> {
+        let id = {
+            let mut rng = self
+                .rng
+                .lock()
+                .map_err(|_| Nano64Error::Error("Error unlocking rng".into()))?;
+            Nano128::generate_with_source(self.clock.now_ms(), rng.as_mut())?
+        };
+        self.encrypt_128(id)
+    }
+
+    #[allow(clippy::wrong_self_convention)]
+    pub fn from_encrypted_bytes_128(&self, bytes: &[u8]) -> Result<Nano128Encrypted, Nano64Error> {
+        let plaintext = self.open(bytes, 16..=16, &[])?;
+        let id = Nano128::try_from(plaintext.as_slice())?;
+        Ok(Nano128Encrypted {
+            id,
+            payload: bytes.to_vec(),
+        })
+    }
+
+    #[allow(clippy::wrong_self_convention)]
+    pub fn from_encrypted_hex_128(&self, hex: &str) -> Result<Nano128Encrypted, Nano64Error> {
+        self.from_encrypted_bytes_128(&Hex::to_bytes_lenient(hex)?)
+    }
+
+    #[allow(clippy::wrong_self_convention)]
+    pub fn from_encrypted_slug_128(&self, slug: &str) -> Result<Nano128Encrypted, Nano64Error> {
+        self.from_encrypted_bytes_128(&Base64Url::decode(slug)?)
+    }
+
+    // Encrypts `plaintext` with the current key into a versioned payload. `aad` keeps payloads
+    // of different kinds but equal length from decrypting as one another.
+    pub(crate) fn seal(&self, plaintext: &[u8], aad: &[u8]) -> Result<Vec<u8>, Nano64Error> {
+        self.record_usage()?;
+        let iv = self.generate_iv()?;
+        let header = [
+            if self.truncated_tag {
+                TRUNCATED_PAYLOAD_VERSION
+            } else {
+                PAYLOAD_VERSION
+            },
+            self.keys.current_key_id(),
+        ];
+        // The header is authenticated too, so it can't be rewritten to pick another key or tag
+        // length without failing the tag check.
+        let mut ciphertext =
+            self.keys
+                .current()
+                .encrypt(&iv, &[&header[..], aad].concat(), plaintext)?;
+        if ciphertext.len() != plaintext.len() + TAG_LENGTH {
             return Err(Nano64Error::Error(format!(
-                "Encrypted payload must be {} len, got {}",
-                PAYLOAD_LENGTH,
-                bytes.len()
+                "unexpected AEAD output length: {}",
+                ciphertext.len()
             )));
         }
-        self.from_encrypted_bytes(&bytes)
+        ciphertext.truncate(plaintext.len() + self.tag_length());
+        let mut payload = Vec::with_capacity(PAYLOAD_HEADER_LENGTH + IV_LENGTH + ciphertext.len());
+        payload.extend_from_slice(&header);
+        payload.extend_from_slice(&iv);
+        payload.extend_from_slice(&ciphertext);
+        Ok(payload)
     }
 
-    fn generate_iv(&self) -> [u8; IV_LENGTH] {
+    // Decrypts a versioned payload sealed with `aad` around a plaintext of `plaintext_length`
+    // bytes. Legacy payloads, always sealed without AAD around the shortest such plaintext, are
+    // also accepted.
+    pub(crate) fn open(
+        &self,
+        payload: &[u8],
+        plaintext_length: RangeInclusive<usize>,
+        aad: &[u8],
+    ) -> Result<Vec<u8>, Nano64Error> {
+        let legacy_length = IV_LENGTH + plaintext_length.start() + TAG_LENGTH;
+        if aad.is_empty() && payload.len() == legacy_length {
+            // No key ID to go by, so the first key whose tag matches wins.
+            return self.open_any(
+                self.keys.iter().map(|(_, backend)| backend),
+                payload,
+                aad,
+                false,
+            );
+        }
+
+        let Some(&version) = payload.first() else {
+            return Err(Nano64Error::Error(format!(
+                "encrypted payload must be {} bytes, got 0",
+                legacy_length + PAYLOAD_HEADER_LENGTH
+            )));
+        };
+        let truncated = match version {
+            PAYLOAD_VERSION => false,
+            TRUNCATED_PAYLOAD_VERSION => true,
+            _ => return Err(Nano64Error::UnsupportedPayloadVersion(version)),
+        };
+        let overhead = PAYLOAD_HEADER_LENGTH
+            + IV_LENGTH
+            + if truncated {
+                TRUNCATED_TAG_LENGTH
+            } else {
+                TAG_LENGTH
+            };
+        let (shortest, longest) = (
+            plaintext_length.start() + overhead,
+            plaintext_length.end() + overhead,
+        );
+        if !(shortest..=longest).contains(&payload.len()) {
+            let expected = if shortest == longest {
+                shortest.to_string()
+            } else {
+                format!("{shortest} to {longest}")
+            };
+            return Err(Nano64Error::Error(format!(
+                "encrypted payload must be {expected} bytes, got {}",
+                payload.len()
+            )));
+        }
+
+        let key_id = payload[1];
+        let (header, body) = payload.split_at(PAYLOAD_HEADER_LENGTH);
+        let aad = &[header, aad].concat();
+        match self.keys.get(key_id) {
+            Some(backend) => {
+                #[cfg(feature = "tracing")]
+                if key_id != self.keys.current_key_id() {
+                    tracing::debug!(key_id, "decrypting payload encrypted under a retired key");
+                }
+                self.open_any(std::iter::once(backend), body, aad, truncated)
+            }
+            None if self.fallbacks.is_empty() => Err(Nano64Error::UnknownKeyId(key_id)),
+            None => self.open_any(std::iter::empty(), body, aad, truncated),
+        }
+    }
+
+    // Tries `backends` and then the fallback keys, in order, until one's tag matches.
+    fn open_any<'a>(
+        &'a self,
+        backends: impl Iterator<Item = &'a B>,
+        body: &[u8],
+        aad: &[u8],
+        truncated: bool,
+    ) -> Result<Vec<u8>, Nano64Error> {
+        let mut candidates = backends.chain(&self.fallbacks);
+        let Some(first) = candidates.next() else {
+            return Err(Nano64Error::Error("no key to decrypt with".into()));
+        };
+        let mut result = Self::open_with(first, body, aad, truncated);
+        for backend in candidates {
+            if result.is_ok() {
+                break;
+            }
+            result = Self::open_with(backend, body, aad, truncated);
+            #[cfg(feature = "tracing")]
+            if result.is_ok() {
+                tracing::debug!("decrypted payload with a key other than the first candidate");
+            }
+        }
+        result
+    }
+
+    // Decrypts a `[iv][ciphertext][tag]` payload of any length of at least `IV_LENGTH`, with a
+    // full tag or, if `truncated`, a `TRUNCATED_TAG_LENGTH` one.
+    fn open_with(
+        backend: &B,
+        payload: &[u8],
+        aad: &[u8],
+        truncated: bool,
+    ) -> Result<Vec<u8>, Nano64Error> {
+        if payload.len() < IV_LENGTH {
+            return Err(Nano64Error::Error("encrypted payload is too short".into()));
+        }
+        let (iv, ciphertext) = payload.split_at(IV_LENGTH);
+        let iv: &[u8; IV_LENGTH] = iv.try_into().expect("split at IV_LENGTH");
+        let result = match ciphertext.len().checked_sub(TRUNCATED_TAG_LENGTH) {
+            Some(split) if truncated => {
+                let (ciphertext, tag) = ciphertext.split_at(split);
+                backend.decrypt_truncated(iv, aad, ciphertext, tag)
+            }
+            None if truncated => Err(Nano64Error::Error("encrypted payload is too short".into())),
+            _ => backend.decrypt(iv, aad, ciphertext),
+        };
+        result.inspect_err(|_| {
+            // Not logged at a higher level: anyone can submit garbage payloads.
+            #[cfg(feature = "tracing")]
+            tracing::debug!("decryption failed");
+        })
+    }
+
+    // Counts an encryption against the current key's budget, refusing it once the limit is hit.
+    fn record_usage(&self) -> Result<(), Nano64Error> {
+        let limit = self.usage_limit();
+        let count = self
+            .usage
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |c| {
+                (c < limit).then_some(c + 1)
+            })
+            .map_err(|_| Nano64Error::KeyUsageExceeded { limit })?
+            + 1;
+        if let Some((threshold, callback)) = &self.usage_warning
+            && count == *threshold
+        {
+            #[cfg(feature = "tracing")]
+            tracing::warn!(
+                count,
+                limit,
+                "encryption key is approaching its usage limit"
+            );
+            callback(count);
+        }
+        Ok(())
+    }
+
+    fn generate_iv(&self) -> Result<[u8; IV_LENGTH], Nano64Error> {
         let mut iv = [0u8; IV_LENGTH];
-        OsRng.fill_bytes(&mut iv);
-        iv
+        match self.nonce {
+            NonceStrategy::Random => OsRng.fill_bytes(&mut iv),
+            NonceStrategy::Counter => {
+                let count = self
+                    .nonce_counter
+                    .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |c| c.checked_add(1))
+                    .map_err(|_| {
+                        Nano64Error::Error(
+                            "nonce counter exhausted; build a new factory to continue".into(),
+                        )
+                    })?;
+                iv[..4].copy_from_slice(&self.nonce_prefix);
+                iv[4..].copy_from_slice(&self.nonce_offset.wrapping_add(count).to_be_bytes());
+            }
+        }
+        Ok(iv)
     }
 }
 
 #[cfg(test)]
 mod tests {
 
-    use crate::{Nano64, Nano64EncryptionFactory, PAYLOAD_LENGTH};
+    use crate::{
+        AeadBackend, AesGcm, GcmAlgorithm, IV_LENGTH, LEGACY_PAYLOAD_LENGTH, Nano64,
+        Nano64EncryptionFactory, Nano64Error, PAYLOAD_LENGTH,
+    };
+    use std::sync::atomic::{AtomicUsize, Ordering};
 
     #[test]
     fn test_nano64_encrypted_complete() {
@@ -182,6 +805,31 @@ mod tests {
         assert!(decrypted_from_bytes.id.equals(&encrypted.id));
     }
 
+    #[test]
+    fn test_nano64_encrypted_slug() {
+        let key = [7u8; 32];
+        let factory = Nano64EncryptionFactory::new(&key, None, None).unwrap();
+        let encrypted = factory.generate_encrypted_now().unwrap();
+        let slug = encrypted.to_encrypted_slug();
+        assert_eq!(slug.len(), 51);
+        let decrypted = factory.from_encrypted_slug(&slug).unwrap();
+        assert_eq!(decrypted.id, encrypted.id);
+        assert!(factory.from_encrypted_slug(&slug[..44]).is_err());
+    }
+
+    #[test]
+    fn test_nano64_encrypted_with_random_source() {
+        let mut next = 0;
+        let factory = Nano64EncryptionFactory::new(&[9u8; 32], None, None)
+            .unwrap()
+            .with_random_source(move |_bits: u32| {
+                next += 1;
+                Ok(next)
+            });
+        assert_eq!(factory.generate_encrypted(1000).unwrap().id.get_random(), 1);
+        assert_eq!(factory.generate_encrypted(1000).unwrap().id.get_random(), 2);
+    }
+
     #[test]
     fn test_nano64_encrypted_generate_encrypted() {
         let key: [u8; 32] = [
@@ -217,7 +865,7 @@ mod tests {
         ];
         let factory = Nano64EncryptionFactory::new(&key, None, None).unwrap();
         let id = Nano64::generate_default().unwrap();
-        let encrypted = factory.encrypt(id.clone()).unwrap();
+        let encrypted = factory.encrypt(id).unwrap();
         assert!(encrypted.id.equals(&id));
     }
 
@@ -281,7 +929,7 @@ mod tests {
             24, 69, 39, 27, 28, 29, 30, 66, 32,
         ];
         let factory = Nano64EncryptionFactory::new(&key, None, None).unwrap();
-        let invalid_payload: [u8; PAYLOAD_LENGTH] = [
+        let invalid_payload: [u8; LEGACY_PAYLOAD_LENGTH] = [
             1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
             1, 1, 1, 1, 1, 1, 1,
         ];
@@ -303,7 +951,7 @@ mod tests {
         ];
         let factory = Nano64EncryptionFactory::new(&key, None, None).unwrap();
         let id = Nano64::generate_default().unwrap();
-        let mut encrypted = if let Ok(got) = factory.encrypt(id.clone()) {
+        let mut encrypted = if let Ok(got) = factory.encrypt(id) {
             got
         } else {
             panic!("Normal encryption should work")
@@ -311,7 +959,7 @@ mod tests {
 
         encrypted.id.value = 1;
 
-        if let Ok(got) = factory.encrypt(encrypted.id.clone()) {
+        if let Ok(got) = factory.encrypt(encrypted.id) {
             got
         } else {
             panic!("ahh");
@@ -347,4 +995,666 @@ mod tests {
             "Encrypted payload has incorrect len"
         );
     }
+
+    #[test]
+    fn test_nano64_encrypted_aes128() {
+        use crate::{GcmAlgorithm, Nano64Error};
+
+        let factory = Nano64EncryptionFactory::new_with_algorithm(
+            GcmAlgorithm::Aes128,
+            &[9u8; 16],
+            None,
+            None,
+        )
+        .unwrap();
+        assert_eq!(factory.algorithm(), GcmAlgorithm::Aes128);
+        let encrypted = factory.generate_encrypted_now().unwrap();
+        assert_eq!(encrypted.to_encrypted_bytes().len(), PAYLOAD_LENGTH);
+        let decrypted = factory
+            .from_encrypted_bytes(&encrypted.to_encrypted_bytes())
+            .unwrap();
+        assert_eq!(decrypted.id, encrypted.id);
+
+        // A same-looking key for the other algorithm can't open it.
+        let aes256 = Nano64EncryptionFactory::new(&[9u8; 32], None, None).unwrap();
+        assert_eq!(aes256.algorithm(), GcmAlgorithm::Aes256);
+        assert!(
+            aes256
+                .from_encrypted_bytes(&encrypted.to_encrypted_bytes())
+                .is_err()
+        );
+
+        assert!(matches!(
+            Nano64EncryptionFactory::new(&[0u8; 16], None, None),
+            Err(Nano64Error::InvalidKeyLength {
+                algorithm: GcmAlgorithm::Aes256,
+                expected: 32,
+                got: 16,
+            })
+        ));
+        let err = Nano64EncryptionFactory::new_with_algorithm(
+            GcmAlgorithm::Aes128,
+            &[0u8; 32],
+            None,
+            None,
+        )
+        .err()
+        .unwrap();
+        assert_eq!(err.to_string(), "AES-128-GCM key must be 16 bytes, got 32");
+    }
+
+    #[test]
+    fn test_nano128_encryption_round_trip() {
+        use crate::{NANO128_PAYLOAD_LENGTH, Nano128};
+
+        let factory = Nano64EncryptionFactory::new(&[7u8; 32], None, None).unwrap();
+        let encrypted = factory.generate_encrypted_128_now().unwrap();
+        assert_eq!(encrypted.to_encrypted_bytes().len(), NANO128_PAYLOAD_LENGTH);
+
+        let id = encrypted.id;
+        let from_bytes = factory
+            .from_encrypted_bytes_128(&encrypted.to_encrypted_bytes())
+            .unwrap();
+        assert_eq!(from_bytes.id, id);
+        let from_hex = factory
+            .from_encrypted_hex_128(&encrypted.to_encrypted_hex())
+            .unwrap();
+        assert_eq!(from_hex.id, id);
+        let from_slug = factory
+            .from_encrypted_slug_128(&encrypted.to_encrypted_slug())
+            .unwrap();
+        assert_eq!(from_slug.id, id);
+
+        let mut tampered = encrypted.to_encrypted_bytes();
+        tampered[NANO128_PAYLOAD_LENGTH - 1] ^= 1;
+        assert!(factory.from_encrypted_bytes_128(&tampered).is_err());
+        assert!(factory.from_encrypted_bytes_128(&tampered[1..]).is_err());
+        assert_ne!(
+            factory.encrypt_128(Nano128::new(1)).unwrap().payload,
+            factory.encrypt_128(Nano128::new(1)).unwrap().payload
+        );
+    }
+
+    // Stand-in for an external provider: delegates to AES-GCM and counts calls.
+    struct CountingBackend {
+        inner: AesGcm,
+        calls: AtomicUsize,
+    }
+
+    impl AeadBackend for CountingBackend {
+        fn encrypt(
+            &self,
+            iv: &[u8; IV_LENGTH],
+            aad: &[u8],
+            plaintext: &[u8],
+        ) -> Result<Vec<u8>, Nano64Error> {
+            self.calls.fetch_add(1, Ordering::Relaxed);
+            self.inner.encrypt(iv, aad, plaintext)
+        }
+
+        fn decrypt(
+            &self,
+            iv: &[u8; IV_LENGTH],
+            aad: &[u8],
+            ciphertext: &[u8],
+        ) -> Result<Vec<u8>, Nano64Error> {
+            self.calls.fetch_add(1, Ordering::Relaxed);
+            self.inner.decrypt(iv, aad, ciphertext)
+        }
+    }
+
+    // Returns plaintext without a tag, which the factory must refuse to store.
+    struct TaglessBackend;
+
+    impl AeadBackend for TaglessBackend {
+        fn encrypt(
+            &self,
+            _iv: &[u8; IV_LENGTH],
+            _aad: &[u8],
+            plaintext: &[u8],
+        ) -> Result<Vec<u8>, Nano64Error> {
+            Ok(plaintext.to_vec())
+        }
+
+        fn decrypt(
+            &self,
+            _iv: &[u8; IV_LENGTH],
+            _aad: &[u8],
+            ciphertext: &[u8],
+        ) -> Result<Vec<u8>, Nano64Error> {
+            Ok(ciphertext.to_vec())
+        }
+    }
+
+    #[test]
+    fn test_custom_backend() {
+        let key = [9u8; 32];
+        let backend = CountingBackend {
+            inner: AesGcm::new(GcmAlgorithm::Aes256, &key).unwrap(),
+            calls: AtomicUsize::new(0),
+        };
+        let factory = Nano64EncryptionFactory::with_backend(backend, None, None);
+        let encrypted = factory.generate_encrypted_now().unwrap();
+        let restored = factory
+            .from_encrypted_bytes(&encrypted.to_encrypted_bytes())
+            .unwrap();
+        assert_eq!(restored.id, encrypted.id);
+        assert_eq!(factory.backend().calls.load(Ordering::Relaxed), 2);
+
+        // Same payload format as the built-in backend.
+        let builtin = Nano64EncryptionFactory::new(&key, None, None).unwrap();
+        assert_eq!(
+            builtin
+                .from_encrypted_bytes(&encrypted.to_encrypted_bytes())
+                .unwrap()
+                .id,
+            encrypted.id
+        );
+
+        let tagless = Nano64EncryptionFactory::with_backend(TaglessBackend, None, None);
+        assert!(tagless.encrypt(Nano64::new(1)).is_err());
+    }
+
+    #[test]
+    fn test_key_rotation() {
+        use crate::Keyring;
+
+        let aes = |byte: u8| AesGcm::new(GcmAlgorithm::Aes256, &[byte; 32]).unwrap();
+        let single_key = Nano64EncryptionFactory::new(&[1u8; 32], None, None).unwrap();
+        let before = Nano64EncryptionFactory::with_keyring(Keyring::new(1, aes(1)), None, None);
+        let after = Nano64EncryptionFactory::with_keyring(
+            Keyring::new(2, aes(2)).with_retired_key(1, aes(1)),
+            None,
+            None,
+        );
+
+        let old = before.generate_encrypted_now().unwrap();
+        assert_eq!(old.to_encrypted_bytes().len(), PAYLOAD_LENGTH);
+        assert_eq!(old.to_encrypted_bytes()[1], 1);
+        assert_eq!(
+            after
+                .from_encrypted_bytes(&old.to_encrypted_bytes())
+                .unwrap()
+                .id,
+            old.id
+        );
+
+        let new = after.encrypt(old.id).unwrap();
+        assert_eq!(new.to_encrypted_bytes()[1], 2);
+        assert_eq!(after.keyring().current_key_id(), 2);
+        assert!(matches!(
+            before.from_encrypted_bytes(&new.to_encrypted_bytes()),
+            Err(Nano64Error::UnknownKeyId(2))
+        ));
+
+        // Single-key factories write key ID 0.
+        let single = single_key.encrypt(old.id).unwrap();
+        assert_eq!(single.to_encrypted_bytes()[1], 0);
+        assert!(matches!(
+            after.from_encrypted_bytes(&single.to_encrypted_bytes()),
+            Err(Nano64Error::UnknownKeyId(0))
+        ));
+        assert!(matches!(
+            single_key.from_encrypted_bytes(&new.to_encrypted_bytes()),
+            Err(Nano64Error::UnknownKeyId(2))
+        ));
+    }
+
+    #[test]
+    fn test_payload_versions() {
+        use crate::{Keyring, PAYLOAD_HEADER_LENGTH, PAYLOAD_VERSION, TRUNCATED_PAYLOAD_VERSION};
+
+        let aes = |byte: u8| AesGcm::new(GcmAlgorithm::Aes256, &[byte; 32]).unwrap();
+        let factory = Nano64EncryptionFactory::with_keyring(
+            Keyring::new(2, aes(2)).with_retired_key(1, aes(1)),
+            None,
+            None,
+        );
+        let encrypted = factory.generate_encrypted_now().unwrap();
+        let bytes = encrypted.to_encrypted_bytes();
+        assert_eq!(bytes[0], PAYLOAD_VERSION);
+
+        let mut future = bytes.clone();
+        future[0] = TRUNCATED_PAYLOAD_VERSION + 1;
+        assert!(matches!(
+            factory.from_encrypted_bytes(&future),
+            Err(Nano64Error::UnsupportedPayloadVersion(3))
+        ));
+
+        // Legacy payloads have no header and are tried against every key.
+        let iv = [4u8; IV_LENGTH];
+        let legacy = [
+            &iv[..],
+            &aes(1).encrypt(&iv, &[], &encrypted.id.to_bytes()).unwrap(),
+        ]
+        .concat();
+        assert_eq!(legacy.len(), LEGACY_PAYLOAD_LENGTH);
+        assert_eq!(
+            factory.from_encrypted_bytes(&legacy).unwrap().id,
+            encrypted.id
+        );
+        assert!(factory.from_encrypted_bytes(&bytes[1..]).is_err());
+        assert!(
+            factory
+                .from_encrypted_bytes(&bytes[PAYLOAD_HEADER_LENGTH..])
+                .is_err()
+        );
+
+        // The header is authenticated: relabelling the key ID fails even when both IDs hold
+        // the same key.
+        let relabelled = Nano64EncryptionFactory::with_keyring(
+            Keyring::new(2, aes(2)).with_retired_key(1, aes(2)),
+            None,
+            None,
+        );
+        assert!(relabelled.from_encrypted_bytes(&bytes).is_ok());
+        let mut tampered = bytes.clone();
+        tampered[1] = 1;
+        assert!(relabelled.from_encrypted_bytes(&tampered).is_err());
+    }
+
+    #[test]
+    fn test_fallback_keys() {
+        let old = Nano64EncryptionFactory::new(&[1u8; 32], None, None).unwrap();
+        let older = Nano64EncryptionFactory::new(&[2u8; 32], None, None).unwrap();
+        let factory = Nano64EncryptionFactory::new(&[3u8; 32], None, None)
+            .unwrap()
+            .with_fallback_keys(&[[1u8; 32], [2u8; 32]]);
+
+        for issuer in [&old, &older, &factory] {
+            let encrypted = issuer.generate_encrypted_now().unwrap();
+            let restored = factory
+                .from_encrypted_bytes(&encrypted.to_encrypted_bytes())
+                .unwrap();
+            assert_eq!(restored.id, encrypted.id);
+        }
+
+        // New payloads only ever use the primary key.
+        let id = factory.generate_encrypted_now().unwrap();
+        assert!(old.from_encrypted_bytes(&id.to_encrypted_bytes()).is_err());
+
+        let stranger = Nano64EncryptionFactory::new(&[4u8; 32], None, None).unwrap();
+        let unknown = stranger.generate_encrypted_now().unwrap();
+        assert!(
+            factory
+                .from_encrypted_bytes(&unknown.to_encrypted_bytes())
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_re_encrypt() {
+        let old = Nano64EncryptionFactory::new(&[1u8; 32], None, None).unwrap();
+        let new = Nano64EncryptionFactory::new(&[2u8; 32], None, None).unwrap();
+
+        let encrypted = old.generate_encrypted_now().unwrap();
+        let migrated = old
+            .re_encrypt(&encrypted.to_encrypted_bytes(), &new)
+            .unwrap();
+        assert_eq!(migrated.id, encrypted.id);
+        assert_eq!(
+            new.from_encrypted_bytes(&migrated.to_encrypted_bytes())
+                .unwrap()
+                .id,
+            encrypted.id
+        );
+        assert!(
+            old.from_encrypted_bytes(&migrated.to_encrypted_bytes())
+                .is_err()
+        );
+
+        let payloads = vec![
+            old.generate_encrypted_now().unwrap().to_encrypted_bytes(),
+            vec![0u8; PAYLOAD_LENGTH],
+            encrypted.to_encrypted_bytes(),
+        ];
+        let results = old.re_encrypt_batch(&payloads, &new);
+        assert_eq!(results.len(), 3);
+        assert!(results[0].is_ok());
+        assert!(results[1].is_err());
+        assert_eq!(results[2].as_ref().unwrap().id, encrypted.id);
+    }
+
+    #[test]
+    fn test_re_encrypt_keeps_metadata() {
+        let old = Nano64EncryptionFactory::new(&[1u8; 32], None, None).unwrap();
+        let new = Nano64EncryptionFactory::new(&[2u8; 32], None, None).unwrap();
+
+        let encrypted = old
+            .encrypt_with_metadata(Nano64::new(7), &[0xAB, 0xCD])
+            .unwrap();
+        let migrated = old
+            .re_encrypt(&encrypted.to_encrypted_bytes(), &new)
+            .unwrap();
+        assert_eq!(migrated.metadata(), &[0xAB, 0xCD]);
+        let restored = new
+            .from_encrypted_bytes(&migrated.to_encrypted_bytes())
+            .unwrap();
+        assert_eq!(restored.id, Nano64::new(7));
+        assert_eq!(restored.metadata(), &[0xAB, 0xCD]);
+    }
+
+    #[test]
+    fn test_encrypted_payload_display_and_parse() {
+        use crate::Nano64EncryptedPayload;
+
+        let factory = Nano64EncryptionFactory::new(&[5u8; 32], None, None).unwrap();
+        let encrypted = factory.generate_encrypted_now().unwrap();
+        let token = encrypted.to_string();
+        assert_eq!(token, encrypted.to_encrypted_hex());
+        assert!(!token.contains(&encrypted.id.to_hex()));
+
+        let payload: Nano64EncryptedPayload = token.parse().unwrap();
+        assert_eq!(payload, encrypted.to_payload());
+        assert_eq!(payload.to_string(), token);
+        assert_eq!(factory.decrypt(&payload).unwrap().id, encrypted.id);
+
+        assert!(matches!(
+            token[..70].parse::<Nano64EncryptedPayload>(),
+            Err(Nano64Error::InvalidByteLength { got: 35, .. })
+        ));
+        assert!("zz".parse::<Nano64EncryptedPayload>().is_err());
+        assert!(Nano64EncryptedPayload::from_bytes(&[0u8; LEGACY_PAYLOAD_LENGTH]).is_ok());
+    }
+
+    #[test]
+    fn test_encrypted_hex_leniency() {
+        let factory = Nano64EncryptionFactory::new(&[6u8; 32], None, None).unwrap();
+        let encrypted = factory.generate_encrypted_now().unwrap();
+        let hex = encrypted.to_encrypted_hex();
+
+        for input in [
+            hex.clone(),
+            hex.to_lowercase(),
+            format!("  {hex}\n"),
+            format!("0x{hex}"),
+            format!("0X{}", hex.to_lowercase()),
+        ] {
+            assert_eq!(factory.from_encrypted_hex(input).unwrap().id, encrypted.id);
+        }
+
+        assert_eq!(
+            factory.from_encrypted_hex_strict(&hex).unwrap().id,
+            encrypted.id
+        );
+        for input in [hex.to_lowercase(), format!("{hex} "), format!("0x{hex}")] {
+            assert!(matches!(
+                factory.from_encrypted_hex_strict(&input),
+                Err(Nano64Error::InvalidEncodedChar { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_counter_nonces() {
+        use crate::{NonceStrategy, PAYLOAD_HEADER_LENGTH};
+
+        let factory = Nano64EncryptionFactory::new(&[8u8; 32], None, None).unwrap();
+        assert_eq!(factory.nonce_strategy(), NonceStrategy::Random);
+        let factory = factory.with_nonce_strategy(NonceStrategy::Counter);
+
+        let ivs: Vec<Vec<u8>> = (0..3)
+            .map(|_| {
+                let encrypted = factory.generate_encrypted_now().unwrap();
+                assert!(
+                    factory
+                        .from_encrypted_bytes(&encrypted.to_encrypted_bytes())
+                        .is_ok()
+                );
+                encrypted.to_encrypted_bytes()
+                    [PAYLOAD_HEADER_LENGTH..PAYLOAD_HEADER_LENGTH + IV_LENGTH]
+                    .to_vec()
+            })
+            .collect();
+        for (i, iv) in ivs.iter().enumerate() {
+            assert_eq!(iv[..4], ivs[0][..4]);
+            assert_eq!(
+                iv[4..],
+                factory.nonce_offset.wrapping_add(i as u64).to_be_bytes()
+            );
+        }
+
+        // Factories on the same key, e.g. replicas or restarts, start at their own random
+        // points rather than all counting from zero.
+        let iv_set = |factory: &Nano64EncryptionFactory| -> std::collections::HashSet<Vec<u8>> {
+            (0..1000)
+                .map(|_| {
+                    factory
+                        .generate_encrypted_now()
+                        .unwrap()
+                        .to_encrypted_bytes()
+                        [PAYLOAD_HEADER_LENGTH..PAYLOAD_HEADER_LENGTH + IV_LENGTH]
+                        .to_vec()
+                })
+                .collect()
+        };
+        let replica = Nano64EncryptionFactory::new(&[8u8; 32], None, None)
+            .unwrap()
+            .with_nonce_strategy(NonceStrategy::Counter);
+        let (ours, theirs) = (iv_set(&factory), iv_set(&replica));
+        assert_eq!(ours.len(), 1000);
+        assert!(ours.is_disjoint(&theirs));
+
+        factory.nonce_counter.store(u64::MAX, Ordering::Relaxed);
+        assert!(factory.generate_encrypted_now().is_err());
+    }
+
+    #[test]
+    fn test_usage_limit() {
+        use crate::GCM_RANDOM_IV_LIMIT;
+        use std::sync::Arc;
+
+        let warned = Arc::new(AtomicUsize::new(0));
+        let seen = Arc::clone(&warned);
+        let factory = Nano64EncryptionFactory::new(&[9u8; 32], None, None).unwrap();
+        assert_eq!(factory.usage_limit(), GCM_RANDOM_IV_LIMIT);
+        let factory = factory
+            .with_usage_limit(3)
+            .with_rotation_callback(2, move |count| {
+                assert_eq!(count, 2);
+                seen.fetch_add(1, Ordering::Relaxed);
+            });
+
+        for _ in 0..3 {
+            factory.generate_encrypted_now().unwrap();
+        }
+        assert_eq!(factory.encryptions(), 3);
+        assert_eq!(warned.load(Ordering::Relaxed), 1);
+        assert!(matches!(
+            factory.generate_encrypted_now(),
+            Err(Nano64Error::KeyUsageExceeded { limit: 3 })
+        ));
+        assert_eq!(factory.encryptions(), 3);
+
+        // Decryption is not limited.
+        let other = Nano64EncryptionFactory::new(&[9u8; 32], None, None).unwrap();
+        let encrypted = other.generate_encrypted_now().unwrap();
+        assert!(
+            factory
+                .from_encrypted_bytes(&encrypted.to_encrypted_bytes())
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_truncated_tag() {
+        use crate::{
+            Nano64EncryptedPayload, Nano128, TRUNCATED_PAYLOAD_LENGTH, TRUNCATED_PAYLOAD_VERSION,
+        };
+
+        let full = Nano64EncryptionFactory::new(&[5u8; 32], None, None).unwrap();
+        let compact = Nano64EncryptionFactory::new(&[5u8; 32], None, None)
+            .unwrap()
+            .with_truncated_tag();
+        assert_eq!(compact.tag_length(), 8);
+        assert_eq!(full.tag_length(), 16);
+
+        let encrypted = compact.generate_encrypted_now().unwrap();
+        let bytes = encrypted.to_encrypted_bytes();
+        assert_eq!(bytes.len(), TRUNCATED_PAYLOAD_LENGTH);
+        assert_eq!(bytes[0], TRUNCATED_PAYLOAD_VERSION);
+        assert_eq!(encrypted.to_encrypted_slug().len(), 40);
+        assert_eq!(
+            compact.from_encrypted_bytes(&bytes).unwrap().id,
+            encrypted.id
+        );
+        let payload = Nano64EncryptedPayload::from_bytes(&bytes).unwrap();
+        assert_eq!(compact.decrypt(&payload).unwrap().id, encrypted.id);
+
+        // Any flipped bit past the header fails the tag check.
+        for i in 2..bytes.len() {
+            let mut tampered = bytes.clone();
+            tampered[i] ^= 0x01;
+            assert!(compact.from_encrypted_bytes(&tampered).is_err(), "byte {i}");
+        }
+        let other = Nano64EncryptionFactory::new(&[6u8; 32], None, None)
+            .unwrap()
+            .with_truncated_tag();
+        assert!(other.from_encrypted_bytes(&bytes).is_err());
+
+        // Readers go by the version byte, so any factory reads both tag lengths.
+        assert_eq!(full.from_encrypted_bytes(&bytes).unwrap().id, encrypted.id);
+        let mut unknown = bytes.clone();
+        unknown[0] = 3;
+        assert!(matches!(
+            full.from_encrypted_bytes(&unknown),
+            Err(Nano64Error::UnsupportedPayloadVersion(3))
+        ));
+        let strong = full.generate_encrypted_now().unwrap();
+        assert_eq!(
+            compact
+                .from_encrypted_bytes(&strong.to_encrypted_bytes())
+                .unwrap()
+                .id,
+            strong.id
+        );
+
+        let id = Nano128::generate(1_700_000_000_000, None).unwrap();
+        let encrypted = compact.encrypt_128(id).unwrap();
+        assert_eq!(encrypted.to_encrypted_bytes().len(), 38);
+        assert_eq!(
+            compact
+                .from_encrypted_bytes_128(&encrypted.to_encrypted_bytes())
+                .unwrap()
+                .id,
+            id
+        );
+    }
+
+    #[test]
+    fn test_encrypted_equality() {
+        use std::collections::HashSet;
+
+        let factory = Nano64EncryptionFactory::new(&[7u8; 32], None, None).unwrap();
+        let id = Nano64::generate_default().unwrap();
+        let first = factory.encrypt(id).unwrap();
+        let second = factory.encrypt(id).unwrap();
+        // Same ID, fresh IV: different tokens.
+        assert!(first != second);
+        assert!(!first.ct_eq(&second));
+
+        let parsed = factory
+            .from_encrypted_bytes(&first.to_encrypted_bytes())
+            .unwrap();
+        assert!(parsed.ct_eq(&first));
+        assert!(parsed == first);
+        assert_eq!(parsed.to_payload(), first.to_payload());
+        assert_ne!(first.to_payload(), second.to_payload());
+
+        let tokens: HashSet<_> = [first.clone(), second, parsed].into_iter().collect();
+        assert_eq!(tokens.len(), 2);
+        assert!(tokens.contains(&first));
+    }
+
+    #[test]
+    fn test_metadata() {
+        use crate::{MAX_METADATA_LENGTH, Nano64EncryptedPayload};
+
+        let factory = Nano64EncryptionFactory::new(&[8u8; 32], None, None).unwrap();
+        let id = Nano64::generate_default().unwrap();
+        let encrypted = factory.encrypt_with_metadata(id, &[7, 42]).unwrap();
+        assert_eq!(encrypted.metadata(), &[7, 42]);
+        let bytes = encrypted.to_encrypted_bytes();
+        assert_eq!(bytes.len(), PAYLOAD_LENGTH + 2);
+
+        let restored = factory.from_encrypted_bytes(&bytes).unwrap();
+        assert_eq!(restored.id, id);
+        assert_eq!(restored.metadata(), &[7, 42]);
+        let payload = Nano64EncryptedPayload::from_bytes(&bytes).unwrap();
+        assert_eq!(factory.decrypt(&payload).unwrap().metadata(), &[7, 42]);
+
+        // Metadata is authenticated along with the ID.
+        let mut tampered = bytes.clone();
+        *tampered.last_mut().unwrap() ^= 0x01;
+        assert!(factory.from_encrypted_bytes(&tampered).is_err());
+        assert!(
+            factory
+                .from_encrypted_bytes(&bytes[..bytes.len() - 1])
+                .is_err()
+        );
+
+        let plain = factory.encrypt(id).unwrap();
+        assert!(plain.metadata().is_empty());
+        assert!(
+            factory
+                .from_encrypted_bytes(&plain.to_encrypted_bytes())
+                .unwrap()
+                .metadata()
+                .is_empty()
+        );
+
+        let full = [1u8; MAX_METADATA_LENGTH];
+        let encrypted = factory.encrypt_with_metadata(id, &full).unwrap();
+        assert_eq!(
+            factory
+                .from_encrypted_bytes(&encrypted.to_encrypted_bytes())
+                .unwrap()
+                .metadata(),
+            &full
+        );
+        assert!(
+            factory
+                .encrypt_with_metadata(id, &[0; MAX_METADATA_LENGTH + 1])
+                .is_err()
+        );
+        assert!(
+            Nano64EncryptedPayload::from_bytes(&[0; PAYLOAD_LENGTH + MAX_METADATA_LENGTH + 1])
+                .is_err()
+        );
+
+        let compact = Nano64EncryptionFactory::new(&[8u8; 32], None, None)
+            .unwrap()
+            .with_truncated_tag();
+        let encrypted = compact.encrypt_with_metadata(id, &[3]).unwrap();
+        assert_eq!(
+            compact
+                .from_encrypted_bytes(&encrypted.to_encrypted_bytes())
+                .unwrap()
+                .metadata(),
+            &[3]
+        );
+    }
+
+    #[test]
+    fn test_encrypted_ids_are_plain_data() {
+        use crate::{Nano64Encrypted, Nano128Encrypted};
+        use std::mem::size_of;
+
+        // The ID, metadata and payload only; cipher state stays in the factory. An AES key
+        // schedule alone would be hundreds of bytes.
+        assert!(size_of::<Nano64Encrypted>() <= 64);
+        assert!(size_of::<Nano128Encrypted>() <= 64);
+
+        let factory = Nano64EncryptionFactory::new(&[2u8; 32], None, None).unwrap();
+        let encrypted = factory.generate_encrypted_now().unwrap();
+        assert_eq!(
+            encrypted.as_encrypted_bytes(),
+            encrypted.to_encrypted_bytes()
+        );
+        let encrypted = factory.generate_encrypted_128_now().unwrap();
+        assert_eq!(
+            encrypted.as_encrypted_bytes(),
+            encrypted.to_encrypted_bytes()
+        );
+    }
 }
@@ -0,0 +1,137 @@
+use crate::{Nano64, Nano64Error};
+use std::{fmt, str};
+
+// Length of the canonical `XXXXXXXXXXX-XXXXX` form.
+pub const CANONICAL_HEX_LENGTH: usize = 17;
+
+// The canonical (uppercase, dashed) text form of a `Nano64`, validated on construction.
+//
+// Because the canonical form is fixed width and uses uppercase hex, ordering the text is the same
+// as ordering the underlying IDs numerically, which makes this suitable as a string key.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Nano64Hex([u8; CANONICAL_HEX_LENGTH]);
+
+impl Nano64Hex {
+    pub fn new(id: Nano64) -> Self {
+        let mut buf = [0u8; CANONICAL_HEX_LENGTH];
+        id.to_hex_buf(&mut buf);
+        Self(buf)
+    }
+
+    pub fn as_str(&self) -> &str {
+        // Only ever built from ASCII hex digits and a dash.
+        str::from_utf8(&self.0).expect("canonical hex is always ASCII")
+    }
+
+    pub fn to_nano64(&self) -> Nano64 {
+        let mut value: u64 = 0;
+        for &c in self.0.iter().filter(|&&c| c != b'-') {
+            let digit = match c {
+                b'0'..=b'9' => c - b'0',
+                _ => c - b'A' + 10,
+            };
+            value = (value << 4) | digit as u64;
+        }
+        Nano64::new(value)
+    }
+}
+
+impl From<Nano64> for Nano64Hex {
+    fn from(id: Nano64) -> Self {
+        Self::new(id)
+    }
+}
+
+impl From<Nano64Hex> for Nano64 {
+    fn from(hex: Nano64Hex) -> Self {
+        hex.to_nano64()
+    }
+}
+
+impl From<Nano64Hex> for String {
+    fn from(hex: Nano64Hex) -> Self {
+        hex.as_str().to_string()
+    }
+}
+
+impl AsRef<str> for Nano64Hex {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+// Accepts anything `Nano64` parses and stores it in canonical form.
+impl str::FromStr for Nano64Hex {
+    type Err = Nano64Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        value.parse::<Nano64>().map(Self::new)
+    }
+}
+
+impl TryFrom<&str> for Nano64Hex {
+    type Error = Nano64Error;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        s.parse::<Nano64Hex>()
+    }
+}
+
+impl TryFrom<String> for Nano64Hex {
+    type Error = Nano64Error;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse::<Nano64Hex>()
+    }
+}
+
+impl fmt::Display for Nano64Hex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl fmt::Debug for Nano64Hex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Nano64Hex({})", self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Nano64, Nano64Hex};
+
+    #[test]
+    fn test_nano64_hex_roundtrip() {
+        for value in [0, 1, 0x123456789ABCDEF0, u64::MAX] {
+            let id = Nano64::new(value);
+            let hex = Nano64Hex::from(id);
+            assert_eq!(hex.as_str(), id.to_hex());
+            assert_eq!(Nano64::from(hex).u64_value(), value);
+        }
+    }
+
+    #[test]
+    fn test_nano64_hex_canonicalizes_input() {
+        let hex: Nano64Hex = "0x123456789abcdef0".parse().unwrap();
+        assert_eq!(hex.as_str(), "123456789AB-CDEF0");
+        assert_eq!(hex.to_string(), "123456789AB-CDEF0");
+        assert!("not-an-id".parse::<Nano64Hex>().is_err());
+    }
+
+    #[test]
+    fn test_nano64_hex_ordering_matches_numeric() {
+        let values = [0u64, 9, 10, 0xF, 0x10, 0xA0000, 0x9FFFFF, 1 << 63, u64::MAX];
+        let mut hexes: Vec<Nano64Hex> = values
+            .iter()
+            .rev()
+            .map(|&v| Nano64::new(v).into())
+            .collect();
+        hexes.sort();
+        let sorted: Vec<u64> = hexes.iter().map(|h| h.to_nano64().u64_value()).collect();
+        assert_eq!(sorted, values);
+        for pair in hexes.windows(2) {
+            assert!(pair[0].as_str() < pair[1].as_str());
+        }
+    }
+}
@@ -0,0 +1,190 @@
+use crate::{Hex, Nano64Error, RandomNumberGeneratorImpl, RandomSource, default_rng};
+use std::{
+    fmt, str,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+// MICRO_TICK_US is the length of one `Nano64Micro` timestamp tick in microseconds.
+pub const MICRO_TICK_US: u64 = 100;
+// MICRO_TIMESTAMP_BITS is the number of bits for the tick count (~891 years from 1970).
+pub const MICRO_TIMESTAMP_BITS: u64 = 48;
+// MICRO_RANDOM_BITS is the number of random bits per tick (0..2^16-1).
+pub const MICRO_RANDOM_BITS: u64 = 16;
+const MICRO_MAX_TIMESTAMP: u64 = (1 << MICRO_TIMESTAMP_BITS) - 1;
+
+// A 64-bit ID with 100µs timestamp precision, for workloads where ordering events within a
+// millisecond matters more than collision resistance.
+//
+// The tradeoff: the timestamp is 48 bits of 100µs ticks since the UNIX epoch, which leaves only
+// 16 random bits per tick. Two IDs from the same tick collide with probability 1/65,536, and
+// the chance of any collision within a tick passes 1% at about 36 IDs per tick (~360k IDs/s).
+// `Nano64`, by comparison, has 2^20 random values per millisecond. Don't use this type where a
+// duplicate ID would be harmful unless uniqueness is enforced elsewhere. The layout is not
+// compatible with `Nano64`; the two can't be converted into each other.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct Nano64Micro {
+    pub(crate) value: u64,
+}
+
+impl From<Nano64Micro> for u64 {
+    fn from(id: Nano64Micro) -> Self {
+        id.value
+    }
+}
+
+impl From<u64> for Nano64Micro {
+    fn from(value: u64) -> Self {
+        Self { value }
+    }
+}
+
+// From big-endian bytes
+impl From<[u8; 8]> for Nano64Micro {
+    fn from(bytes: [u8; 8]) -> Self {
+        Self {
+            value: u64::from_be_bytes(bytes),
+        }
+    }
+}
+
+// From hex string, with or without the dash
+impl str::FromStr for Nano64Micro {
+    type Err = Nano64Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let clean = value.replace("-", "");
+        if clean.len() != 16 {
+            return Err(Nano64Error::Error(format!(
+                "hex must be 16 chars after removing dash, got {}",
+                clean.len()
+            )));
+        }
+        let bytes: [u8; 8] = Hex::to_bytes(&clean)?
+            .try_into()
+            .map_err(|_| Nano64Error::Error("hex must decode to exactly 8 bytes".into()))?;
+        Ok(Self::from(bytes))
+    }
+}
+
+// `{}` prints the canonical `XXXXXXXXXXXX-XXXX` hex form (same as `to_hex`).
+impl fmt::Display for Nano64Micro {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(&self.to_hex())
+    }
+}
+
+impl Nano64Micro {
+    pub const fn new(value: u64) -> Self {
+        Self { value }
+    }
+
+    // `ticks` counts 100µs steps since the UNIX epoch.
+    pub fn from_parts(ticks: u64, random: u16) -> Result<Self, Nano64Error> {
+        if ticks > MICRO_MAX_TIMESTAMP {
+            return Err(Nano64Error::TimeStampExceedsBitRange(ticks));
+        }
+        Ok(Self {
+            value: (ticks << MICRO_RANDOM_BITS) | random as u64,
+        })
+    }
+
+    // Reads the system clock directly: the installed default clock only has millisecond
+    // precision.
+    pub fn generate_now(rng: Option<RandomNumberGeneratorImpl>) -> Result<Self, Nano64Error> {
+        let since_epoch = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(|_| Nano64Error::Error("system time is before the UNIX epoch".into()))?;
+        let ticks =
+            u64::try_from(since_epoch.as_micros() / MICRO_TICK_US as u128).unwrap_or(u64::MAX);
+        Self::generate(ticks, rng)
+    }
+
+    pub fn generate(
+        ticks: u64,
+        rng: Option<RandomNumberGeneratorImpl>,
+    ) -> Result<Self, Nano64Error> {
+        let mut rng = if let Some(_rng) = rng {
+            _rng
+        } else {
+            default_rng
+        };
+        Self::generate_with_source(ticks, &mut rng)
+    }
+
+    pub fn generate_with_source<R: RandomSource + ?Sized>(
+        ticks: u64,
+        rng: &mut R,
+    ) -> Result<Self, Nano64Error> {
+        let random = rng.random_bits(MICRO_RANDOM_BITS as u32)? as u16;
+        Self::from_parts(ticks, random)
+    }
+
+    // The timestamp in 100µs ticks since the UNIX epoch.
+    pub fn get_timestamp(&self) -> u64 {
+        self.value >> MICRO_RANDOM_BITS
+    }
+
+    pub fn get_timestamp_micros(&self) -> u64 {
+        self.get_timestamp() * MICRO_TICK_US
+    }
+
+    pub fn get_random(&self) -> u16 {
+        self.value as u16
+    }
+
+    pub fn u64_value(&self) -> u64 {
+        self.value
+    }
+
+    pub fn to_date(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_micros(self.get_timestamp_micros())
+    }
+
+    // Big-endian bytes
+    pub fn to_bytes(&self) -> [u8; 8] {
+        self.value.to_be_bytes()
+    }
+
+    // Canonical `XXXXXXXXXXXX-XXXX` form: tick count, dash, random field.
+    pub fn to_hex(&self) -> String {
+        format!("{:012X}-{:04X}", self.get_timestamp(), self.get_random())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Nano64Micro, testing::MockRng};
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn test_nano64_micro_layout() {
+        let id = Nano64Micro::from_parts(17_000_000_000_123, 0xBEEF).unwrap();
+        assert_eq!(id.get_timestamp(), 17_000_000_000_123);
+        assert_eq!(id.get_timestamp_micros(), 1_700_000_000_012_300);
+        assert_eq!(id.get_random(), 0xBEEF);
+        assert_eq!(
+            id.to_date(),
+            UNIX_EPOCH + Duration::from_micros(1_700_000_000_012_300)
+        );
+        assert!(Nano64Micro::from_parts(1 << 48, 0).is_err());
+
+        let hex = id.to_hex();
+        assert_eq!(hex, "0F761EF6107B-BEEF");
+        assert_eq!(hex.parse::<Nano64Micro>().unwrap(), id);
+        assert_eq!(format!("{id}"), hex);
+        assert_eq!(Nano64Micro::from(id.to_bytes()), id);
+    }
+
+    #[test]
+    fn test_nano64_micro_orders_within_a_millisecond() {
+        let mut rng = MockRng::new([0xFFFF, 0]);
+        let earlier = Nano64Micro::generate_with_source(17_000_000_000_000, &mut rng).unwrap();
+        let later = Nano64Micro::generate_with_source(17_000_000_000_001, &mut rng).unwrap();
+        assert!(earlier < later);
+
+        let now = Nano64Micro::generate_now(None).unwrap();
+        // 2^48 ticks of 100µs reach well past the year 2500.
+        assert!(now.get_timestamp() > 17_000_000_000_000);
+    }
+}
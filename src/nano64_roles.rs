@@ -0,0 +1,195 @@
+use crate::{
+    AeadBackend, AesGcm, BLIND_INDEX_LENGTH, Clock, Nano64, Nano64Encrypted,
+    Nano64EncryptedPayload, Nano64EncryptionFactory, Nano64Error, Nano64Token, Nano128,
+    Nano128Encrypted,
+};
+use std::time::Duration;
+
+// The encrypting half of a `Nano64EncryptionFactory`, for services that only mint payloads
+// and tokens. With a symmetric cipher the key itself can still decrypt, so this limits what
+// the service's code can do, not what a leaked key can; keep decrypt access out of its key
+// store too.
+//
+// Configure the factory first (keyring, nonce strategy, truncated tag, usage limit), then
+// convert it with `Nano64Encryptor::from`.
+pub struct Nano64Encryptor<B: AeadBackend = AesGcm>(Nano64EncryptionFactory<B>);
+
+// The decrypting half of a `Nano64EncryptionFactory`, for services that only read payloads
+// and verify tokens. Built the same way as `Nano64Encryptor`.
+pub struct Nano64Decryptor<B: AeadBackend = AesGcm>(Nano64EncryptionFactory<B>);
+
+impl Nano64Encryptor {
+    // An AES-256-GCM encryptor with the default clock and RNG; `aes_key` must be 32 bytes.
+    pub fn new(aes_key: &[u8]) -> Result<Self, Nano64Error> {
+        Ok(Self(Nano64EncryptionFactory::new(aes_key, None, None)?))
+    }
+}
+
+impl Nano64Decryptor {
+    // An AES-256-GCM decryptor with the default clock; `aes_key` must be 32 bytes.
+    pub fn new(aes_key: &[u8]) -> Result<Self, Nano64Error> {
+        Ok(Self(Nano64EncryptionFactory::new(aes_key, None, None)?))
+    }
+}
+
+impl<B: AeadBackend> From<Nano64EncryptionFactory<B>> for Nano64Encryptor<B> {
+    fn from(factory: Nano64EncryptionFactory<B>) -> Self {
+        Self(factory)
+    }
+}
+
+impl<B: AeadBackend> From<Nano64EncryptionFactory<B>> for Nano64Decryptor<B> {
+    fn from(factory: Nano64EncryptionFactory<B>) -> Self {
+        Self(factory)
+    }
+}
+
+impl<B: AeadBackend> Nano64Encryptor<B> {
+    pub fn encrypt(&self, id: Nano64) -> Result<Nano64Encrypted, Nano64Error> {
+        self.0.encrypt(id)
+    }
+
+    pub fn encrypt_with_metadata(
+        &self,
+        id: Nano64,
+        metadata: &[u8],
+    ) -> Result<Nano64Encrypted, Nano64Error> {
+        self.0.encrypt_with_metadata(id, metadata)
+    }
+
+    pub fn generate_encrypted(&self, timestamp: u64) -> Result<Nano64Encrypted, Nano64Error> {
+        self.0.generate_encrypted(timestamp)
+    }
+
+    pub fn generate_encrypted_now(&self) -> Result<Nano64Encrypted, Nano64Error> {
+        self.0.generate_encrypted_now()
+    }
+
+    pub fn encrypt_128(&self, id: Nano128) -> Result<Nano128Encrypted, Nano64Error> {
+        self.0.encrypt_128(id)
+    }
+
+    pub fn generate_encrypted_128_now(&self) -> Result<Nano128Encrypted, Nano64Error> {
+        self.0.generate_encrypted_128_now()
+    }
+
+    pub fn issue_token(&self, id: Nano64, ttl: Duration) -> Result<Nano64Token, Nano64Error> {
+        self.0.issue_token(id, ttl)
+    }
+
+    // Writers of searchable rows need the lookup value too.
+    pub fn blind_index(&self, id: &Nano64) -> Result<[u8; BLIND_INDEX_LENGTH], Nano64Error> {
+        self.0.blind_index(id)
+    }
+
+    pub fn encryptions(&self) -> u64 {
+        self.0.encryptions()
+    }
+}
+
+impl<B: AeadBackend> Nano64Decryptor<B> {
+    // Replaces the clock `verify_token` checks expiry against.
+    pub fn with_clock(self, clock: impl Clock + 'static) -> Self {
+        Self(self.0.with_clock(clock))
+    }
+
+    #[allow(clippy::wrong_self_convention)]
+    pub fn from_encrypted_bytes(&self, bytes: &[u8]) -> Result<Nano64Encrypted, Nano64Error> {
+        self.0.from_encrypted_bytes(bytes)
+    }
+
+    pub fn decrypt(
+        &self,
+        payload: &Nano64EncryptedPayload,
+    ) -> Result<Nano64Encrypted, Nano64Error> {
+        self.0.decrypt(payload)
+    }
+
+    #[allow(clippy::wrong_self_convention)]
+    pub fn from_encrypted_hex(&self, hex: String) -> Result<Nano64Encrypted, Nano64Error> {
+        self.0.from_encrypted_hex(hex)
+    }
+
+    #[allow(clippy::wrong_self_convention)]
+    pub fn from_encrypted_hex_strict(&self, hex: &str) -> Result<Nano64Encrypted, Nano64Error> {
+        self.0.from_encrypted_hex_strict(hex)
+    }
+
+    #[allow(clippy::wrong_self_convention)]
+    pub fn from_encrypted_slug(&self, slug: &str) -> Result<Nano64Encrypted, Nano64Error> {
+        self.0.from_encrypted_slug(slug)
+    }
+
+    #[allow(clippy::wrong_self_convention)]
+    pub fn from_encrypted_bytes_128(&self, bytes: &[u8]) -> Result<Nano128Encrypted, Nano64Error> {
+        self.0.from_encrypted_bytes_128(bytes)
+    }
+
+    #[allow(clippy::wrong_self_convention)]
+    pub fn from_encrypted_hex_128(&self, hex: &str) -> Result<Nano128Encrypted, Nano64Error> {
+        self.0.from_encrypted_hex_128(hex)
+    }
+
+    #[allow(clippy::wrong_self_convention)]
+    pub fn from_encrypted_slug_128(&self, slug: &str) -> Result<Nano128Encrypted, Nano64Error> {
+        self.0.from_encrypted_slug_128(slug)
+    }
+
+    pub fn verify_token(&self, token: &str) -> Result<Nano64Token, Nano64Error> {
+        self.0.verify_token(token)
+    }
+
+    pub fn verify_token_bytes(&self, bytes: &[u8]) -> Result<Nano64Token, Nano64Error> {
+        self.0.verify_token_bytes(bytes)
+    }
+
+    // Readers look rows up by it.
+    pub fn blind_index(&self, id: &Nano64) -> Result<[u8; BLIND_INDEX_LENGTH], Nano64Error> {
+        self.0.blind_index(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Nano64Decryptor, Nano64EncryptionFactory, Nano64Encryptor, NonceStrategy};
+    use std::time::Duration;
+
+    #[test]
+    fn test_split_roles() {
+        let key = [4u8; 32];
+        let encryptor = Nano64Encryptor::new(&key).unwrap();
+        let decryptor = Nano64Decryptor::new(&key).unwrap();
+
+        let encrypted = encryptor.generate_encrypted_now().unwrap();
+        assert_eq!(encryptor.encryptions(), 1);
+        assert_eq!(
+            decryptor
+                .from_encrypted_slug(&encrypted.to_encrypted_slug())
+                .unwrap()
+                .id,
+            encrypted.id
+        );
+        let token = encryptor
+            .issue_token(encrypted.id, Duration::from_secs(60))
+            .unwrap();
+        assert_eq!(
+            decryptor.verify_token(&token.to_string()).unwrap().id,
+            encrypted.id
+        );
+
+        // Factory settings carry over.
+        let compact: Nano64Encryptor = Nano64EncryptionFactory::new(&key, None, None)
+            .unwrap()
+            .with_nonce_strategy(NonceStrategy::Counter)
+            .with_truncated_tag()
+            .into();
+        let compact_payload = compact.generate_encrypted_now().unwrap();
+        assert_eq!(compact_payload.to_encrypted_bytes().len(), 30);
+        assert_eq!(
+            decryptor.decrypt(&compact_payload.to_payload()).unwrap().id,
+            compact_payload.id
+        );
+
+        assert!(Nano64Encryptor::new(&[0u8; 16]).is_err());
+    }
+}
@@ -0,0 +1,129 @@
+use crate::{Base64Url, Nano64, Nano64Error, mac::KeyedHmac};
+
+// SIGNATURE_LENGTH is the number of bytes of HMAC-SHA256 kept in a `Nano64Signed` tag.
+pub const SIGNATURE_LENGTH: usize = 8;
+// SIGNED_LENGTH is the length of a `Nano64Signed` in bytes: the ID followed by its tag.
+pub const SIGNED_LENGTH: usize = 8 + SIGNATURE_LENGTH;
+// Shortest key `Nano64SigningFactory` accepts.
+pub const MIN_SIGNING_KEY_LENGTH: usize = 16;
+
+// A plaintext ID with a truncated HMAC-SHA256 tag. The ID stays readable; the tag keeps anyone
+// without the key from forging or guessing valid IDs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Nano64Signed {
+    pub id: Nano64,
+    pub(crate) tag: [u8; SIGNATURE_LENGTH],
+}
+
+impl Nano64Signed {
+    // `<canonical hex>.<base64url tag>`, 29 URL-safe chars.
+    pub fn to_token(&self) -> String {
+        format!("{}.{}", self.id.to_hex(), Base64Url::encode(&self.tag))
+    }
+
+    pub fn to_signed_bytes(&self) -> [u8; SIGNED_LENGTH] {
+        let mut bytes = [0u8; SIGNED_LENGTH];
+        bytes[..8].copy_from_slice(&self.id.value.to_be_bytes());
+        bytes[8..].copy_from_slice(&self.tag);
+        bytes
+    }
+}
+
+// Signs and verifies `Nano64Signed` tokens under one HMAC key.
+#[derive(Clone)]
+pub struct Nano64SigningFactory {
+    mac: KeyedHmac,
+}
+
+impl Nano64SigningFactory {
+    // `key` must be at least `MIN_SIGNING_KEY_LENGTH` bytes.
+    pub fn new(key: &[u8]) -> Result<Self, Nano64Error> {
+        if key.len() < MIN_SIGNING_KEY_LENGTH {
+            return Err(Nano64Error::Error(format!(
+                "signing key must be at least {MIN_SIGNING_KEY_LENGTH} bytes, got {}",
+                key.len()
+            )));
+        }
+        Ok(Self {
+            mac: KeyedHmac::new(key)?,
+        })
+    }
+
+    pub fn sign(&self, id: Nano64) -> Nano64Signed {
+        let mut tag = [0u8; SIGNATURE_LENGTH];
+        tag.copy_from_slice(&self.mac.tag(&id.value.to_be_bytes())[..SIGNATURE_LENGTH]);
+        Nano64Signed { id, tag }
+    }
+
+    // Parses a `to_token` string, failing with `InvalidSignature` if the tag doesn't match.
+    pub fn verify_and_parse(&self, token: &str) -> Result<Nano64Signed, Nano64Error> {
+        let (id, tag) = token
+            .split_once('.')
+            .ok_or_else(|| Nano64Error::Error("signed token must be <id>.<signature>".into()))?;
+        let id: Nano64 = id.parse()?;
+        self.verify(id, &Base64Url::decode(tag)?)
+    }
+
+    // Like `verify_and_parse`, for `to_signed_bytes` output.
+    pub fn verify_bytes(&self, bytes: &[u8]) -> Result<Nano64Signed, Nano64Error> {
+        if bytes.len() != SIGNED_LENGTH {
+            return Err(Nano64Error::InvalidByteLength {
+                expected: SIGNED_LENGTH,
+                got: bytes.len(),
+            });
+        }
+        self.verify(Nano64::from_be_slice(&bytes[..8])?, &bytes[8..])
+    }
+
+    fn verify(&self, id: Nano64, tag: &[u8]) -> Result<Nano64Signed, Nano64Error> {
+        if tag.len() != SIGNATURE_LENGTH {
+            return Err(Nano64Error::InvalidSignature);
+        }
+        if !self.mac.verify_truncated(&id.value.to_be_bytes(), tag) {
+            return Err(Nano64Error::InvalidSignature);
+        }
+        let mut owned = [0u8; SIGNATURE_LENGTH];
+        owned.copy_from_slice(tag);
+        Ok(Nano64Signed { id, tag: owned })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Nano64, Nano64Error, Nano64SigningFactory, SIGNED_LENGTH};
+
+    #[test]
+    fn test_sign_and_verify() {
+        let factory = Nano64SigningFactory::new(b"0123456789abcdef").unwrap();
+        let id = Nano64::generate_default().unwrap();
+        let signed = factory.sign(id);
+
+        let token = signed.to_token();
+        assert_eq!(token.len(), 29);
+        assert!(token.starts_with(&id.to_hex()));
+        assert_eq!(factory.verify_and_parse(&token).unwrap(), signed);
+
+        let bytes = signed.to_signed_bytes();
+        assert_eq!(bytes.len(), SIGNED_LENGTH);
+        assert_eq!(factory.verify_bytes(&bytes).unwrap().id, id);
+
+        // Swapping in another ID keeps the old tag, which no longer matches.
+        let forged = format!(
+            "{}{}",
+            Nano64::new(id.u64_value() ^ 1).to_hex(),
+            &token[17..]
+        );
+        assert!(matches!(
+            factory.verify_and_parse(&forged),
+            Err(Nano64Error::InvalidSignature)
+        ));
+
+        let other = Nano64SigningFactory::new(b"fedcba9876543210").unwrap();
+        assert!(matches!(
+            other.verify_and_parse(&token),
+            Err(Nano64Error::InvalidSignature)
+        ));
+        assert!(factory.verify_and_parse(&id.to_hex()).is_err());
+        assert!(Nano64SigningFactory::new(b"short").is_err());
+    }
+}
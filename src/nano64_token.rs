@@ -0,0 +1,145 @@
+use crate::{AeadBackend, Base64Url, Nano64, Nano64EncryptionFactory, Nano64Error};
+use std::{fmt, time::Duration};
+
+// Authenticated with every token payload, so a token never decrypts as an encrypted `Nano128`
+// of the same length, or the other way round.
+const TOKEN_AAD: &[u8] = b"nano64-token";
+// The ID followed by the expiry, both big-endian.
+const TOKEN_PLAINTEXT_LENGTH: usize = 16;
+
+// An encrypted ID that expires, for password-reset links and signed download URLs. The expiry
+// is sealed next to the ID, so it can't be extended without failing decryption.
+#[derive(Clone)]
+pub struct Nano64Token {
+    pub id: Nano64,
+    expires_at: u64,
+    payload: Vec<u8>,
+}
+
+impl Nano64Token {
+    // Unix ms from which `verify_token` rejects the token.
+    pub fn expires_at(&self) -> u64 {
+        self.expires_at
+    }
+
+    // Payload as unpadded base64url, ready for a URL.
+    pub fn to_encrypted_slug(&self) -> String {
+        Base64Url::encode(&self.payload)
+    }
+
+    pub fn to_encrypted_bytes(&self) -> Vec<u8> {
+        self.payload.clone()
+    }
+
+    pub fn as_encrypted_bytes(&self) -> &[u8] {
+        &self.payload
+    }
+}
+
+// Writes the slug, the form `verify_token` takes.
+impl fmt::Display for Nano64Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_encrypted_slug())
+    }
+}
+
+impl<B: AeadBackend> Nano64EncryptionFactory<B> {
+    // Encrypts `id` into a token that `verify_token` accepts for `ttl`, by the factory's clock.
+    pub fn issue_token(&self, id: Nano64, ttl: Duration) -> Result<Nano64Token, Nano64Error> {
+        let ttl_ms = u64::try_from(ttl.as_millis()).unwrap_or(u64::MAX);
+        let expires_at = self.clock.now_ms().saturating_add(ttl_ms);
+        let mut plaintext = [0u8; TOKEN_PLAINTEXT_LENGTH];
+        plaintext[..8].copy_from_slice(&id.value.to_be_bytes());
+        plaintext[8..].copy_from_slice(&expires_at.to_be_bytes());
+        Ok(Nano64Token {
+            id,
+            expires_at,
+            payload: self.seal(&plaintext, TOKEN_AAD)?,
+        })
+    }
+
+    // Decrypts a `to_encrypted_slug` token. Fails with `TokenExpired` once its expiry has
+    // passed, by the factory's clock.
+    pub fn verify_token(&self, token: &str) -> Result<Nano64Token, Nano64Error> {
+        self.verify_token_bytes(&Base64Url::decode(token)?)
+    }
+
+    // Like `verify_token`, for `to_encrypted_bytes` output.
+    pub fn verify_token_bytes(&self, bytes: &[u8]) -> Result<Nano64Token, Nano64Error> {
+        let plaintext = self.open(
+            bytes,
+            TOKEN_PLAINTEXT_LENGTH..=TOKEN_PLAINTEXT_LENGTH,
+            TOKEN_AAD,
+        )?;
+        if plaintext.len() != TOKEN_PLAINTEXT_LENGTH {
+            return Err(Nano64Error::Error(format!(
+                "decryption yielded invalid length: {}",
+                plaintext.len()
+            )));
+        }
+        let id = Nano64::from_be_slice(&plaintext[..8])?;
+        let expires_at = u64::from_be_bytes(plaintext[8..].try_into().expect("8 bytes"));
+
+        if self.clock.now_ms() >= expires_at {
+            return Err(Nano64Error::TokenExpired { expires_at });
+        }
+        Ok(Nano64Token {
+            id,
+            expires_at,
+            payload: bytes.to_vec(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Nano64, Nano64EncryptionFactory, Nano64Error, testing::MockClock};
+    use std::time::Duration;
+
+    #[test]
+    fn test_token_expiry() {
+        let clock = MockClock::new(1_700_000_000_000);
+        let factory = Nano64EncryptionFactory::new(&[3u8; 32], None, None)
+            .unwrap()
+            .with_clock(clock.clone());
+        let id = Nano64::generate_default().unwrap();
+
+        let token = factory.issue_token(id, Duration::from_secs(60)).unwrap();
+        assert_eq!(token.expires_at(), 1_700_000_060_000);
+        let slug = token.to_string();
+        assert_eq!(slug, token.to_encrypted_slug());
+        assert_eq!(factory.verify_token(&slug).unwrap().id, id);
+
+        clock.advance(59_999);
+        assert!(factory.verify_token(&slug).is_ok());
+        clock.advance(1);
+        assert!(matches!(
+            factory.verify_token(&slug),
+            Err(Nano64Error::TokenExpired {
+                expires_at: 1_700_000_060_000
+            })
+        ));
+
+        // Tampering with the sealed expiry fails decryption rather than extending the token.
+        let mut bytes = token.to_encrypted_bytes();
+        bytes[25] ^= 0x01;
+        clock.set(1_700_000_000_000);
+        assert!(matches!(
+            factory.verify_token_bytes(&bytes),
+            Err(Nano64Error::Error(_))
+        ));
+
+        // Tokens and encrypted `Nano128`s are the same length but never mistaken for each other.
+        assert!(
+            factory
+                .from_encrypted_bytes_128(&token.to_encrypted_bytes())
+                .is_err()
+        );
+        let nano128 = factory.generate_encrypted_128_now().unwrap();
+        assert!(
+            factory
+                .verify_token_bytes(&nano128.to_encrypted_bytes())
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,146 @@
+use crate::{Nano64, Nano64Error, RANDOM_BITS};
+
+// A worker/shard ID stored in the top `bits` of the random field, Snowflake-style. Generators
+// configured with distinct node IDs of the same width can never produce the same ID, without
+// coordinating. The remaining `RANDOM_BITS - bits` bits stay random (or count up, for
+// monotonic IDs), so each node gets `2^(20 - bits)` IDs per millisecond before monotonic
+// generation has to borrow the next one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct NodeId {
+    id: u32,
+    bits: u32,
+}
+
+impl NodeId {
+    // Widest node field allowed, which leaves 16 IDs per millisecond per node.
+    pub const MAX_BITS: u32 = 16;
+
+    // No node field; the whole random field is random.
+    pub(crate) const NONE: NodeId = NodeId { id: 0, bits: 0 };
+
+    // Fails unless `bits` is 1-16 and `id` fits in it.
+    pub fn new(id: u32, bits: u32) -> Result<Self, Nano64Error> {
+        check_bits(bits)?;
+        if id >= 1 << bits {
+            return Err(Nano64Error::NodeIdOutOfRange { id, bits });
+        }
+        Ok(Self { id, bits })
+    }
+
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    pub fn bits(&self) -> u32 {
+        self.bits
+    }
+
+    // Bits of the random field left for randomness (or the monotonic counter).
+    pub(crate) fn counter_bits(&self) -> u32 {
+        RANDOM_BITS as u32 - self.bits
+    }
+
+    pub(crate) fn counter_mask(&self) -> u64 {
+        (1 << self.counter_bits()) - 1
+    }
+
+    // The node ID positioned within an ID.
+    pub(crate) fn prefix(&self) -> u64 {
+        (self.id as u64) << self.counter_bits()
+    }
+}
+
+fn check_bits(bits: u32) -> Result<(), Nano64Error> {
+    if bits == 0 || bits > NodeId::MAX_BITS {
+        return Err(Nano64Error::InvalidNodeBits(bits));
+    }
+    Ok(())
+}
+
+impl Nano64 {
+    // The node ID of an ID generated with a `bits`-wide `NodeId`. Meaningless for IDs
+    // generated without one.
+    pub fn get_node(&self, bits: u32) -> Result<u32, Nano64Error> {
+        check_bits(bits)?;
+        Ok(self.get_random() >> (RANDOM_BITS as u32 - bits))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Nano64, Nano64Error, Nano64Generator, NodeId,
+        testing::{MockClock, MockRng},
+    };
+
+    #[test]
+    fn test_node_id_validation() {
+        let node = NodeId::new(5, 10).unwrap();
+        assert_eq!((node.id(), node.bits()), (5, 10));
+        assert!(NodeId::new(1023, 10).is_ok());
+        assert!(matches!(
+            NodeId::new(1024, 10),
+            Err(Nano64Error::NodeIdOutOfRange { id: 1024, bits: 10 })
+        ));
+        assert!(matches!(
+            NodeId::new(0, 0),
+            Err(Nano64Error::InvalidNodeBits(0))
+        ));
+        assert!(NodeId::new(0, 17).is_err());
+        assert!(Nano64::MAX.get_node(0).is_err());
+        assert_eq!(Nano64::MAX.get_node(16).unwrap(), 0xFFFF);
+    }
+
+    #[test]
+    fn test_generator_with_node() {
+        let node = NodeId::new(0b101, 3).unwrap();
+        let generator = Nano64Generator::new()
+            .with_clock(MockClock::new(1000))
+            .with_rng(MockRng::constant(u32::MAX))
+            .with_node(node)
+            .unwrap();
+        assert_eq!(generator.node(), Some(node));
+        assert_eq!(Nano64Generator::new().node(), None);
+
+        let id = generator.generate().unwrap();
+        assert_eq!(id.get_node(3).unwrap(), 0b101);
+        assert_eq!(id.get_random() as u64, (0b101 << 17) | ((1 << 17) - 1));
+        for id in generator.generate_batch(4).unwrap() {
+            assert_eq!(id.get_node(3).unwrap(), 0b101);
+        }
+        let other = Nano64Generator::new()
+            .with_clock(MockClock::new(1000))
+            .with_rng(MockRng::constant(u32::MAX))
+            .with_node(NodeId::new(0b100, 3).unwrap())
+            .unwrap();
+        assert_ne!(other.generate().unwrap(), id);
+    }
+
+    #[test]
+    fn test_monotonic_with_node_keeps_node_bits() {
+        let node = NodeId::new(1, 16).unwrap();
+        let generator = Nano64Generator::new()
+            .with_clock(MockClock::new(1000))
+            .with_rng(MockRng::constant(0))
+            .with_node(node)
+            .unwrap();
+        // 4 counter bits: 16 IDs in millisecond 1000, then the sequence borrows 1001.
+        let ids: Vec<Nano64> = (0..17)
+            .map(|_| generator.generate_monotonic().unwrap())
+            .collect();
+        assert!(ids.windows(2).all(|w| w[0] < w[1]));
+        assert!(ids.iter().all(|id| id.get_node(16).unwrap() == 1));
+        assert_eq!(ids[15].get_timestamp(), 1000);
+        assert_eq!(ids[15].get_random() as u64 & 0xF, 0xF);
+        assert_eq!(ids[16].get_timestamp(), 1001);
+        assert_eq!(ids[16].get_random() as u64 & 0xF, 0);
+
+        let block: Vec<Nano64> = generator.generate_monotonic_block(3).unwrap().collect();
+        assert!(block.iter().all(|id| id.get_node(16).unwrap() == 1));
+        // A block can't carry into the node bits, so it must fit in one millisecond.
+        assert!(matches!(
+            generator.generate_monotonic_block(16),
+            Err(Nano64Error::RandomFieldExhausted { .. })
+        ));
+    }
+}
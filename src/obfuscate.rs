@@ -0,0 +1,85 @@
+use crate::Nano64;
+
+const OBFUSCATION_ROUNDS: usize = 4;
+
+// A keyed bijection on 64-bit values (a 4-round Feistel network) for hiding an ID's timestamp
+// and sequence from end users. It is fast and keeps the result a plain `u64`, e.g. 11 chars of
+// `Base62`, but it is obfuscation, not encryption: someone who collects enough pairs of IDs and
+// tokens can recover the mapping. Use `Nano64Fpe` or `Nano64EncryptionFactory` where that matters.
+#[derive(Clone)]
+pub struct Nano64Obfuscator {
+    round_keys: [u64; OBFUSCATION_ROUNDS],
+}
+
+impl Nano64Obfuscator {
+    pub fn new(key: [u8; 16]) -> Self {
+        let key = u128::from_be_bytes(key);
+        let (mut high, mut low) = ((key >> 64) as u64, key as u64);
+        let round_keys =
+            std::array::from_fn(|_| splitmix64(&mut high) ^ splitmix64(&mut low).rotate_left(32));
+        Self { round_keys }
+    }
+
+    pub fn obfuscate(&self, id: Nano64) -> u64 {
+        let (mut left, mut right) = ((id.value >> 32) as u32, id.value as u32);
+        for key in self.round_keys {
+            (left, right) = (right, left ^ round(right, key));
+        }
+        ((left as u64) << 32) | right as u64
+    }
+
+    pub fn deobfuscate(&self, value: u64) -> Nano64 {
+        let (mut left, mut right) = ((value >> 32) as u32, value as u32);
+        for key in self.round_keys.into_iter().rev() {
+            (left, right) = (right ^ round(left, key), left);
+        }
+        Nano64::new(((left as u64) << 32) | right as u64)
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for Nano64Obfuscator {
+    fn drop(&mut self) {
+        zeroize::Zeroize::zeroize(&mut self.round_keys);
+    }
+}
+
+fn round(half: u32, key: u64) -> u32 {
+    let mut x = (half as u64 ^ key).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    x ^= x >> 31;
+    (x.wrapping_mul(0x94D0_49BB_1331_11EB) >> 32) as u32
+}
+
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Nano64, Nano64Obfuscator};
+
+    #[test]
+    fn test_obfuscate_round_trip() {
+        let obfuscator = Nano64Obfuscator::new(*b"0123456789abcdef");
+        for value in [0, 1, u64::MAX, 0x0123_4567_89AB_CDEF] {
+            let id = Nano64::new(value);
+            assert_eq!(obfuscator.deobfuscate(obfuscator.obfuscate(id)), id);
+        }
+
+        // Consecutive IDs from the same millisecond shouldn't look related.
+        let a = obfuscator.obfuscate(Nano64::from_parts(1_735_689_600_000, 1).unwrap());
+        let b = obfuscator.obfuscate(Nano64::from_parts(1_735_689_600_000, 2).unwrap());
+        assert!((a ^ b).count_ones() > 8);
+        assert_ne!(a >> 20, b >> 20);
+
+        let other = Nano64Obfuscator::new(*b"fedcba9876543210");
+        assert_ne!(
+            other.obfuscate(Nano64::new(1)),
+            obfuscator.obfuscate(Nano64::new(1))
+        );
+    }
+}
@@ -0,0 +1,83 @@
+use crate::{Nano64, Nano64Error, RandomNumberGeneratorImpl};
+use rayon::prelude::*;
+
+// IDs each rayon task generates per clock read.
+const PARALLEL_CHUNK: usize = 64 * 1024;
+// Rounds of replacing duplicates before giving up. A working RNG needs a handful at most.
+const MAX_UNIQUE_ROUNDS: usize = 32;
+
+impl Nano64 {
+    // `n` IDs generated across the rayon thread pool. With `unique`, duplicates are replaced
+    // until every ID is distinct, and the result is sorted; otherwise the order is arbitrary.
+    // An RNG too weak to fill the gaps within `MAX_UNIQUE_ROUNDS` rounds is an error.
+    // `Some(pooled_rng)` avoids contending on a shared RNG.
+    pub fn generate_batch_parallel(
+        n: usize,
+        rng: Option<RandomNumberGeneratorImpl>,
+        unique: bool,
+    ) -> Result<Vec<Nano64>, Nano64Error> {
+        let mut ids = vec![Self::NIL; n];
+        ids.par_chunks_mut(PARALLEL_CHUNK)
+            .try_for_each(|chunk| Self::generate_into(chunk, rng))?;
+        if !unique {
+            return Ok(ids);
+        }
+
+        for _ in 0..MAX_UNIQUE_ROUNDS {
+            ids.par_sort_unstable();
+            ids.dedup();
+            let missing = n - ids.len();
+            if missing == 0 {
+                return Ok(ids);
+            }
+            ids.extend(Self::generate_batch(missing, rng)?);
+        }
+        ids.par_sort_unstable();
+        ids.dedup();
+        if ids.len() == n {
+            return Ok(ids);
+        }
+        Err(Nano64Error::Error(format!(
+            "{} of {n} IDs still duplicated after {MAX_UNIQUE_ROUNDS} rounds; is the RNG random?",
+            n - ids.len()
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Nano64, Nano64Error, pooled_rng};
+
+    #[test]
+    fn test_generate_batch_parallel() {
+        let n = 3 * super::PARALLEL_CHUNK + 17;
+        let ids = Nano64::generate_batch_parallel(n, Some(pooled_rng), false).unwrap();
+        assert_eq!(ids.len(), n);
+        assert!(ids.iter().all(|id| !id.is_nil()));
+
+        // 200k IDs over a handful of milliseconds collide with near certainty, so this
+        // exercises the replacement loop.
+        let unique = Nano64::generate_batch_parallel(n, Some(pooled_rng), true).unwrap();
+        assert_eq!(unique.len(), n);
+        assert!(unique.windows(2).all(|w| w[0] < w[1]));
+        assert!(
+            Nano64::generate_batch_parallel(0, None, true)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_generate_batch_parallel_gives_up_on_constant_rng() {
+        fn constant_rng(_bits: u32) -> Result<u32, Nano64Error> {
+            Ok(7)
+        }
+        assert!(Nano64::generate_batch_parallel(1000, Some(constant_rng), true).is_err());
+        assert_eq!(
+            Nano64::generate_batch_parallel(1000, Some(constant_rng), false)
+                .unwrap()
+                .len(),
+            1000
+        );
+    }
+}
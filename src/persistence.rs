@@ -0,0 +1,139 @@
+use crate::{MonotonicState, Nano64, Nano64Error, RANDOM_MASK};
+use std::{
+    ffi::OsString,
+    fs::{self, File},
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+// Keeps a `MonotonicState` snapshot in a file so a restarted process never reissues IDs the
+// previous one handed out, even if its clock is now behind.
+//
+// The file holds the canonical hex of the last ID. Saves write a temporary file, fsync it and
+// rename it over the old one, so a crash leaves either the old or the new snapshot.
+#[derive(Clone, Debug)]
+pub struct MonotonicFileStore {
+    path: PathBuf,
+}
+
+impl MonotonicFileStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn save(&self, state: &MonotonicState) -> Result<(), Nano64Error> {
+        let tmp = self.temp_path();
+        let written = File::create_new(&tmp).and_then(|mut file| {
+            file.write_all(state.export().to_hex().as_bytes())?;
+            file.sync_all()
+        });
+        if let Err(e) = written.and_then(|_| fs::rename(&tmp, &self.path)) {
+            let _ = fs::remove_file(&tmp);
+            return Err(e.into());
+        }
+        // Make the rename itself durable.
+        #[cfg(unix)]
+        if let Some(dir) = self.path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            File::open(dir)?.sync_all()?;
+        }
+        Ok(())
+    }
+
+    // `<file>.<pid>.<random>.tmp` next to the snapshot, so concurrent saves, from this process
+    // or another, never write the same temporary file.
+    fn temp_path(&self) -> PathBuf {
+        let mut name = self
+            .path
+            .file_name()
+            .map(OsString::from)
+            .unwrap_or_default();
+        name.push(format!(
+            ".{}.{:016x}.tmp",
+            std::process::id(),
+            rand::random::<u64>()
+        ));
+        self.path.with_file_name(name)
+    }
+
+    // The saved snapshot, or `None` if nothing has been saved yet.
+    pub fn load(&self) -> Result<Option<Nano64>, Nano64Error> {
+        match fs::read_to_string(&self.path) {
+            Ok(contents) => Ok(Some(contents.trim().parse()?)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    // Imports the saved snapshot into `state`, skipping `margin_ms` milliseconds past it. IDs
+    // issued after the last `save` and before a crash are covered as long as saves happen at
+    // least every `margin_ms`; pass 0 if the state is always saved on shutdown.
+    pub fn restore(&self, state: &MonotonicState, margin_ms: u64) -> Result<(), Nano64Error> {
+        if let Some(last) = self.load()? {
+            let value = if margin_ms == 0 {
+                last
+            } else {
+                let timestamp = last.get_timestamp().saturating_add(margin_ms);
+                Nano64::from_parts(timestamp, RANDOM_MASK as u32)?
+            };
+            state.import(value);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{MonotonicFileStore, MonotonicState, Nano64Error};
+
+    fn fixed_rng(_bits: u32) -> Result<u32, Nano64Error> {
+        Ok(7)
+    }
+
+    #[test]
+    fn test_file_store_round_trip() {
+        let dir = std::env::temp_dir().join(format!("nano64-store-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let store = MonotonicFileStore::new(dir.join("monotonic.state"));
+        assert!(store.load().unwrap().is_none());
+
+        let state = MonotonicState::new();
+        let last = state.generate(5000, &mut fixed_rng).unwrap();
+        store.save(&state).unwrap();
+        assert_eq!(store.load().unwrap(), Some(last));
+
+        let restarted = MonotonicState::new();
+        store.restore(&restarted, 0).unwrap();
+        assert!(restarted.generate(4000, &mut fixed_rng).unwrap() > last);
+
+        let with_margin = MonotonicState::new();
+        store.restore(&with_margin, 100).unwrap();
+        assert_eq!(
+            with_margin
+                .generate(4000, &mut fixed_rng)
+                .unwrap()
+                .get_timestamp(),
+            5101
+        );
+
+        // Concurrent saves each use their own temporary file and leave none behind.
+        std::thread::scope(|scope| {
+            for _ in 0..8 {
+                scope.spawn(|| store.save(&state).unwrap());
+            }
+        });
+        assert_eq!(store.load().unwrap(), Some(last));
+        let leftovers: Vec<_> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(leftovers, ["monotonic.state"]);
+
+        std::fs::write(store.path(), "not an id").unwrap();
+        assert!(store.load().is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,44 @@
+// What monotonic generation does when a millisecond's random field runs out, i.e. the last ID
+// issued already has the maximum random value and the clock hasn't moved on.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ExhaustionPolicy {
+    // Keep counting into the next millisecond, so IDs may run ahead of the wall clock until it
+    // catches up. This is what `Nano64::generate_monotonic*` always does.
+    #[default]
+    BorrowNextMs,
+    // Busy-wait on the generator's clock until the next millisecond starts. IDs never carry a
+    // timestamp ahead of the clock, at the cost of stalling bursts.
+    SpinWaitForNextMs,
+    // Fail with `Nano64Error::RandomFieldExhausted` instead of borrowing.
+    Error,
+}
+
+// What a generator does when its clock reads earlier than a reading it has already used, e.g.
+// after an NTP step or a VM migration.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ClockBackwardsPolicy {
+    // Keep using the latest timestamp seen until the clock catches up. IDs stay in timestamp
+    // order; monotonic IDs keep counting up from the last one.
+    #[default]
+    ClampToLast,
+    // Fail with `Nano64Error::ClockMovedBackwards`.
+    Error,
+    // Sleep until the clock is back at the latest timestamp seen.
+    WaitUntilCaughtUp,
+}
+
+// A clock reading that went backwards, passed to the callback installed with
+// `Nano64Generator::with_clock_anomaly_callback`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ClockAnomaly {
+    // Latest timestamp the generator had seen.
+    pub last_ms: u64,
+    // The reading that was behind it.
+    pub now_ms: u64,
+}
+
+impl ClockAnomaly {
+    pub fn behind_by_ms(&self) -> u64 {
+        self.last_ms.saturating_sub(self.now_ms)
+    }
+}
@@ -0,0 +1,210 @@
+use crate::{
+    ClockImpl, Nano64, Nano64Error, RandomNumberGeneratorImpl, default_rng, time_now_since_epoch_ms,
+};
+use std::{
+    collections::{HashMap, VecDeque},
+    hash::Hash,
+    sync::Mutex,
+};
+
+// Per-key issuance quota for `QuotaedGenerator`: at most `limit` IDs in any `window_ms` window.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct QuotaOptions {
+    pub limit: u64,
+    pub window_ms: u64,
+}
+
+// Generator that meters issuance per caller-supplied key (tenant, API client, ...) over a
+// sliding window, and returns `Nano64Error::QuotaExceeded` once a key is over its quota.
+pub struct QuotaedGenerator<K> {
+    options: QuotaOptions,
+    clock: ClockImpl,
+    rng: RandomNumberGeneratorImpl,
+    issued: Mutex<HashMap<K, VecDeque<u64>>>,
+}
+
+impl<K: Hash + Eq + Clone> QuotaedGenerator<K> {
+    pub fn new(
+        options: QuotaOptions,
+        clock: Option<ClockImpl>,
+        rng: Option<RandomNumberGeneratorImpl>,
+    ) -> Result<Self, Nano64Error> {
+        if options.limit == 0 || options.window_ms == 0 {
+            return Err(Nano64Error::Error(
+                "quota limit and window_ms must be greater than zero".into(),
+            ));
+        }
+
+        let rng = if let Some(_rng) = rng {
+            _rng
+        } else {
+            default_rng
+        };
+
+        let clock = if let Some(_clock) = clock {
+            _clock
+        } else {
+            time_now_since_epoch_ms
+        };
+
+        Ok(Self {
+            options,
+            clock,
+            rng,
+            issued: Mutex::new(HashMap::new()),
+        })
+    }
+
+    // IDs that fail to generate don't count against the quota.
+    pub fn generate(&self, key: &K) -> Result<Nano64, Nano64Error> {
+        let now = self.reserve(key)?;
+        Nano64::generate(now, Some(self.rng)).inspect_err(|_| self.release(key, now))
+    }
+
+    pub fn generate_monotonic(&self, key: &K) -> Result<Nano64, Nano64Error> {
+        let now = self.reserve(key)?;
+        Nano64::generate_monotonic(now, Some(self.rng)).inspect_err(|_| self.release(key, now))
+    }
+
+    // How many more IDs `key` may be issued right now.
+    pub fn remaining(&self, key: &K) -> u64 {
+        let now = (self.clock)();
+        let mut issued = self.issued.lock().unwrap_or_else(|e| e.into_inner());
+        match issued.get_mut(key) {
+            Some(window) => {
+                self.evict(window, now);
+                self.options.limit.saturating_sub(window.len() as u64)
+            }
+            None => self.options.limit,
+        }
+    }
+
+    // Drops bookkeeping for keys with nothing left in their window.
+    pub fn prune(&self) {
+        let now = (self.clock)();
+        let mut issued = self.issued.lock().unwrap_or_else(|e| e.into_inner());
+        issued.retain(|_, window| {
+            self.evict(window, now);
+            !window.is_empty()
+        });
+    }
+
+    // Records an issuance for `key` if it is within quota, returning the timestamp to use.
+    fn reserve(&self, key: &K) -> Result<u64, Nano64Error> {
+        let now = (self.clock)();
+        let mut issued = self
+            .issued
+            .lock()
+            .map_err(|_| Nano64Error::Error("Error unlocking quota state".into()))?;
+
+        let window = issued.entry(key.clone()).or_default();
+        self.evict(window, now);
+
+        if window.len() as u64 >= self.options.limit {
+            let oldest = window.front().copied().unwrap_or(now);
+            return Err(Nano64Error::QuotaExceeded {
+                limit: self.options.limit,
+                window_ms: self.options.window_ms,
+                retry_after_ms: (oldest + self.options.window_ms).saturating_sub(now),
+            });
+        }
+
+        window.push_back(now);
+        Ok(now)
+    }
+
+    // Gives back an issuance `reserve` recorded at `now` whose ID was never issued.
+    fn release(&self, key: &K, now: u64) {
+        let mut issued = self.issued.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(window) = issued.get_mut(key)
+            && let Some(pos) = window.iter().rposition(|&ts| ts == now)
+        {
+            window.remove(pos);
+        }
+    }
+
+    fn evict(&self, window: &mut VecDeque<u64>, now: u64) {
+        while let Some(&ts) = window.front() {
+            if ts + self.options.window_ms > now {
+                break;
+            }
+            window.pop_front();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    use crate::{Nano64Error, QuotaOptions, QuotaedGenerator};
+
+    static NOW: AtomicU64 = AtomicU64::new(1_000_000);
+    fn mock_clock() -> u64 {
+        NOW.load(Ordering::SeqCst)
+    }
+
+    #[test]
+    fn test_quota_enforced_per_key_over_sliding_window() {
+        let options = QuotaOptions {
+            limit: 3,
+            window_ms: 1000,
+        };
+        let generator = QuotaedGenerator::<&str>::new(options, Some(mock_clock), None).unwrap();
+        for _ in 0..3 {
+            generator.generate(&"tenant-a").unwrap();
+        }
+
+        match generator.generate(&"tenant-a") {
+            Err(Nano64Error::QuotaExceeded {
+                limit,
+                window_ms,
+                retry_after_ms,
+            }) => {
+                assert_eq!(limit, 3);
+                assert_eq!(window_ms, 1000);
+                assert_eq!(retry_after_ms, 1000);
+            }
+            other => panic!("expected QuotaExceeded, got {other:?}"),
+        }
+
+        // Other keys are metered independently.
+        assert_eq!(generator.remaining(&"tenant-b"), 3);
+        generator.generate_monotonic(&"tenant-b").unwrap();
+        assert_eq!(generator.remaining(&"tenant-b"), 2);
+
+        // Once the window slides past the first issuances, tenant-a may mint again.
+        NOW.fetch_add(1000, Ordering::SeqCst);
+        assert_eq!(generator.remaining(&"tenant-a"), 3);
+        generator.generate(&"tenant-a").unwrap();
+
+        NOW.fetch_add(5000, Ordering::SeqCst);
+        generator.prune();
+        assert!(generator.issued.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_quota_not_spent_on_failed_generation() {
+        fn failing_rng(_bits: u32) -> Result<u32, Nano64Error> {
+            Err(Nano64Error::Error("rng failure".into()))
+        }
+
+        let options = QuotaOptions {
+            limit: 1,
+            window_ms: 60_000,
+        };
+        let generator = QuotaedGenerator::<u32>::new(options, None, Some(failing_rng)).unwrap();
+        assert!(generator.generate(&1).is_err());
+        assert!(generator.generate(&1).is_err());
+        assert_eq!(generator.remaining(&1), 1);
+    }
+
+    #[test]
+    fn test_quota_rejects_invalid_options() {
+        let options = QuotaOptions {
+            limit: 0,
+            window_ms: 1000,
+        };
+        assert!(QuotaedGenerator::<u32>::new(options, None, None).is_err());
+    }
+}
@@ -0,0 +1,65 @@
+use crate::Nano64Error;
+use rand::RngCore;
+
+// A source of random bits for ID generation. Unlike `RandomNumberGeneratorImpl`, a source may
+// carry state: a seeded RNG, a hardware RNG handle, or a closure capturing its environment.
+//
+// Any `FnMut(u32) -> Result<u32, Nano64Error>` is a source, including every
+// `RandomNumberGeneratorImpl`. Wrap a `rand::RngCore` in `RngSource` to use it.
+pub trait RandomSource: Send {
+    // Returns a value with `bits` (1-32) random low bits.
+    fn random_bits(&mut self, bits: u32) -> Result<u32, Nano64Error>;
+}
+
+impl<F> RandomSource for F
+where
+    F: FnMut(u32) -> Result<u32, Nano64Error> + Send,
+{
+    fn random_bits(&mut self, bits: u32) -> Result<u32, Nano64Error> {
+        self(bits)
+    }
+}
+
+// Adapts any `rand::RngCore` (e.g. a seeded `StdRng`) into a `RandomSource`.
+pub struct RngSource<R>(pub R);
+
+impl<R: RngCore + Send> RandomSource for RngSource<R> {
+    fn random_bits(&mut self, bits: u32) -> Result<u32, Nano64Error> {
+        if bits == 0 || bits > 32 {
+            return Err(Nano64Error::RNGOutOfBounds(bits));
+        }
+        Ok(self.0.next_u32() >> (32 - bits))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Nano64, Nano64Error, RandomSource, RngSource};
+    use rand::{SeedableRng, rngs::StdRng};
+
+    #[test]
+    fn test_rng_source_is_deterministic_when_seeded() {
+        let mut a = RngSource(StdRng::seed_from_u64(7));
+        let mut b = RngSource(StdRng::seed_from_u64(7));
+        for _ in 0..100 {
+            let x = a.random_bits(20).unwrap();
+            assert_eq!(x, b.random_bits(20).unwrap());
+            assert!(x < 1 << 20);
+        }
+        assert!(a.random_bits(0).is_err());
+        assert!(a.random_bits(33).is_err());
+    }
+
+    #[test]
+    fn test_stateful_closure_source() {
+        let mut counter = 0;
+        let mut source = move |_bits: u32| -> Result<u32, Nano64Error> {
+            counter += 1;
+            Ok(counter)
+        };
+        let a = Nano64::generate_with_source(1000, &mut source).unwrap();
+        let b = Nano64::generate_with_source(1000, &mut source).unwrap();
+        assert_eq!(a.get_random(), 1);
+        assert_eq!(b.get_random(), 2);
+    }
+}
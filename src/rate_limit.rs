@@ -0,0 +1,155 @@
+use crate::{Nano64, Nano64Error, Nano64Generator};
+use std::{sync::Mutex, thread, time::Duration};
+
+// Largest number of random IDs per millisecond that keeps the chance of any collision within
+// that millisecond under 1% (birthday bound over the 2^20 random values).
+pub const ONE_PERCENT_COLLISION_RATE_PER_MS: u64 = 145;
+
+// What `RateLimitedGenerator` does once the current millisecond's budget is spent.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RateLimitMode {
+    // Block the calling thread until the next millisecond.
+    #[default]
+    Sleep,
+    // Fail with `Nano64Error::QuotaExceeded` (with a 1ms window).
+    Error,
+}
+
+// Wraps a `Nano64Generator` and caps emission at `max_per_ms` IDs per millisecond of its clock,
+// e.g. `ONE_PERCENT_COLLISION_RATE_PER_MS`.
+pub struct RateLimitedGenerator {
+    inner: Nano64Generator,
+    max_per_ms: u64,
+    mode: RateLimitMode,
+    // (millisecond, IDs issued in it)
+    window: Mutex<(u64, u64)>,
+}
+
+impl RateLimitedGenerator {
+    pub fn new(inner: Nano64Generator, max_per_ms: u64) -> Result<Self, Nano64Error> {
+        if max_per_ms == 0 {
+            return Err(Nano64Error::Error(
+                "max_per_ms must be greater than zero".into(),
+            ));
+        }
+        Ok(Self {
+            inner,
+            max_per_ms,
+            mode: RateLimitMode::default(),
+            window: Mutex::new((0, 0)),
+        })
+    }
+
+    pub fn with_mode(mut self, mode: RateLimitMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    pub fn generate(&self) -> Result<Nano64, Nano64Error> {
+        let now = self.reserve()?;
+        self.inner.generate_at(now)
+    }
+
+    pub fn generate_monotonic(&self) -> Result<Nano64, Nano64Error> {
+        let now = self.reserve()?;
+        self.inner.generate_monotonic_at(now)
+    }
+
+    pub fn generator(&self) -> &Nano64Generator {
+        &self.inner
+    }
+
+    // Counts one ID against the current millisecond, waiting or failing if it is full, and
+    // returns the timestamp to use.
+    fn reserve(&self) -> Result<u64, Nano64Error> {
+        loop {
+            let now = self.inner.now_ms()?;
+            {
+                let mut window = self
+                    .window
+                    .lock()
+                    .map_err(|_| Nano64Error::Error("Error unlocking rate limit state".into()))?;
+                if window.0 != now {
+                    *window = (now, 0);
+                }
+                if window.1 < self.max_per_ms {
+                    window.1 += 1;
+                    return Ok(now);
+                }
+            }
+            match self.mode {
+                RateLimitMode::Sleep => thread::sleep(Duration::from_millis(1)),
+                RateLimitMode::Error => {
+                    return Err(Nano64Error::QuotaExceeded {
+                        limit: self.max_per_ms,
+                        window_ms: 1,
+                        retry_after_ms: 1,
+                    });
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Nano64Error, Nano64Generator, ONE_PERCENT_COLLISION_RATE_PER_MS, RateLimitMode,
+        RateLimitedGenerator,
+        testing::{MockClock, MockRng},
+    };
+    use std::{thread, time::Duration};
+
+    fn generator(clock: &MockClock) -> Nano64Generator {
+        Nano64Generator::new()
+            .with_clock(clock.clone())
+            .with_rng(MockRng::new([1, 2, 3]))
+    }
+
+    // Exact chance that `n` uniformly random 20-bit values aren't all distinct.
+    fn collision_probability(n: u64) -> f64 {
+        let space = (1u64 << 20) as f64;
+        1.0 - (0..n).map(|i| 1.0 - i as f64 / space).product::<f64>()
+    }
+
+    #[test]
+    fn test_one_percent_collision_rate() {
+        let n = ONE_PERCENT_COLLISION_RATE_PER_MS;
+        assert!(collision_probability(n) < 0.01);
+        assert!(collision_probability(n + 1) >= 0.01);
+        assert!((collision_probability(145) - 0.009907).abs() < 1e-5);
+        assert!((collision_probability(146) - 0.010044).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_rate_limited_generator_errors_over_limit() {
+        let clock = MockClock::new(1000);
+        let limited = RateLimitedGenerator::new(generator(&clock), 2)
+            .unwrap()
+            .with_mode(RateLimitMode::Error);
+        limited.generate().unwrap();
+        limited.generate_monotonic().unwrap();
+        assert!(matches!(
+            limited.generate(),
+            Err(Nano64Error::QuotaExceeded {
+                limit: 2,
+                window_ms: 1,
+                ..
+            })
+        ));
+        clock.advance(1);
+        assert_eq!(limited.generate().unwrap().get_timestamp(), 1001);
+        assert!(RateLimitedGenerator::new(Nano64Generator::new(), 0).is_err());
+    }
+
+    #[test]
+    fn test_rate_limited_generator_sleeps_until_next_ms() {
+        let clock = MockClock::new(1000);
+        let limited = RateLimitedGenerator::new(generator(&clock), 1).unwrap();
+        limited.generate().unwrap();
+        let waiter = thread::spawn(move || limited.generate().unwrap());
+        thread::sleep(Duration::from_millis(20));
+        clock.advance(1);
+        assert_eq!(waiter.join().unwrap().get_timestamp(), 1001);
+    }
+}
@@ -0,0 +1,143 @@
+use crate::{Nano64, Nano128};
+use serde::{
+    Deserialize, Deserializer, Serialize, Serializer,
+    de::{self, Visitor},
+};
+use std::fmt;
+
+// Human-readable formats (JSON, TOML, ...) get the canonical hex string, binary formats
+// (bincode, MessagePack, ...) get the raw u64.
+impl Serialize for Nano64 {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(&self.to_hex())
+        } else {
+            serializer.serialize_u64(self.value)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Nano64 {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_str(Nano64Visitor)
+        } else {
+            deserializer.deserialize_u64(Nano64Visitor)
+        }
+    }
+}
+
+pub(crate) struct Nano64Visitor;
+
+impl<'de> Visitor<'de> for Nano64Visitor {
+    type Value = Nano64;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a Nano64 hex string, u64 or 8 big-endian bytes")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Nano64, E> {
+        v.parse::<Nano64>().map_err(E::custom)
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Nano64, E> {
+        Ok(Nano64::new(v))
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Nano64, E> {
+        let bytes: [u8; 8] = v
+            .try_into()
+            .map_err(|_| E::invalid_length(v.len(), &self))?;
+        Ok(Nano64::from(bytes))
+    }
+}
+
+// Same scheme as `Nano64`: the canonical hex string for human-readable formats, the raw u128
+// otherwise.
+impl Serialize for Nano128 {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(&self.to_hex())
+        } else {
+            serializer.serialize_u128(self.value)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Nano128 {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_str(Nano128Visitor)
+        } else {
+            deserializer.deserialize_u128(Nano128Visitor)
+        }
+    }
+}
+
+struct Nano128Visitor;
+
+impl<'de> Visitor<'de> for Nano128Visitor {
+    type Value = Nano128;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a Nano128 hex string, u128 or 16 big-endian bytes")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Nano128, E> {
+        v.parse::<Nano128>().map_err(E::custom)
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Nano128, E> {
+        Ok(Nano128::new(v as u128))
+    }
+
+    fn visit_u128<E: de::Error>(self, v: u128) -> Result<Nano128, E> {
+        Ok(Nano128::new(v))
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Nano128, E> {
+        Nano128::try_from(v).map_err(|_| E::invalid_length(v.len(), &self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Nano64;
+    use serde_test::{Configure, Token, assert_de_tokens, assert_tokens};
+
+    #[test]
+    fn test_serde_json_uses_hex() {
+        let id = Nano64::new(0x0123456789ABCDEF);
+        let json = serde_json::to_string(&id).unwrap();
+        assert_eq!(json, "\"0123456789A-BCDEF\"");
+        let back: Nano64 = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, id);
+        assert!(serde_json::from_str::<Nano64>("\"not-hex\"").is_err());
+    }
+
+    #[test]
+    fn test_serde_compact_uses_u64() {
+        let id = Nano64::new(0x0123456789ABCDEF);
+        assert_tokens(&id.compact(), &[Token::U64(0x0123456789ABCDEF)]);
+        assert_tokens(&id.readable(), &[Token::Str("0123456789A-BCDEF")]);
+        assert_de_tokens(
+            &id.compact(),
+            &[Token::Bytes(&[
+                0x01, 0x23, 0x45, 0x67, 0x89, 0xAB, 0xCD, 0xEF,
+            ])],
+        );
+    }
+
+    #[test]
+    fn test_serde_nano128() {
+        use crate::Nano128;
+
+        let id = Nano128::new(0x0123456789ABCDEF_FEDCBA9876543210);
+        let json = serde_json::to_string(&id).unwrap();
+        assert_eq!(json, "\"0123456789A-BCDEFFEDCBA9876543210\"");
+        assert_eq!(serde_json::from_str::<Nano128>(&json).unwrap(), id);
+        assert_de_tokens(&Nano128::new(42).compact(), &[Token::U64(42)]);
+        const BYTES: [u8; 16] = 0x0123456789ABCDEF_FEDCBA9876543210u128.to_be_bytes();
+        assert_de_tokens(&id.compact(), &[Token::Bytes(&BYTES)]);
+    }
+}
@@ -0,0 +1,58 @@
+// Always (de)serializes a `Nano64` as its hex string, regardless of format. Use it with
+// `#[serde(with = "nano64::serde_string")]` when the consumer can't hold a full u64, e.g.
+// JavaScript numbers lose precision above 2^53. Human-readable formats, which describe their own
+// types, also accept integers on deserialize; binary formats may not, so they only read strings.
+use crate::{Nano64, serde_impl::Nano64Visitor};
+use serde::{Deserializer, Serializer};
+
+pub fn serialize<S: Serializer>(id: &Nano64, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&id.to_hex())
+}
+
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Nano64, D::Error> {
+    if deserializer.is_human_readable() {
+        deserializer.deserialize_any(Nano64Visitor)
+    } else {
+        deserializer.deserialize_str(Nano64Visitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Nano64;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Wrapper {
+        #[serde(with = "crate::serde_string")]
+        id: Nano64,
+    }
+
+    #[test]
+    fn test_serde_string_round_trip() {
+        let w = Wrapper {
+            id: Nano64::new(u64::MAX - 1),
+        };
+        let json = serde_json::to_string(&w).unwrap();
+        assert_eq!(json, "{\"id\":\"FFFFFFFFFFF-FFFFE\"}");
+        assert_eq!(serde_json::from_str::<Wrapper>(&json).unwrap(), w);
+    }
+
+    #[test]
+    fn test_serde_string_accepts_integer() {
+        let w: Wrapper = serde_json::from_str("{\"id\":12345}").unwrap();
+        assert_eq!(w.id.u64_value(), 12345);
+        assert!(serde_json::from_str::<Wrapper>("{\"id\":true}").is_err());
+    }
+
+    #[test]
+    fn test_serde_string_bincode_round_trip() {
+        let w = Wrapper {
+            id: Nano64::new(u64::MAX - 1),
+        };
+        let bytes = bincode::serialize(&w).unwrap();
+        // A length-prefixed string, not the 8-byte integer.
+        assert_eq!(bytes.len(), 8 + "FFFFFFFFFFF-FFFFE".len());
+        assert_eq!(bincode::deserialize::<Wrapper>(&bytes).unwrap(), w);
+    }
+}
@@ -0,0 +1,107 @@
+use crate::{IdGenerator, MonotonicBlock, Nano64, Nano64Error, Nano64Generator};
+use std::{
+    sync::mpsc::{self, Sender, SyncSender},
+    thread,
+};
+
+enum Request {
+    One(SyncSender<Result<Nano64, Nano64Error>>),
+    Lease(usize, SyncSender<Result<MonotonicBlock, Nano64Error>>),
+}
+
+// A dedicated thread that owns a `Nano64Generator` and hands out monotonic IDs, singly or in
+// leases of `n`, over a channel. Every ID comes from one thread, so IDs are strictly increasing
+// across the whole process without callers contending on the generator.
+//
+// Clones are handles to the same service. The thread exits once every handle is dropped.
+#[derive(Clone)]
+pub struct Nano64Service {
+    requests: Sender<Request>,
+}
+
+impl Nano64Service {
+    pub fn spawn(generator: Nano64Generator) -> Result<Self, Nano64Error> {
+        let (requests, inbox) = mpsc::channel::<Request>();
+        thread::Builder::new()
+            .name("nano64-service".into())
+            .spawn(move || {
+                // Replies fail only if the caller stopped waiting, which is fine to ignore.
+                for request in inbox {
+                    match request {
+                        Request::One(reply) => {
+                            let _ = reply.send(generator.generate_monotonic());
+                        }
+                        Request::Lease(n, reply) => {
+                            let _ = reply.send(generator.generate_monotonic_block(n));
+                        }
+                    }
+                }
+            })
+            .map_err(Nano64Error::Io)?;
+        Ok(Self { requests })
+    }
+
+    pub fn next_id(&self) -> Result<Nano64, Nano64Error> {
+        let (reply, response) = mpsc::sync_channel(1);
+        self.call(Request::One(reply), response)
+    }
+
+    // Reserves `n` consecutive IDs in one round trip.
+    pub fn lease(&self, n: usize) -> Result<MonotonicBlock, Nano64Error> {
+        let (reply, response) = mpsc::sync_channel(1);
+        self.call(Request::Lease(n, reply), response)
+    }
+
+    fn call<T>(
+        &self,
+        request: Request,
+        response: mpsc::Receiver<Result<T, Nano64Error>>,
+    ) -> Result<T, Nano64Error> {
+        let stopped = || Nano64Error::Error("ID service has stopped".into());
+        self.requests.send(request).map_err(|_| stopped())?;
+        response.recv().map_err(|_| stopped())?
+    }
+}
+
+impl IdGenerator for Nano64Service {
+    fn next_id(&self) -> Result<Nano64, Nano64Error> {
+        Nano64Service::next_id(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Nano64, Nano64Generator, Nano64Service};
+    use std::thread;
+
+    #[test]
+    fn test_service_is_strictly_monotonic_across_threads() {
+        let service = Nano64Service::spawn(Nano64Generator::new()).unwrap();
+        let handles: Vec<_> = (0..8)
+            .map(|i| {
+                let service = service.clone();
+                thread::spawn(move || {
+                    let mut ids = Vec::new();
+                    for _ in 0..200 {
+                        if i % 2 == 0 {
+                            ids.push(service.next_id().unwrap());
+                        } else {
+                            ids.extend(service.lease(3).unwrap());
+                        }
+                    }
+                    ids
+                })
+            })
+            .collect();
+        let mut all: Vec<Nano64> = handles
+            .into_iter()
+            .flat_map(|h| h.join().unwrap())
+            .collect();
+        let count = all.len();
+        assert_eq!(count, 4 * 200 + 4 * 200 * 3);
+        all.sort();
+        all.dedup();
+        assert_eq!(all.len(), count);
+        assert!(service.next_id().unwrap() > *all.last().unwrap());
+    }
+}
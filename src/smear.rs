@@ -0,0 +1,43 @@
+use std::sync::{Arc, Mutex, OnceLock};
+
+// Options for smeared generation.
+//
+// Once more than `burst_threshold` IDs have been requested for the same millisecond, further
+// IDs for that millisecond are pushed forward by 1..=`window_ms` milliseconds. This spreads a
+// burst over `window_ms + 1` milliseconds worth of random space, at the cost of IDs within the
+// window only being approximately (not strictly) time ordered.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SmearOptions {
+    // Maximum number of milliseconds an ID may be pushed into the future.
+    pub window_ms: u64,
+    // Number of IDs allowed in a single millisecond before smearing kicks in.
+    pub burst_threshold: u64,
+}
+
+impl Default for SmearOptions {
+    // Defaults to the ~1% collision threshold of 145 IDs/ms and a 4ms window.
+    fn default() -> Self {
+        Self {
+            window_ms: 3,
+            burst_threshold: 145,
+        }
+    }
+}
+
+pub(crate) struct SmearRefs {
+    pub(crate) timestamp: u64,
+    pub(crate) count: u64,
+}
+
+pub(crate) static SMEAR_REFS: OnceLock<Arc<Mutex<SmearRefs>>> = OnceLock::new();
+
+pub(crate) fn get_smear_refs() -> Arc<Mutex<SmearRefs>> {
+    SMEAR_REFS
+        .get_or_init(|| {
+            Arc::new(Mutex::new(SmearRefs {
+                timestamp: 0,
+                count: 0,
+            }))
+        })
+        .clone()
+}
@@ -0,0 +1,272 @@
+// Deterministic clocks, RNGs and monotonic-state helpers for tests. Enable the `testing`
+// feature (typically as a dev-dependency) to use them from downstream crates.
+use crate::{
+    Clock, IdGenerator, Nano64, Nano64Error, RandomSource,
+    monotonic_refs::{MonotonicRefs, THREAD_MONOTONIC_REFS, get_monotonic_state},
+};
+use std::sync::{
+    Arc, Mutex, MutexGuard,
+    atomic::{AtomicU64, AtomicUsize, Ordering},
+};
+
+// A clock that only moves when told to. Clones share the same time, so keep one handle in the
+// test and pass another to the generator.
+#[derive(Clone, Debug, Default)]
+pub struct MockClock {
+    now: Arc<AtomicU64>,
+}
+
+impl MockClock {
+    pub fn new(now_ms: u64) -> Self {
+        Self {
+            now: Arc::new(AtomicU64::new(now_ms)),
+        }
+    }
+
+    pub fn set(&self, now_ms: u64) {
+        self.now.store(now_ms, Ordering::SeqCst);
+    }
+
+    pub fn advance(&self, ms: u64) {
+        self.now.fetch_add(ms, Ordering::SeqCst);
+    }
+}
+
+impl Clock for MockClock {
+    fn now_ms(&self) -> u64 {
+        self.now.load(Ordering::SeqCst)
+    }
+}
+
+#[derive(Debug)]
+struct MockRngState {
+    values: Vec<u32>,
+    next: usize,
+    calls: usize,
+}
+
+// An RNG that returns scripted values in order, starting over after the last one. Each value is
+// masked to the requested number of bits. With no values scripted, every call fails, which is
+// handy for exercising error paths. Clones share the script and the call count.
+#[derive(Clone, Debug)]
+pub struct MockRng {
+    state: Arc<Mutex<MockRngState>>,
+}
+
+impl MockRng {
+    pub fn new(values: impl Into<Vec<u32>>) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(MockRngState {
+                values: values.into(),
+                next: 0,
+                calls: 0,
+            })),
+        }
+    }
+
+    pub fn constant(value: u32) -> Self {
+        Self::new([value])
+    }
+
+    pub fn failing() -> Self {
+        Self::new([])
+    }
+
+    // Number of times random bits were requested, including failed requests.
+    pub fn calls(&self) -> usize {
+        self.lock().calls
+    }
+
+    fn lock(&self) -> MutexGuard<'_, MockRngState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl RandomSource for MockRng {
+    fn random_bits(&mut self, bits: u32) -> Result<u32, Nano64Error> {
+        let mut state = self.lock();
+        state.calls += 1;
+        if bits == 0 || bits > 32 {
+            return Err(Nano64Error::RNGOutOfBounds(bits));
+        }
+        if state.values.is_empty() {
+            return Err(Nano64Error::Error("MockRng has no scripted values".into()));
+        }
+        let value = state.values[state.next];
+        state.next = (state.next + 1) % state.values.len();
+        Ok(if bits < 32 {
+            value & ((1 << bits) - 1)
+        } else {
+            value
+        })
+    }
+}
+
+// An `IdGenerator` that hands out a fixed list of IDs in order, so tests can assert exactly
+// which ID a record got. Once the list runs out it fails, or with `wrapping` starts over.
+// Clones share the position.
+#[derive(Clone, Debug)]
+pub struct SequenceGenerator {
+    ids: Arc<[Nano64]>,
+    next: Arc<AtomicUsize>,
+    wrap: bool,
+}
+
+impl SequenceGenerator {
+    pub fn new(ids: impl Into<Vec<Nano64>>) -> Self {
+        Self {
+            ids: ids.into().into(),
+            next: Arc::new(AtomicUsize::new(0)),
+            wrap: false,
+        }
+    }
+
+    pub fn wrapping(mut self) -> Self {
+        self.wrap = true;
+        self
+    }
+
+    // IDs left before the sequence is exhausted (or wraps).
+    pub fn remaining(&self) -> usize {
+        self.ids
+            .len()
+            .saturating_sub(self.next.load(Ordering::SeqCst))
+    }
+}
+
+impl IdGenerator for SequenceGenerator {
+    fn next_id(&self) -> Result<Nano64, Nano64Error> {
+        let len = self.ids.len();
+        let index = if self.wrap && len > 0 {
+            self.next
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |i| Some((i + 1) % len))
+                .unwrap_or_default()
+        } else {
+            self.next
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |i| {
+                    (i < len).then_some(i + 1)
+                })
+                .map_err(|_| {
+                    Nano64Error::Error(format!("SequenceGenerator exhausted after {len} IDs"))
+                })?
+        };
+        Ok(self.ids[index])
+    }
+}
+
+static MONOTONIC_TEST_LOCK: Mutex<()> = Mutex::new(());
+
+// Exclusive access to the process-wide monotonic state used by `Nano64::generate_monotonic*`.
+// Tests run concurrently by default, so any test that resets or inspects that state should hold
+// this guard. The state is reset when the guard is taken and again when it is dropped.
+pub struct MonotonicStateGuard {
+    _lock: MutexGuard<'static, ()>,
+}
+
+impl MonotonicStateGuard {
+    // Makes the process-wide sequence continue as if `timestamp`/`random` was the last ID issued.
+    pub fn set_last(&self, timestamp: u64, random: u64) {
+        get_monotonic_state().set_last(timestamp, random);
+    }
+}
+
+impl Drop for MonotonicStateGuard {
+    fn drop(&mut self) {
+        reset_monotonic_state();
+    }
+}
+
+// Blocks until no other test holds the monotonic state, then resets it. A test that panicked
+// while holding the guard does not poison it for the rest.
+pub fn lock_monotonic_state() -> MonotonicStateGuard {
+    let lock = MONOTONIC_TEST_LOCK
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    reset_monotonic_state();
+    MonotonicStateGuard { _lock: lock }
+}
+
+// Forgets the last ID of the process-wide monotonic sequence and of the calling thread's
+// thread-local sequence. Prefer `lock_monotonic_state` in tests that run in parallel.
+pub fn reset_monotonic_state() {
+    get_monotonic_state().set_last(0, 0);
+    THREAD_MONOTONIC_REFS.with_borrow_mut(|refs| {
+        *refs = MonotonicRefs {
+            last_timestamp: 0,
+            last_random: 0,
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Nano64, Nano64Error, Nano64Generator, RandomSource,
+        testing::{MockClock, MockRng, lock_monotonic_state},
+    };
+
+    #[test]
+    fn test_mock_clock_set_and_advance() {
+        let clock = MockClock::new(1000);
+        let generator = Nano64Generator::new()
+            .with_clock(clock.clone())
+            .with_rng(MockRng::constant(7));
+        assert_eq!(generator.generate().unwrap().get_timestamp(), 1000);
+        clock.advance(5);
+        assert_eq!(generator.generate().unwrap().get_timestamp(), 1005);
+        clock.set(2000);
+        assert_eq!(generator.generate().unwrap().get_timestamp(), 2000);
+    }
+
+    #[test]
+    fn test_mock_rng_scripted_values() {
+        let mut rng = MockRng::new([1, 2, u32::MAX]);
+        let handle = rng.clone();
+        assert_eq!(rng.random_bits(20).unwrap(), 1);
+        assert_eq!(rng.random_bits(20).unwrap(), 2);
+        assert_eq!(rng.random_bits(20).unwrap(), 0xFFFFF);
+        assert_eq!(rng.random_bits(32).unwrap(), 1);
+        assert!(matches!(
+            rng.random_bits(33),
+            Err(Nano64Error::RNGOutOfBounds(33))
+        ));
+        assert_eq!(handle.calls(), 5);
+
+        let mut failing = MockRng::failing();
+        assert!(failing.random_bits(20).is_err());
+        assert!(Nano64::generate_with_source(1000, &mut failing).is_err());
+    }
+
+    #[test]
+    fn test_lock_monotonic_state_resets() {
+        let mut rng = MockRng::constant(0);
+        {
+            let guard = lock_monotonic_state();
+            guard.set_last(5000, 9);
+            let id = Nano64::generate_monotonic_with_source(5000, &mut rng).unwrap();
+            assert_eq!(id.get_random(), 10);
+        }
+        let _guard = lock_monotonic_state();
+        let id = Nano64::generate_monotonic_with_source(5000, &mut rng).unwrap();
+        assert_eq!(id.get_random(), 0);
+    }
+
+    #[test]
+    fn test_sequence_generator() {
+        use crate::{IdGenerator, testing::SequenceGenerator};
+
+        let ids = [Nano64::new(1), Nano64::new(2)];
+        let generator = SequenceGenerator::new(ids);
+        let shared = generator.clone();
+        assert_eq!(generator.next_id().unwrap(), ids[0]);
+        assert_eq!(shared.remaining(), 1);
+        assert_eq!(shared.next_id().unwrap(), ids[1]);
+        assert!(generator.next_id().is_err());
+        assert!(generator.next_id().is_err());
+
+        let wrapping = SequenceGenerator::new(ids).wrapping();
+        let got: Vec<Nano64> = (0..5).map(|_| wrapping.next_id().unwrap()).collect();
+        assert_eq!(got, [ids[0], ids[1], ids[0], ids[1], ids[0]]);
+        assert!(SequenceGenerator::new([]).wrapping().next_id().is_err());
+    }
+}
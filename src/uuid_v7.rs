@@ -0,0 +1,115 @@
+use crate::{MAX_TIMESTAMP, Nano64, Nano64Error, RANDOM_MASK, TIMESTAMP_SHIFT};
+use uuid::Uuid;
+
+// Embeds a Nano64 in a UUIDv7-shaped value (RFC 9562):
+//
+//   48 bits unix_ts_ms | 4 bits version (7) | 12 bits rand_a | 2 bits variant | 62 bits rand_b
+//
+// The timestamp goes into unix_ts_ms unchanged, the top 12 random bits fill rand_a and the low
+// 8 random bits lead rand_b. The remaining 54 bits of rand_b are zero, which is what
+// `try_from_uuid` checks to make the round trip lossless.
+const RAND_B_BITS: u32 = 62;
+const RAND_B_SPARE_BITS: u32 = RAND_B_BITS - 8;
+const VERSION_MASK: u128 = 0xF << 76;
+const VARIANT_MASK: u128 = 0b11 << 62;
+const VERSION_7: u128 = 0x7 << 76;
+const VARIANT_RFC: u128 = 0b10 << 62;
+
+impl Nano64 {
+    pub fn to_uuid(&self) -> Uuid {
+        let timestamp = (self.value >> TIMESTAMP_SHIFT) as u128;
+        let random = (self.value & RANDOM_MASK) as u128;
+        let rand_a = random >> 8;
+        let rand_b_top = random & 0xFF;
+        let bits = (timestamp << 80)
+            | VERSION_7
+            | (rand_a << 64)
+            | VARIANT_RFC
+            | (rand_b_top << RAND_B_SPARE_BITS);
+        Uuid::from_u128(bits)
+    }
+
+    // Only accepts UUIDs produced by `to_uuid`; a general UUIDv7 carries more random bits than
+    // a Nano64 can hold and is rejected instead of truncated.
+    pub fn try_from_uuid(uuid: &Uuid) -> Result<Self, Nano64Error> {
+        let bits = uuid.as_u128();
+        if bits & VERSION_MASK != VERSION_7 || bits & VARIANT_MASK != VARIANT_RFC {
+            return Err(Nano64Error::Error(format!("{uuid} is not a UUIDv7")));
+        }
+        let timestamp = (bits >> 80) as u64;
+        if timestamp > MAX_TIMESTAMP {
+            return Err(Nano64Error::TimeStampExceedsBitRange(timestamp));
+        }
+        if bits & ((1 << RAND_B_SPARE_BITS) - 1) != 0 {
+            return Err(Nano64Error::Error(format!(
+                "{uuid} has random bits a Nano64 cannot carry"
+            )));
+        }
+        let rand_a = ((bits >> 64) & 0xFFF) as u64;
+        let rand_b_top = ((bits >> RAND_B_SPARE_BITS) & 0xFF) as u64;
+        Ok(Self {
+            value: (timestamp << TIMESTAMP_SHIFT) | (rand_a << 8) | rand_b_top,
+        })
+    }
+}
+
+impl From<Nano64> for Uuid {
+    fn from(id: Nano64) -> Self {
+        id.to_uuid()
+    }
+}
+
+impl TryFrom<Uuid> for Nano64 {
+    type Error = Nano64Error;
+
+    fn try_from(uuid: Uuid) -> Result<Self, Self::Error> {
+        Nano64::try_from_uuid(&uuid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Nano64;
+    use uuid::{Uuid, Variant};
+
+    #[test]
+    fn test_uuid_round_trip() {
+        for _ in 0..1000 {
+            let id = Nano64::generate_default().unwrap();
+            let uuid = id.to_uuid();
+            assert_eq!(uuid.get_version_num(), 7);
+            assert_eq!(uuid.get_variant(), Variant::RFC4122);
+            assert_eq!(Nano64::try_from_uuid(&uuid).unwrap(), id);
+        }
+        let id = Nano64::from_parts(0x0123_4567_89AB, 0xFEDCB).unwrap();
+        assert_eq!(
+            id.to_uuid().to_string(),
+            "01234567-89ab-7fed-b2c0-000000000000"
+        );
+        assert_eq!(
+            Nano64::try_from(Uuid::from(Nano64::MAX)).unwrap(),
+            Nano64::MAX
+        );
+    }
+
+    #[test]
+    fn test_uuid_preserves_order() {
+        let mut ids: Vec<Nano64> = (0..200)
+            .map(|_| Nano64::generate_default().unwrap())
+            .collect();
+        let mut uuids: Vec<Uuid> = ids.iter().map(Nano64::to_uuid).collect();
+        ids.sort();
+        uuids.sort();
+        assert_eq!(uuids, ids.iter().map(Nano64::to_uuid).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_uuid_rejects_foreign_values() {
+        let v4 = Uuid::from_u128(0x0123_4567_89ab_4def_8123_4567_89ab_cdef);
+        assert!(Nano64::try_from_uuid(&v4).is_err());
+        let full_v7 = Uuid::from_u128(0x0123_4567_89ab_7def_8123_4567_89ab_cdef);
+        assert!(Nano64::try_from_uuid(&full_v7).is_err());
+        let future_v7 = Uuid::from_u128(0xffff_ffff_ffff_7000_8000_0000_0000_0000);
+        assert!(Nano64::try_from_uuid(&future_v7).is_err());
+    }
+}
@@ -0,0 +1,91 @@
+use crate::{Epoch, Nano64, Nano64Error};
+
+// Plausibility bounds for `Nano64::validate`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ValidationPolicy {
+    // How far ahead of the local clock a timestamp may be, to allow for clock skew between hosts.
+    pub future_tolerance_ms: u64,
+    // Maximum age of a timestamp. `None` accepts any past timestamp.
+    pub max_age_ms: Option<u64>,
+    // Epoch the IDs were generated with. A mismatch usually shows up as a timestamp far in the
+    // future (or the past).
+    pub epoch: Epoch,
+}
+
+impl Default for ValidationPolicy {
+    // Defaults to one minute of skew, no age limit and the UNIX epoch.
+    fn default() -> Self {
+        Self {
+            future_tolerance_ms: 60_000,
+            max_age_ms: None,
+            epoch: Epoch::UNIX,
+        }
+    }
+}
+
+impl ValidationPolicy {
+    pub(crate) fn check(&self, id: &Nano64, now_ms: u64) -> Result<(), Nano64Error> {
+        let timestamp = id.get_timestamp();
+        let unix_ms = self.epoch.to_unix_ms(timestamp);
+        let ahead_by = unix_ms.saturating_sub(now_ms);
+        if ahead_by > self.future_tolerance_ms {
+            return Err(Nano64Error::TimeStampInFuture {
+                timestamp,
+                ahead_by_ms: ahead_by,
+            });
+        }
+        if let Some(max_age_ms) = self.max_age_ms {
+            let age = now_ms.saturating_sub(unix_ms);
+            if age > max_age_ms {
+                return Err(Nano64Error::TimeStampTooOld {
+                    timestamp,
+                    age_ms: age,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Nano64, Nano64Error, ValidationPolicy};
+
+    #[test]
+    fn test_validation_policy() {
+        let policy = ValidationPolicy {
+            future_tolerance_ms: 1_000,
+            max_age_ms: Some(10_000),
+            ..Default::default()
+        };
+        let now = 1_000_000;
+        let at = |ts| Nano64::from_parts(ts, 0).unwrap();
+
+        assert!(policy.check(&at(now), now).is_ok());
+        assert!(policy.check(&at(now + 1_000), now).is_ok());
+        assert!(policy.check(&at(now - 10_000), now).is_ok());
+        assert!(matches!(
+            policy.check(&at(now + 1_001), now),
+            Err(Nano64Error::TimeStampInFuture {
+                ahead_by_ms: 1_001,
+                ..
+            })
+        ));
+        assert!(matches!(
+            policy.check(&at(now - 10_001), now),
+            Err(Nano64Error::TimeStampTooOld { age_ms: 10_001, .. })
+        ));
+        assert!(ValidationPolicy::default().check(&at(0), now).is_ok());
+    }
+
+    #[test]
+    fn test_validate_rejects_forged_id() {
+        assert!(
+            Nano64::generate_default()
+                .unwrap()
+                .validate(&ValidationPolicy::default())
+                .is_ok()
+        );
+        assert!(Nano64::MAX.validate(&ValidationPolicy::default()).is_err());
+    }
+}
@@ -0,0 +1,146 @@
+use crate::{errors::*, wordlist::WORDS};
+
+// Number of words produced by `Words::encode`: 6 data words plus 1 checksum word.
+pub const WORD_COUNT: usize = 7;
+
+const DATA_WORDS: usize = WORD_COUNT - 1;
+const BITS_PER_WORD: u32 = 11;
+const WORD_MASK: u128 = (1 << BITS_PER_WORD) - 1;
+// 6 words carry 66 bits; the 2 spare low bits are always zero.
+const PADDING_BITS: u32 = DATA_WORDS as u32 * BITS_PER_WORD - 64;
+
+// Encodes 64-bit values as BIP-0039 English words for reading IDs aloud.
+//
+// The last word is a checksum over the value, so a misheard or mistyped word is almost always
+// rejected instead of silently decoding to a different ID.
+pub struct Words;
+
+impl Words {
+    pub fn encode(value: u64) -> String {
+        let bits = (value as u128) << PADDING_BITS;
+        let mut words = Vec::with_capacity(WORD_COUNT);
+        for i in (0..DATA_WORDS).rev() {
+            let index = (bits >> (i as u32 * BITS_PER_WORD)) & WORD_MASK;
+            words.push(WORDS[index as usize]);
+        }
+        words.push(WORDS[checksum(value)]);
+        words.join(" ")
+    }
+
+    // Words may be separated by whitespace or dashes, are case-insensitive, and may be
+    // abbreviated to their first four letters.
+    pub fn decode(words: &str) -> Result<u64, Nano64Error> {
+        let parts: Vec<&str> = words
+            .split(|c: char| c.is_whitespace() || c == '-')
+            .filter(|w| !w.is_empty())
+            .collect();
+
+        if parts.len() != WORD_COUNT {
+            return Err(Nano64Error::WordCountMismatch {
+                expected: WORD_COUNT,
+                got: parts.len(),
+            });
+        }
+
+        let indexes = parts
+            .iter()
+            .map(|w| word_index(w))
+            .collect::<Result<Vec<usize>, Nano64Error>>()?;
+
+        let mut bits: u128 = 0;
+        for index in &indexes[..DATA_WORDS] {
+            bits = (bits << BITS_PER_WORD) | *index as u128;
+        }
+
+        if bits & ((1 << PADDING_BITS) - 1) != 0 {
+            return Err(Nano64Error::WordChecksumMismatch);
+        }
+
+        let value = (bits >> PADDING_BITS) as u64;
+        if indexes[DATA_WORDS] != checksum(value) {
+            return Err(Nano64Error::WordChecksumMismatch);
+        }
+
+        Ok(value)
+    }
+}
+
+// FNV-1a over the big-endian bytes, folded to a word index.
+fn checksum(value: u64) -> usize {
+    let mut hash: u32 = 0x811C9DC5;
+    for byte in value.to_be_bytes() {
+        hash ^= byte as u32;
+        hash = hash.wrapping_mul(0x01000193);
+    }
+    (hash as u128 & WORD_MASK) as usize
+}
+
+fn word_index(word: &str) -> Result<usize, Nano64Error> {
+    let lower = word.to_ascii_lowercase();
+    if let Ok(index) = WORDS.binary_search(&lower.as_str()) {
+        return Ok(index);
+    }
+
+    // Every word is unique in its first four letters, so accept those as an abbreviation.
+    if lower.len() == 4 {
+        let start = WORDS.partition_point(|w| *w < lower.as_str());
+        if start < WORDS.len() && WORDS[start].starts_with(lower.as_str()) {
+            return Ok(start);
+        }
+    }
+
+    Err(Nano64Error::UnknownWord(word.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Nano64Error, WORD_COUNT, Words};
+
+    #[test]
+    fn test_words_roundtrip() {
+        for value in [0, 1, 0x123456789ABCDEF0, u64::MAX] {
+            let words = Words::encode(value);
+            assert_eq!(words.split(' ').count(), WORD_COUNT);
+            assert_eq!(Words::decode(&words).unwrap(), value);
+        }
+    }
+
+    #[test]
+    fn test_words_lenient_input() {
+        let words = Words::encode(0x199E4C62AD4DAEFC);
+        let shouty = words.to_uppercase().replace(' ', "-");
+        assert_eq!(Words::decode(&shouty).unwrap(), 0x199E4C62AD4DAEFC);
+        let abbreviated: Vec<String> = words
+            .split(' ')
+            .map(|w| w.chars().take(4).collect())
+            .collect();
+        assert_eq!(
+            Words::decode(&abbreviated.join("  ")).unwrap(),
+            0x199E4C62AD4DAEFC
+        );
+    }
+
+    #[test]
+    fn test_words_detects_substitution() {
+        let words = Words::encode(0x123456789ABCDEF0);
+        let mut parts: Vec<&str> = words.split(' ').collect();
+        parts[2] = if parts[2] == "zoo" { "zone" } else { "zoo" };
+        let err = Words::decode(&parts.join(" ")).unwrap_err();
+        assert!(matches!(err, Nano64Error::WordChecksumMismatch));
+    }
+
+    #[test]
+    fn test_words_errors() {
+        assert!(matches!(
+            Words::decode("abandon ability").unwrap_err(),
+            Nano64Error::WordCountMismatch {
+                expected: WORD_COUNT,
+                got: 2
+            }
+        ));
+        assert!(matches!(
+            Words::decode("abandon ability able about above absent notaword").unwrap_err(),
+            Nano64Error::UnknownWord(_)
+        ));
+    }
+}